@@ -0,0 +1,145 @@
+use crate::function::Function;
+use crate::parameter::ParameterType;
+use crate::{keccak256, AbiParserError, Parameter};
+
+use ethane_types::H256;
+use std::fmt;
+
+/// An ABI event instance.
+///
+/// Declared in Solidity as `event Name(...)` and emitted by `emit Name(...)`.
+/// The event name is available as the key to the respective event in the
+/// `HashMap` of the [`crate::Abi`] parser.
+pub struct Event {
+    pub inputs: Vec<EventParameter>,
+    pub anonymous: bool,
+}
+
+/// ABI event parameter type.
+///
+/// Same as a function parameter, but indexed parameters are stored in the
+/// topics of a log instead of its data.
+pub struct EventParameter {
+    pub name: String,
+    pub parameter_type: ParameterType,
+    pub indexed: bool,
+}
+
+impl Event {
+    /// Tries to parse a `.json` value into an [`Event`].
+    pub fn parse(raw_event: &serde_json::Value) -> Result<Self, AbiParserError> {
+        let parameters = Function::parse_parameters(&raw_event["inputs"])?;
+        let inputs = parameters
+            .into_iter()
+            .enumerate()
+            .map(|(i, parameter)| EventParameter {
+                name: parameter.name,
+                parameter_type: parameter.parameter_type,
+                indexed: raw_event["inputs"][i]["indexed"].as_bool().unwrap_or(false),
+            })
+            .collect();
+        Ok(Self {
+            inputs,
+            anonymous: raw_event["anonymous"].as_bool().unwrap_or(false),
+        })
+    }
+
+    /// Returns the first topic of the logs of this event, the Keccak hash of
+    /// its signature.
+    pub fn topic(&self, name: &str) -> H256 {
+        let signature = format!(
+            "{}({})",
+            name,
+            self.inputs
+                .iter()
+                .map(|input| input.parameter_type.as_abi_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+        H256::from(keccak256(signature.as_bytes()))
+    }
+
+    /// Decodes the topics without the signature and the data of a log into
+    /// the event parameters, in the order of their declaration.
+    ///
+    /// Indexed parameters of a dynamic, array or tuple type are only stored as
+    /// the hash of their value, so they are returned as
+    /// [`Parameter::FixedBytes`] with that hash.
+    pub(crate) fn decode(
+        &self,
+        topics: &[H256],
+        data: &[u8],
+    ) -> Result<Vec<Parameter>, AbiParserError> {
+        let indexed = self.inputs.iter().filter(|input| input.indexed).count();
+        if indexed != topics.len() {
+            return Err(AbiParserError::InvalidAbiEncoding(format!(
+                "Invalid number of topics. Expected {}, got {}",
+                indexed,
+                topics.len()
+            )));
+        }
+        let mut values = crate::parameter::decode_from(
+            self.inputs
+                .iter()
+                .filter(|input| !input.indexed)
+                .map(|input| &input.parameter_type),
+            data,
+        )?
+        .into_iter();
+        let mut topics = topics.iter();
+
+        let mut parameters = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            let parameter = if input.indexed {
+                // the number of topics was checked above
+                let topic = topics.next().unwrap();
+                match input.parameter_type {
+                    ParameterType::String
+                    | ParameterType::Bytes
+                    | ParameterType::Array(_)
+                    | ParameterType::FixedArray(_, _)
+                    | ParameterType::Tuple(_) => Parameter::FixedBytes(topic.as_bytes().to_vec()),
+                    ref parameter_type => crate::parameter::decode_from(
+                        std::iter::once(parameter_type),
+                        topic.as_bytes(),
+                    )?
+                    .remove(0),
+                }
+            } else {
+                values.next().ok_or_else(|| {
+                    AbiParserError::InvalidAbiEncoding("Missing event data".to_owned())
+                })?
+            };
+            parameters.push(parameter);
+        }
+        Ok(parameters)
+    }
+}
+
+/// A decoded event log.
+#[derive(Clone)]
+pub struct DecodedLog {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+}
+
+impl fmt::Display for DecodedLog {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}({})",
+            self.name,
+            self.parameters
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+impl fmt::Debug for DecodedLog {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "DecodedLog({})", self)
+    }
+}
@@ -33,20 +33,18 @@ impl Function {
     ///
     /// If the ABI file is properly formatted, both the function inputs and
     /// outputs can be parsed using this function.
-    fn parse_parameters(
+    pub(crate) fn parse_parameters(
         raw_func: &serde_json::Value,
     ) -> Result<Vec<FunctionParameter>, AbiParserError> {
         match raw_func {
             serde_json::Value::Array(parameters) => {
                 let mut result = Vec::new();
                 for parameter in parameters {
-                    let p_type = parameter["type"].as_str().ok_or_else(|| {
-                        AbiParserError::MissingData("Missing parameter type".to_owned())
-                    })?;
+                    let p_type = Self::parse_type(parameter)?;
                     let p_name = parameter["name"].as_str().ok_or_else(|| {
                         AbiParserError::MissingData("Missing parameter name".to_owned())
                     })?;
-                    let parameter_type = ParameterType::parse(p_type)?;
+                    let parameter_type = ParameterType::parse(&p_type)?;
                     result.push(FunctionParameter {
                         name: p_name.to_owned(),
                         parameter_type,
@@ -59,6 +57,33 @@ impl Function {
             )),
         }
     }
+
+    /// Returns the type of a `.json` parameter as an ABI string.
+    ///
+    /// Tuples are given as `tuple`, `tuple[]`, etc. in the `.json` file with
+    /// their types listed under `components`, so they are resolved to the
+    /// `(T1,T2,...)` notation.
+    fn parse_type(parameter: &serde_json::Value) -> Result<String, AbiParserError> {
+        let p_type = parameter["type"]
+            .as_str()
+            .ok_or_else(|| AbiParserError::MissingData("Missing parameter type".to_owned()))?;
+        if let Some(array_suffix) = p_type.strip_prefix("tuple") {
+            let components = match &parameter["components"] {
+                serde_json::Value::Array(components) => components
+                    .iter()
+                    .map(Self::parse_type)
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => {
+                    return Err(AbiParserError::MissingData(
+                        "Missing tuple components".to_owned(),
+                    ))
+                }
+            };
+            Ok(format!("({}){}", components.join(","), array_suffix))
+        } else {
+            Ok(p_type.to_owned())
+        }
+    }
 }
 
 /// ABI function parameter type.
@@ -136,4 +161,36 @@ mod test {
         assert_eq!(function.payable, Some(false));
         assert_eq!(function.state_mutability, Some(StateMutability::View));
     }
+
+    #[test]
+    fn parse_tuple_parameters() {
+        let json: serde_json::Value = serde_json::from_str(
+            r#"[
+                {
+                    "name": "calls",
+                    "type": "tuple[]",
+                    "components": [
+                        { "name": "target", "type": "address" },
+                        { "name": "allowFailure", "type": "bool" },
+                        { "name": "callData", "type": "bytes" }
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let parameters = Function::parse_parameters(&json).unwrap();
+        assert_eq!(
+            parameters[0].parameter_type,
+            ParameterType::Array(Box::new(ParameterType::Tuple(vec![
+                ParameterType::Address,
+                ParameterType::Bool,
+                ParameterType::Bytes,
+            ])))
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(r#"[{ "name": "call", "type": "tuple" }]"#).unwrap();
+        assert!(Function::parse_parameters(&json).is_err());
+    }
 }
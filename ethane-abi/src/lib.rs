@@ -2,22 +2,31 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
 
+use ethane_types::H256;
+
 use tiny_keccak::{Hasher, Keccak};
 
+mod event;
 mod function;
 mod parameter;
+mod revert;
 
+pub use event::{DecodedLog, Event, EventParameter};
 use function::Function;
 pub use function::StateMutability;
 pub use parameter::Parameter;
 use parameter::ParameterType;
+pub use revert::{CustomError, PanicCode, Revert, ERROR_SELECTOR, PANIC_SELECTOR};
 
 /// Parses a `.json` file containing ABI encoded Solidity functions.
 ///
 /// It stores the functions in a `HashMap` with the function name being the key
-/// and the parsed function the value.
+/// and the parsed function the value. Custom errors and events are stored the
+/// same way.
 pub struct Abi {
     pub functions: HashMap<String, Function>,
+    pub errors: HashMap<String, CustomError>,
+    pub events: HashMap<String, Event>,
 }
 
 impl Default for Abi {
@@ -32,6 +41,8 @@ impl Abi {
     pub fn new() -> Self {
         Self {
             functions: HashMap::new(),
+            errors: HashMap::new(),
+            events: HashMap::new(),
         }
     }
 
@@ -48,6 +59,23 @@ impl Abi {
                         "Function name is missing from ABI.".to_owned(),
                     ));
                 }
+            } else if abi[i]["type"] == "error" {
+                if let Some(name) = abi[i]["name"].as_str() {
+                    self.errors
+                        .insert(name.to_owned(), CustomError::parse(&abi[i])?);
+                } else {
+                    return Err(AbiParserError::MissingData(
+                        "Error name is missing from ABI.".to_owned(),
+                    ));
+                }
+            } else if abi[i]["type"] == "event" {
+                if let Some(name) = abi[i]["name"].as_str() {
+                    self.events.insert(name.to_owned(), Event::parse(&abi[i])?);
+                } else {
+                    return Err(AbiParserError::MissingData(
+                        "Event name is missing from ABI.".to_owned(),
+                    ));
+                }
             }
             i += 1;
         }
@@ -85,6 +113,13 @@ impl Abi {
         parameters: Vec<Parameter>,
     ) -> Result<Vec<u8>, AbiParserError> {
         if let Some(function) = self.functions.get(function_name) {
+            if function.inputs.len() != parameters.len() {
+                return Err(AbiParserError::InvalidAbiEncoding(format!(
+                    "Invalid number of parameters supplied. Expected {}, got {}",
+                    function.inputs.len(),
+                    parameters.len()
+                )));
+            }
             let mut abi_arguments = Vec::<String>::with_capacity(parameters.len());
             for (input, param) in function.inputs.iter().zip(parameters.iter()) {
                 if input.parameter_type.type_check(param) {
@@ -97,12 +132,7 @@ impl Abi {
                 }
             }
             let signature = format!("{}({})", function_name, abi_arguments.join(","));
-            let mut hasher = Keccak::v256();
-            hasher.update(signature.as_bytes());
-            // Take first 4 bytes of the Keccak hash
-            let mut out = [0_u8; 32];
-            hasher.finalize(&mut out);
-            let mut hash = out[0..4].to_vec();
+            let mut hash = selector(&signature).to_vec();
             // Append the encoded parameters to the hash
             parameter::encode_into(&mut hash, parameters);
             Ok(hash)
@@ -123,22 +153,102 @@ impl Abi {
         hash: &[u8],
     ) -> Result<Vec<Parameter>, AbiParserError> {
         if let Some(function) = self.functions.get(function_name) {
-            let mut start_index = 0;
-            let mut parameters = Vec::<Parameter>::with_capacity(function.outputs.len());
-            for output in &function.outputs {
-                let (parameter, i) =
-                    Parameter::decode(&output.parameter_type, &hash[start_index..]);
-                start_index += i;
-                parameters.push(parameter);
-            }
-
-            Ok(parameters)
+            parameter::decode_from(
+                function.outputs.iter().map(|output| &output.parameter_type),
+                hash,
+            )
         } else {
             Err(AbiParserError::MissingData(
                 "Function name not found in ABI".to_owned(),
             ))
         }
     }
+
+    /// Decodes the data returned by a reverted call into a [`Revert`] reason.
+    ///
+    /// Besides the builtin `Error(string)` and `Panic(uint256)` errors, the
+    /// custom errors declared in the parsed ABI are recognized as well. Data
+    /// that doesn't match any of them is returned as [`Revert::Unknown`].
+    pub fn decode_revert(&self, data: &[u8]) -> Result<Revert, AbiParserError> {
+        if data.len() < 4 {
+            return Ok(Revert::Unknown(data.to_vec()));
+        }
+        let (error_selector, arguments) = data.split_at(4);
+        if error_selector == ERROR_SELECTOR {
+            let reason = parameter::decode_from(&[ParameterType::String], arguments)?;
+            if let Some(Parameter::String(reason)) = reason.first() {
+                return Ok(Revert::Error(String::from_utf8_lossy(reason).into_owned()));
+            }
+        } else if error_selector == PANIC_SELECTOR {
+            let code = parameter::decode_from(&[ParameterType::Uint(256)], arguments)?;
+            if let Some(Parameter::Uint(code, _)) = code.first() {
+                if let Some(code) = PanicCode::parse(*code) {
+                    return Ok(Revert::Panic(code));
+                }
+            }
+        } else {
+            for (name, error) in &self.errors {
+                let signature = format!(
+                    "{}({})",
+                    name,
+                    error
+                        .inputs
+                        .iter()
+                        .map(|input| input.parameter_type.as_abi_string())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                );
+                if selector(&signature) == error_selector {
+                    let parameters = parameter::decode_from(
+                        error.inputs.iter().map(|input| &input.parameter_type),
+                        arguments,
+                    )?;
+                    return Ok(Revert::Custom {
+                        name: name.clone(),
+                        parameters,
+                    });
+                }
+            }
+        }
+
+        Ok(Revert::Unknown(data.to_vec()))
+    }
+
+    /// Decodes the topics and data of a log into a [`DecodedLog`].
+    ///
+    /// The event is identified by the first topic, the hash of its signature,
+    /// so anonymous events are not recognized.
+    pub fn decode_log(&self, topics: &[H256], data: &[u8]) -> Result<DecodedLog, AbiParserError> {
+        let (signature, indexed) = topics
+            .split_first()
+            .ok_or_else(|| AbiParserError::MissingData("Log has no topics".to_owned()))?;
+        for (name, event) in &self.events {
+            if !event.anonymous && event.topic(name) == *signature {
+                return Ok(DecodedLog {
+                    name: name.clone(),
+                    parameters: event.decode(indexed, data)?,
+                });
+            }
+        }
+        Err(AbiParserError::MissingData(
+            "Event not found in ABI".to_owned(),
+        ))
+    }
+}
+
+/// Returns the first 4 bytes of the Keccak hash of a function or error signature.
+fn selector(signature: &str) -> [u8; 4] {
+    let mut selector = [0_u8; 4];
+    selector.copy_from_slice(&keccak256(signature.as_bytes())[0..4]);
+    selector
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0_u8; 32];
+    hasher.finalize(&mut out);
+    out
 }
 
 #[derive(Debug)]
@@ -0,0 +1,256 @@
+use super::{Parameter, ParameterType};
+use crate::AbiParserError;
+
+use std::convert::TryInto;
+
+/// Decodes a sequence of ABI encoded values of the given types.
+///
+/// Unlike [`Parameter::decode`], which only sees the data from the current
+/// head position, this keeps track of the start of the encoding block, so
+/// offsets of dynamic types ([`String`](ParameterType::String),
+/// [`Bytes`](ParameterType::Bytes), arrays and tuples) can be resolved.
+pub fn decode_from<'a>(
+    parameter_types: impl IntoIterator<Item = &'a ParameterType>,
+    data: &[u8],
+) -> Result<Vec<Parameter>, AbiParserError> {
+    let mut head = 0;
+    let mut parameters = Vec::<Parameter>::new();
+    for parameter_type in parameter_types {
+        if parameter_type.is_dynamic() {
+            // dynamic data is encoded in the tail, the head only contains its offset
+            let offset = read_usize(data, head)?;
+            let tail = data.get(offset..).ok_or_else(|| too_short(offset, data))?;
+            parameters.push(decode_dynamic(parameter_type, tail)?);
+            head += 32;
+        } else {
+            let (parameter, size) = decode_static(parameter_type, data, head)?;
+            parameters.push(parameter);
+            head += size;
+        }
+    }
+
+    Ok(parameters)
+}
+
+/// Decodes a dynamic type from the start of its tail encoding.
+fn decode_dynamic(
+    parameter_type: &ParameterType,
+    data: &[u8],
+) -> Result<Parameter, AbiParserError> {
+    match parameter_type {
+        ParameterType::String | ParameterType::Bytes => {
+            let len = read_usize(data, 0)?;
+            let end = len
+                .checked_add(32)
+                .ok_or_else(|| too_short(usize::MAX, data))?;
+            let bytes = data.get(32..end).ok_or_else(|| too_short(end, data))?;
+            match parameter_type {
+                ParameterType::String => Ok(Parameter::String(bytes.to_vec())),
+                _ => Ok(Parameter::new_bytes(bytes)),
+            }
+        }
+        ParameterType::Array(inner) => {
+            let len = read_usize(data, 0)?;
+            let elements = decode_from((0..len).map(|_| inner.as_ref()), &data[32..])?;
+            Ok(Parameter::Array(elements))
+        }
+        ParameterType::FixedArray(inner, len) => Ok(Parameter::FixedArray(decode_from(
+            (0..*len).map(|_| inner.as_ref()),
+            data,
+        )?)),
+        ParameterType::Tuple(parameter_types) => {
+            Ok(Parameter::Tuple(decode_from(parameter_types, data)?))
+        }
+        _ => Err(not_supported(parameter_type)),
+    }
+}
+
+/// Decodes a static type at the given head position and returns the number of
+/// bytes it occupies.
+fn decode_static(
+    parameter_type: &ParameterType,
+    data: &[u8],
+    head: usize,
+) -> Result<(Parameter, usize), AbiParserError> {
+    match parameter_type {
+        ParameterType::Address
+        | ParameterType::Bool
+        | ParameterType::Int(_)
+        | ParameterType::Uint(_) => Ok(Parameter::decode(parameter_type, read_word(data, head)?)),
+        ParameterType::FixedBytes(len) if *len <= 32 => Ok((
+            Parameter::new_fixed_bytes(&read_word(data, head)?[..*len]),
+            32,
+        )),
+        ParameterType::FixedArray(inner, len) => {
+            let (elements, size) =
+                decode_static_sequence((0..*len).map(|_| inner.as_ref()), data, head)?;
+            Ok((Parameter::FixedArray(elements), size))
+        }
+        ParameterType::Tuple(parameter_types) => {
+            let (elements, size) = decode_static_sequence(parameter_types, data, head)?;
+            Ok((Parameter::Tuple(elements), size))
+        }
+        _ => Err(not_supported(parameter_type)),
+    }
+}
+
+fn decode_static_sequence<'a>(
+    parameter_types: impl IntoIterator<Item = &'a ParameterType>,
+    data: &[u8],
+    head: usize,
+) -> Result<(Vec<Parameter>, usize), AbiParserError> {
+    let mut size = 0;
+    let mut elements = Vec::<Parameter>::new();
+    for parameter_type in parameter_types {
+        let (element, element_size) = decode_static(parameter_type, data, head + size)?;
+        elements.push(element);
+        size += element_size;
+    }
+
+    Ok((elements, size))
+}
+
+fn read_word(data: &[u8], start: usize) -> Result<&[u8], AbiParserError> {
+    let end = start
+        .checked_add(32)
+        .ok_or_else(|| too_short(usize::MAX, data))?;
+    data.get(start..end).ok_or_else(|| too_short(end, data))
+}
+
+fn read_usize(data: &[u8], start: usize) -> Result<usize, AbiParserError> {
+    let word = read_word(data, start)?;
+    let (high, low) = word.split_at(24);
+    if high.iter().any(|b| *b != 0) {
+        return Err(AbiParserError::InvalidAbiEncoding(
+            "Offset or length does not fit into usize".to_owned(),
+        ));
+    }
+    // unwrap is fine, the slice is exactly 8 bytes long
+    let value = u64::from_be_bytes(low.try_into().unwrap());
+    value.try_into().map_err(|_| {
+        AbiParserError::InvalidAbiEncoding("Offset or length does not fit into usize".to_owned())
+    })
+}
+
+fn not_supported(parameter_type: &ParameterType) -> AbiParserError {
+    AbiParserError::InvalidAbiEncoding(format!(
+        "Decoding {} is not supported",
+        parameter_type.as_abi_string()
+    ))
+}
+
+fn too_short(expected: usize, data: &[u8]) -> AbiParserError {
+    AbiParserError::InvalidAbiEncoding(format!(
+        "Output data is too short. Expected at least {} bytes, got {}",
+        expected,
+        data.len()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn decode_static_and_dynamic() {
+        let data = hex!(
+            "
+            0000000000000000000000000000000000000000000000000000000000000060
+            000000000000000000000000000000000000000000000000000000000000002a
+            abcdef0000000000000000000000000000000000000000000000000000000000
+            000000000000000000000000000000000000000000000000000000000000000d
+            48656c6c6f2c20576f726c642100000000000000000000000000000000000000"
+        );
+        let parameters = decode_from(
+            &[
+                ParameterType::String,
+                ParameterType::Uint(256),
+                ParameterType::FixedBytes(3),
+            ],
+            &data,
+        )
+        .unwrap();
+
+        assert_eq!(parameters[0].to_string(), "Hello, World!");
+        assert_eq!(parameters[1].to_string(), "42");
+        match &parameters[2] {
+            Parameter::FixedBytes(bytes) => assert_eq!(bytes, &[0xab, 0xcd, 0xef]),
+            _ => panic!("Expected fixed bytes"),
+        }
+    }
+
+    #[test]
+    fn decode_invalid_data() {
+        // offset points outside of the data
+        let data = hex!("0000000000000000000000000000000000000000000000000000000000000040");
+        match decode_from(&[ParameterType::Bytes], &data) {
+            Err(AbiParserError::InvalidAbiEncoding(e)) => {
+                assert!(e.starts_with("Output data is too short"))
+            }
+            _ => panic!("Decoding should fail with an invalid offset"),
+        }
+
+        match decode_from(&[ParameterType::FixedBytes(33)], &data) {
+            Err(AbiParserError::InvalidAbiEncoding(e)) => {
+                assert_eq!(e, "Decoding bytes33 is not supported")
+            }
+            _ => panic!("Decoding bytes33 should not be supported"),
+        }
+    }
+
+    #[test]
+    fn decode_nested() {
+        // (bool,bytes)[] as returned by Multicall3 aggregate3, followed by a uint256[2]
+        let data = hex!(
+            "
+            0000000000000000000000000000000000000000000000000000000000000060
+            0000000000000000000000000000000000000000000000000000000000000007
+            0000000000000000000000000000000000000000000000000000000000000008
+            0000000000000000000000000000000000000000000000000000000000000002
+            0000000000000000000000000000000000000000000000000000000000000040
+            00000000000000000000000000000000000000000000000000000000000000c0
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000040
+            0000000000000000000000000000000000000000000000000000000000000001
+            2a00000000000000000000000000000000000000000000000000000000000000
+            0000000000000000000000000000000000000000000000000000000000000000
+            0000000000000000000000000000000000000000000000000000000000000040
+            0000000000000000000000000000000000000000000000000000000000000000"
+        );
+        let parameters = decode_from(
+            &[
+                ParameterType::Array(Box::new(ParameterType::Tuple(vec![
+                    ParameterType::Bool,
+                    ParameterType::Bytes,
+                ]))),
+                ParameterType::FixedArray(Box::new(ParameterType::Uint(256)), 2),
+            ],
+            &data,
+        )
+        .unwrap();
+
+        match &parameters[0] {
+            Parameter::Array(results) => {
+                assert_eq!(results.len(), 2);
+                match (&results[0], &results[1]) {
+                    (Parameter::Tuple(first), Parameter::Tuple(second)) => {
+                        assert_eq!(first[0].to_string(), "true");
+                        assert!(matches!(&first[1], Parameter::Bytes(data) if data == &[0x2a]));
+                        assert_eq!(second[0].to_string(), "false");
+                        assert!(matches!(&second[1], Parameter::Bytes(data) if data.is_empty()));
+                    }
+                    _ => panic!("Expected tuples"),
+                }
+            }
+            _ => panic!("Expected an array"),
+        }
+        match &parameters[1] {
+            Parameter::FixedArray(values) => {
+                assert_eq!(values[0].to_string(), "7");
+                assert_eq!(values[1].to_string(), "8");
+            }
+            _ => panic!("Expected a fixed array"),
+        }
+    }
+}
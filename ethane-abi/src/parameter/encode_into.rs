@@ -27,11 +27,16 @@ pub fn encode_into(hash: &mut Vec<u8>, parameters: Vec<Parameter>) {
         let offset = (hash.len() - dynamic_offset).to_be_bytes();
         hash[range].copy_from_slice(&left_pad_to_32_bytes(&offset));
         match &parameters[index] {
-            Parameter::Array(data) | Parameter::Tuple(data) => {
+            Parameter::Array(data) => {
                 // encode the length of the underlying dynamic data
                 hash.extend_from_slice(&left_pad_to_32_bytes(&data.len().to_be_bytes()));
                 encode_into(hash, data.to_vec());
             }
+            // dynamic tuples and fixed arrays are encoded like a sequence of
+            // parameters, without a length
+            Parameter::FixedArray(data) | Parameter::Tuple(data) => {
+                encode_into(hash, data.to_vec());
+            }
             _ => hash.extend_from_slice(&parameters[index].static_encode()),
         }
     }
@@ -231,4 +236,30 @@ mod test {
             assert_eq!(hash[start..end], expected[start..end]);
         }
     }
+
+    #[test]
+    fn dynamic_tuple_array() {
+        let mut hash = vec![0x82, 0xad, 0x56, 0xcb];
+        // encode aggregate3((address,bool,bytes)[])
+        encode_into(
+            &mut hash,
+            vec![Parameter::Array(vec![Parameter::Tuple(vec![
+                Parameter::from(ethane_types::Address::from_int_unchecked(0xaa_u8)),
+                Parameter::from(true),
+                Parameter::new_bytes(&[0x12, 0x34]),
+            ])])],
+        );
+        let expected = hex!(
+            "82ad56cb
+            0000000000000000000000000000000000000000000000000000000000000020
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000020
+            00000000000000000000000000000000000000000000000000000000000000aa
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000060
+            0000000000000000000000000000000000000000000000000000000000000002
+            1234000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(hash, expected.to_vec());
+    }
 }
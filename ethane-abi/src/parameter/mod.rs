@@ -1,9 +1,11 @@
 mod construction;
+mod decode_from;
 pub mod display;
 mod encode_into;
 mod parameter_type;
 mod utils;
 
+pub use decode_from::decode_from;
 pub use encode_into::encode_into;
 pub use parameter_type::ParameterType;
 use utils::*;
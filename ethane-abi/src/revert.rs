@@ -0,0 +1,165 @@
+use crate::function::{Function, FunctionParameter};
+use crate::{AbiParserError, Parameter};
+
+use ethane_types::H256;
+use std::fmt;
+
+/// Selector of the builtin `Error(string)` error.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of the builtin `Panic(uint256)` error.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// An ABI custom error instance.
+///
+/// Declared in Solidity as `error Name(...)` and raised by `revert Name(...)`.
+/// The error name is available as the key to the respective error in the
+/// `HashMap` of the [`crate::Abi`] parser.
+pub struct CustomError {
+    pub inputs: Vec<FunctionParameter>,
+}
+
+impl CustomError {
+    /// Tries to parse a `.json` value into a [`CustomError`].
+    pub fn parse(raw_error: &serde_json::Value) -> Result<Self, AbiParserError> {
+        Ok(Self {
+            inputs: Function::parse_parameters(&raw_error["inputs"])?,
+        })
+    }
+}
+
+/// A decoded revert reason.
+#[derive(Clone)]
+pub enum Revert {
+    /// Raised by `require(condition, "reason")` or `revert("reason")`.
+    Error(String),
+    /// Raised by failing assertions, arithmetic errors, etc.
+    Panic(PanicCode),
+    /// Raised by an error declared in the contract ABI.
+    Custom {
+        name: String,
+        parameters: Vec<Parameter>,
+    },
+    /// The revert data doesn't match any known error, e.g. it's empty
+    /// because of a `require(condition)` without a reason.
+    Unknown(Vec<u8>),
+}
+
+impl fmt::Display for Revert {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(reason) => write!(formatter, "{}", reason),
+            Self::Panic(code) => write!(formatter, "panic: {}", code),
+            Self::Custom { name, parameters } => write!(
+                formatter,
+                "{}({})",
+                name,
+                parameters
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Self::Unknown(data) if data.is_empty() => write!(formatter, "no reason given"),
+            Self::Unknown(data) => write!(
+                formatter,
+                "unknown reason 0x{}",
+                data.iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<String>>()
+                    .join("")
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for Revert {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "Revert({})", self)
+    }
+}
+
+/// Possible codes of the builtin `Panic(uint256)` error.
+///
+/// See the [Solidity
+/// documentation](https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require)
+/// for further info.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PanicCode {
+    Generic,
+    AssertionFailed,
+    ArithmeticOverflow,
+    DivisionByZero,
+    InvalidEnumValue,
+    InvalidStorageEncoding,
+    EmptyArrayPop,
+    IndexOutOfBounds,
+    OutOfMemory,
+    ZeroFunctionPointer,
+    Unknown(u64),
+}
+
+impl PanicCode {
+    /// Parses the code from the ABI encoded `uint256` argument.
+    ///
+    /// Returns `None` if the code doesn't fit into 64 bits, which is never
+    /// the case for a panic raised by the compiler.
+    pub fn parse(code: H256) -> Option<Self> {
+        let (high, low) = code.as_bytes().split_at(24);
+        if high.iter().any(|b| *b != 0) {
+            return None;
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(low);
+        let code = match u64::from_be_bytes(bytes) {
+            0x00 => Self::Generic,
+            0x01 => Self::AssertionFailed,
+            0x11 => Self::ArithmeticOverflow,
+            0x12 => Self::DivisionByZero,
+            0x21 => Self::InvalidEnumValue,
+            0x22 => Self::InvalidStorageEncoding,
+            0x31 => Self::EmptyArrayPop,
+            0x32 => Self::IndexOutOfBounds,
+            0x41 => Self::OutOfMemory,
+            0x51 => Self::ZeroFunctionPointer,
+            other => Self::Unknown(other),
+        };
+
+        Some(code)
+    }
+
+    /// Returns the numeric code of the panic.
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::Generic => 0x00,
+            Self::AssertionFailed => 0x01,
+            Self::ArithmeticOverflow => 0x11,
+            Self::DivisionByZero => 0x12,
+            Self::InvalidEnumValue => 0x21,
+            Self::InvalidStorageEncoding => 0x22,
+            Self::EmptyArrayPop => 0x31,
+            Self::IndexOutOfBounds => 0x32,
+            Self::OutOfMemory => 0x41,
+            Self::ZeroFunctionPointer => 0x51,
+            Self::Unknown(code) => *code,
+        }
+    }
+}
+
+impl fmt::Display for PanicCode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Generic => "generic compiler inserted panic",
+            Self::AssertionFailed => "assertion failed",
+            Self::ArithmeticOverflow => "arithmetic overflow or underflow",
+            Self::DivisionByZero => "division or modulo by zero",
+            Self::InvalidEnumValue => "conversion into an invalid enum value",
+            Self::InvalidStorageEncoding => "access to an incorrectly encoded storage byte array",
+            Self::EmptyArrayPop => "pop on an empty array",
+            Self::IndexOutOfBounds => "array index out of bounds",
+            Self::OutOfMemory => "too much memory allocated",
+            Self::ZeroFunctionPointer => "call to a zero-initialized internal function",
+            Self::Unknown(_) => "unknown panic",
+        };
+        write!(formatter, "{} (0x{:02x})", description, self.code())
+    }
+}
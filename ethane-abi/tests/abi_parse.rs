@@ -1,5 +1,5 @@
-use ethane_abi::{Abi, Parameter};
-use ethane_types::{Address, U256};
+use ethane_abi::{Abi, AbiParserError, PanicCode, Parameter, Revert};
+use ethane_types::{Address, H256, U256};
 use hex_literal::hex;
 
 use std::convert::TryFrom;
@@ -64,3 +64,148 @@ fn test_abi_encode() {
     assert_eq!(returned_parameters[1].to_string(), String::from("3071"));
     assert_eq!(returned_parameters[2].to_string(), String::from("3327"));
 }
+
+#[test]
+fn test_abi_invalid_input() {
+    let path = Path::new("tests/foo.abi");
+    let mut abi = Abi::new();
+    abi.parse_file(path).expect("unable to parse abi");
+
+    // missing parameter
+    let address = Address::try_from("0x95eDA452256C1190947f9ba1fD19422f0120858a").unwrap();
+    match abi.encode("approve", vec![Parameter::from(address)]) {
+        Err(AbiParserError::InvalidAbiEncoding(e)) => {
+            assert!(e.starts_with("Invalid number of parameters"))
+        }
+        _ => panic!("Encoding should fail with a missing parameter"),
+    }
+
+    // unknown function
+    match abi.encode("mint", vec![]) {
+        Err(AbiParserError::MissingData(_)) => {}
+        _ => panic!("Encoding should fail with an unknown function"),
+    }
+
+    // output data is too short (e.g. a call to an address without code returns `0x`)
+    match abi.decode("getPrices", &[0u8; 40]) {
+        Err(AbiParserError::InvalidAbiEncoding(e)) => {
+            assert!(e.starts_with("Output data is too short"))
+        }
+        _ => panic!("Decoding should fail with insufficient data"),
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_abi_decode_revert() {
+    let path = Path::new("tests/foo.abi");
+    let mut abi = Abi::new();
+    abi.parse_file(path).expect("unable to parse abi");
+
+    // Error(string)
+    let data = hex!("
+        08c379a0
+        0000000000000000000000000000000000000000000000000000000000000020
+        0000000000000000000000000000000000000000000000000000000000000012
+        6e6f7420656e6f7567682062616c616e63650000000000000000000000000000"
+    );
+    match abi.decode_revert(&data).unwrap() {
+        Revert::Error(reason) => assert_eq!(reason, "not enough balance"),
+        revert => panic!("Expected Error(string), got {:?}", revert),
+    }
+
+    // Panic(uint256)
+    let data = hex!("
+        4e487b71
+        0000000000000000000000000000000000000000000000000000000000000011"
+    );
+    match abi.decode_revert(&data).unwrap() {
+        Revert::Panic(code) => {
+            assert_eq!(code, PanicCode::ArithmeticOverflow);
+            assert_eq!(code.to_string(), "arithmetic overflow or underflow (0x11)");
+        }
+        revert => panic!("Expected Panic(uint256), got {:?}", revert),
+    }
+
+    // InsufficientBalance(uint256,uint256) declared in the ABI
+    let data = hex!("
+        cf479181
+        0000000000000000000000000000000000000000000000000000000000000064
+        00000000000000000000000000000000000000000000000000000000000003e8"
+    );
+    let revert = abi.decode_revert(&data).unwrap();
+    assert_eq!(revert.to_string(), "InsufficientBalance(100, 1000)");
+    match revert {
+        Revert::Custom { name, parameters } => {
+            assert_eq!(name, "InsufficientBalance");
+            assert_eq!(parameters.len(), 2);
+        }
+        revert => panic!("Expected a custom error, got {:?}", revert),
+    }
+
+    // require(condition) without a reason
+    match abi.decode_revert(&[]).unwrap() {
+        Revert::Unknown(data) => assert!(data.is_empty()),
+        revert => panic!("Expected an unknown revert, got {:?}", revert),
+    }
+
+    // unknown selector
+    let revert = abi.decode_revert(&hex!("deadbeef")).unwrap();
+    assert_eq!(revert.to_string(), "unknown reason 0xdeadbeef");
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_abi_decode_log() {
+    let path = Path::new("tests/foo.abi");
+    let mut abi = Abi::new();
+    abi.parse_file(path).expect("unable to parse abi");
+
+    // Transfer(address indexed from, address indexed to, uint256 value)
+    let transfer = abi.events["Transfer"].topic("Transfer");
+    assert_eq!(
+        transfer,
+        H256::from(hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"))
+    );
+    let topics = [
+        transfer,
+        H256::from(hex!("00000000000000000000000095eda452256c1190947f9ba1fd19422f0120858a")),
+        H256::from(hex!("0000000000000000000000001a4c0439ba035dacf0d573394107597ceebf9ff8")),
+    ];
+    let data = hex!("0000000000000000000000000000000000000000000000000000000000000613");
+    let log = abi.decode_log(&topics, &data).unwrap();
+    assert_eq!(log.name, "Transfer");
+    assert_eq!(
+        log.to_string(),
+        "Transfer(0x95eda452256c1190947f9ba1fd19422f0120858a, 0x1a4c0439ba035dacf0d573394107597ceebf9ff8, 1555)"
+    );
+
+    // Named(string indexed name, string note), the indexed string is only stored as its hash
+    let name_hash = hex!("1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8");
+    let topics = [abi.events["Named"].topic("Named"), H256::from(name_hash)];
+    let data = hex!("
+        0000000000000000000000000000000000000000000000000000000000000020
+        0000000000000000000000000000000000000000000000000000000000000002
+        6869000000000000000000000000000000000000000000000000000000000000"
+    );
+    let log = abi.decode_log(&topics, &data).unwrap();
+    match &log.parameters[..] {
+        [Parameter::FixedBytes(hash), Parameter::String(note)] => {
+            assert_eq!(hash[..], name_hash[..]);
+            assert_eq!(note, b"hi");
+        }
+        _ => panic!("Unexpected parameters {:?}", log),
+    }
+
+    // missing topic
+    match abi.decode_log(&topics[..1], &data) {
+        Err(AbiParserError::InvalidAbiEncoding(_)) => {}
+        result => panic!("Expected an encoding error, got {:?}", result),
+    }
+
+    // unknown event
+    match abi.decode_log(&[H256::zero()], &[]) {
+        Err(AbiParserError::MissingData(_)) => {}
+        result => panic!("Expected a missing event, got {:?}", result),
+    }
+}
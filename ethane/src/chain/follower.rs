@@ -0,0 +1,353 @@
+use crate::rpc::Rpc;
+use crate::types::{BlockHeader, BlockParameter, H256};
+use crate::{Connection, ConnectionError, Request};
+
+use std::collections::VecDeque;
+
+/// Default number of recent blocks tracked by a [BlockFollower]
+pub const DEFAULT_FOLLOW_DEPTH: usize = 64;
+
+/// Wraps the different errors that may occur while following the chain.
+#[derive(Debug)]
+pub enum BlockFollowerError {
+    /// The common ancestor of a reorganization is older than the oldest tracked block, which
+    /// has the given number
+    ReorgTooDeep(u64),
+    /// A header has no hash or number, e.g. the header of a pending block
+    IncompleteHeader,
+    /// The node does not know the block with the given hash
+    UnknownBlock(H256),
+    /// The node could not be queried
+    Connection(ConnectionError),
+}
+
+impl From<ConnectionError> for BlockFollowerError {
+    fn from(err: ConnectionError) -> Self {
+        Self::Connection(err)
+    }
+}
+
+/// A change of the canonical chain, returned by [BlockFollower]
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockEvent {
+    /// Blocks which were removed from the canonical chain, newest first
+    Reverted(Vec<BlockHeader>),
+    /// Blocks which were added to the canonical chain, oldest first
+    Applied(Vec<BlockHeader>),
+}
+
+/// Follows the head of the chain and detects reorganizations
+///
+/// Headers are passed to [push](Self::push), e.g. from
+/// [eth_subscribe_new_heads](crate::rpc::eth_subscribe_new_heads), or fetched with
+/// [poll](Self::poll). A header which does not extend the current head is traced back to the
+/// common ancestor with the tracked blocks. The blocks after the ancestor are reported as
+/// [Reverted](BlockEvent::Reverted) and the new branch as [Applied](BlockEvent::Applied), so
+/// state derived from the reverted blocks can be undone before the new ones are processed.
+/// Missed blocks are fetched and applied as well.
+#[derive(Clone, Debug)]
+pub struct BlockFollower {
+    depth: usize,
+    blocks: VecDeque<BlockHeader>,
+}
+
+impl Default for BlockFollower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockFollower {
+    /// Creates a follower which tracks the [DEFAULT_FOLLOW_DEPTH] most recent blocks.
+    pub fn new() -> Self {
+        Self {
+            depth: DEFAULT_FOLLOW_DEPTH,
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Sets the number of tracked blocks, which limits the depth of detectable reorganizations.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// The current head of the chain
+    pub fn head(&self) -> Option<&BlockHeader> {
+        self.blocks.back()
+    }
+
+    /// The tracked blocks, oldest first
+    pub fn blocks(&self) -> impl Iterator<Item = &BlockHeader> {
+        self.blocks.iter()
+    }
+
+    /// Forgets all tracked blocks, the next header is applied without checks.
+    pub fn reset(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Fetches the latest block and processes it like [push](Self::push).
+    pub fn poll<T: Request>(
+        &mut self,
+        connection: &mut Connection<T>,
+    ) -> Result<Vec<BlockEvent>, BlockFollowerError> {
+        let header = connection
+            .call(block_header_by_number(BlockParameter::Latest))?
+            .ok_or(BlockFollowerError::IncompleteHeader)?;
+        self.push(connection, header)
+    }
+
+    /// Processes a new head of the chain and returns the resulting changes.
+    ///
+    /// Returns no events if the header is the current head. If the reorganization is deeper
+    /// than the tracked blocks, the follower is left unchanged.
+    pub fn push<T: Request>(
+        &mut self,
+        connection: &mut Connection<T>,
+        header: BlockHeader,
+    ) -> Result<Vec<BlockEvent>, BlockFollowerError> {
+        number_of(&header)?;
+        // the new branch, newest first
+        let mut branch = vec![header];
+        let ancestor = loop {
+            let oldest = &branch[branch.len() - 1];
+            let hash = hash_of(oldest)?;
+            if let Some(index) = self.position(hash) {
+                branch.pop();
+                break Some(index);
+            }
+            if let Some(index) = self.position(oldest.parent_hash) {
+                break Some(index);
+            }
+            let first = match self.blocks.front() {
+                Some(first) => first,
+                None => break None,
+            };
+            let first_number = number_of(first)?;
+            if number_of(oldest)? <= first_number {
+                return Err(BlockFollowerError::ReorgTooDeep(first_number));
+            }
+            let parent = connection
+                .call(block_header_by_hash(oldest.parent_hash))?
+                .ok_or(BlockFollowerError::UnknownBlock(oldest.parent_hash))?;
+            branch.push(parent);
+        };
+
+        let mut events = Vec::new();
+        if let Some(index) = ancestor {
+            let mut reverted = self.blocks.split_off(index + 1);
+            if !reverted.is_empty() {
+                reverted.make_contiguous().reverse();
+                events.push(BlockEvent::Reverted(reverted.into()));
+            }
+        }
+        if !branch.is_empty() {
+            branch.reverse();
+            self.blocks.extend(branch.iter().cloned());
+            while self.blocks.len() > self.depth {
+                self.blocks.pop_front();
+            }
+            events.push(BlockEvent::Applied(branch));
+        }
+        Ok(events)
+    }
+
+    fn position(&self, hash: H256) -> Option<usize> {
+        self.blocks
+            .iter()
+            .rposition(|block| block.hash == Some(hash))
+    }
+}
+
+fn hash_of(header: &BlockHeader) -> Result<H256, BlockFollowerError> {
+    header.hash.ok_or(BlockFollowerError::IncompleteHeader)
+}
+
+fn number_of(header: &BlockHeader) -> Result<u64, BlockFollowerError> {
+    header
+        .number
+        .map(|number| u64::from_be_bytes(number.into_bytes()))
+        .ok_or(BlockFollowerError::IncompleteHeader)
+}
+
+/// Same as [eth_get_block_by_hash](crate::rpc::eth_get_block_by_hash), but returns the header only.
+fn block_header_by_hash(block_hash: H256) -> Rpc<Option<BlockHeader>> {
+    let mut rpc = Rpc::new("eth_getBlockByHash");
+    rpc.add_param(block_hash);
+    rpc.add_param(false);
+    rpc
+}
+
+fn block_header_by_number(block_param: BlockParameter) -> Rpc<Option<BlockHeader>> {
+    let mut rpc = Rpc::new("eth_getBlockByNumber");
+    rpc.add_param(block_param);
+    rpc.add_param(false);
+    rpc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    fn header_json(number: u64, hash: u8, parent: u8) -> String {
+        format!(
+            r#"{{"number":"0x{:x}","hash":"0x{:064x}","parentHash":"0x{:064x}",
+            "transactionsRoot":"0x00","stateRoot":"0x00","receiptsRoot":"0x00","difficulty":"0x0",
+            "sha3Uncles":"0x00","miner":"0x00","gasLimit":"0x0","gasUsed":"0x0","extraData":"0x"}}"#,
+            number, hash, parent
+        )
+    }
+
+    fn header(number: u64, hash: u8, parent: u8) -> BlockHeader {
+        serde_json::from_str(&header_json(number, hash, parent)).unwrap()
+    }
+
+    fn hashes(headers: &[BlockHeader]) -> Vec<H256> {
+        headers.iter().map(|header| header.hash.unwrap()).collect()
+    }
+
+    fn block_hash(hash: u8) -> H256 {
+        H256::from_int_unchecked(hash)
+    }
+
+    /// Answers `eth_getBlockByHash` with the known headers
+    struct Chain {
+        headers: HashMap<H256, String>,
+    }
+
+    impl Chain {
+        fn new(headers: &[(u64, u8, u8)]) -> Connection<Chain> {
+            Connection::new(Chain {
+                headers: headers
+                    .iter()
+                    .map(|(number, hash, parent)| {
+                        (block_hash(*hash), header_json(*number, *hash, *parent))
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    impl Request for Chain {
+        fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+            let request: serde_json::Value = serde_json::from_str(&cmd).unwrap();
+            assert_eq!(request["method"], "eth_getBlockByHash");
+            let hash = H256::try_from(request["params"][0].as_str().unwrap()).unwrap();
+            let header = self
+                .headers
+                .get(&hash)
+                .map_or("null", |header| header.as_str());
+            Ok(format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, header))
+        }
+    }
+
+    #[test]
+    fn block_follower_extends_and_fills_gaps() {
+        let mut connection = Chain::new(&[(2, 2, 1), (3, 3, 2)]);
+        let mut follower = BlockFollower::new();
+
+        let events = follower.push(&mut connection, header(1, 1, 0)).unwrap();
+        assert_eq!(events, vec![BlockEvent::Applied(vec![header(1, 1, 0)])]);
+        assert!(follower
+            .push(&mut connection, header(1, 1, 0))
+            .unwrap()
+            .is_empty());
+
+        // blocks 2 and 3 were missed
+        let events = follower.push(&mut connection, header(4, 4, 3)).unwrap();
+        match &events[..] {
+            [BlockEvent::Applied(applied)] => {
+                assert_eq!(
+                    hashes(applied),
+                    vec![block_hash(2), block_hash(3), block_hash(4)]
+                )
+            }
+            _ => panic!("Expected applied blocks, got {:?}", events),
+        }
+        assert_eq!(follower.head(), Some(&header(4, 4, 3)));
+    }
+
+    #[test]
+    fn block_follower_reorg() {
+        // 1 <- 2 <- 3 is replaced by 1 <- 12 <- 13 <- 14
+        let mut connection = Chain::new(&[(2, 12, 1), (3, 13, 12)]);
+        let mut follower = BlockFollower::new();
+        for block in [header(1, 1, 0), header(2, 2, 1), header(3, 3, 2)].iter() {
+            follower.push(&mut connection, block.clone()).unwrap();
+        }
+
+        let events = follower.push(&mut connection, header(4, 14, 13)).unwrap();
+        match &events[..] {
+            [BlockEvent::Reverted(reverted), BlockEvent::Applied(applied)] => {
+                assert_eq!(hashes(reverted), vec![block_hash(3), block_hash(2)]);
+                assert_eq!(
+                    hashes(applied),
+                    vec![block_hash(12), block_hash(13), block_hash(14)]
+                );
+            }
+            _ => panic!("Expected a reorg, got {:?}", events),
+        }
+        assert_eq!(
+            hashes(&follower.blocks().cloned().collect::<Vec<_>>()),
+            vec![
+                block_hash(1),
+                block_hash(12),
+                block_hash(13),
+                block_hash(14)
+            ]
+        );
+
+        // the chain goes back to an older block
+        let events = follower.push(&mut connection, header(2, 12, 1)).unwrap();
+        match &events[..] {
+            [BlockEvent::Reverted(reverted)] => {
+                assert_eq!(hashes(reverted), vec![block_hash(14), block_hash(13)])
+            }
+            _ => panic!("Expected reverted blocks, got {:?}", events),
+        }
+    }
+
+    #[test]
+    fn block_follower_reorg_too_deep() {
+        let mut connection = Chain::new(&[(3, 13, 12), (2, 12, 11)]);
+        let mut follower = BlockFollower::new().depth(2);
+        for block in [header(1, 1, 0), header(2, 2, 1), header(3, 3, 2)].iter() {
+            follower.push(&mut connection, block.clone()).unwrap();
+        }
+        assert_eq!(follower.blocks().count(), 2);
+
+        match follower.push(&mut connection, header(4, 14, 13)) {
+            Err(BlockFollowerError::ReorgTooDeep(number)) => assert_eq!(number, 2),
+            result => panic!("Expected a too deep reorg, got {:?}", result),
+        }
+        assert_eq!(follower.head(), Some(&header(3, 3, 2)));
+    }
+
+    #[test]
+    fn block_follower_connection_errors() {
+        let mock = MockTransport::new();
+        mock.push_error("eth_getBlockByHash", -32005, "limit exceeded");
+        mock.push_error("eth_getBlockByNumber", -32005, "limit exceeded");
+        let mut connection = Connection::new(mock.clone());
+        let mut follower = BlockFollower::new();
+        for block in [header(1, 1, 0), header(2, 2, 1), header(3, 3, 2)].iter() {
+            follower.push(&mut connection, block.clone()).unwrap();
+        }
+
+        // the errors are not mistaken for unknown blocks
+        assert!(matches!(
+            follower.push(&mut connection, header(4, 14, 13)),
+            Err(BlockFollowerError::Connection(ConnectionError::JsonRpc(_)))
+        ));
+        assert!(matches!(
+            follower.poll(&mut connection),
+            Err(BlockFollowerError::Connection(ConnectionError::JsonRpc(_)))
+        ));
+        assert_eq!(follower.head(), Some(&header(3, 3, 2)));
+        assert!(mock.is_done());
+    }
+}
@@ -0,0 +1,7 @@
+//! Helpers to follow the head of the chain
+
+#[cfg(feature = "blocking")]
+mod follower;
+
+#[cfg(feature = "blocking")]
+pub use follower::{BlockEvent, BlockFollower, BlockFollowerError, DEFAULT_FOLLOW_DEPTH};
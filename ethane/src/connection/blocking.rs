@@ -1,5 +1,7 @@
-use super::{ConnectionError, Request, Subscribe, Subscription};
-use crate::rpc::{Rpc, RpcResponse, SubscriptionRequest};
+use super::{parse_response, ConnectionError, FilterWatcher, Request, Subscribe, Subscription};
+use crate::rpc::{self, Rpc, SubscriptionRequest};
+use crate::transaction::PendingTransaction;
+use crate::types::{Bytes, Filter, Log, TransactionRequest, H256};
 
 use serde::de::DeserializeOwned;
 
@@ -29,13 +31,56 @@ where
             let result_data = self.transport.request(
                 serde_json::to_string(&rpc).map_err(|e| ConnectionError::Serde(e.to_string()))?,
             )?;
-            let result = serde_json::from_str::<RpcResponse<U>>(&result_data)
-                .map_err(|e| ConnectionError::Serde(e.to_string()))?;
-            Ok(result.result)
+            parse_response(&result_data)
         } else {
             Err(ConnectionError::NoTicketId)
         }
     }
+
+    /// Sends a transaction with `eth_sendTransaction`.
+    ///
+    /// Returns a [PendingTransaction] which can be used to wait for the receipt.
+    pub fn send_transaction(
+        &mut self,
+        transaction: TransactionRequest,
+    ) -> Result<PendingTransaction, ConnectionError> {
+        let hash = self.call(rpc::eth_send_transaction(transaction))?;
+        Ok(PendingTransaction::new(hash))
+    }
+
+    /// Sends a signed transaction with `eth_sendRawTransaction`.
+    ///
+    /// Returns a [PendingTransaction] which can be used to wait for the receipt.
+    pub fn send_raw_transaction(
+        &mut self,
+        raw_transaction: Bytes,
+    ) -> Result<PendingTransaction, ConnectionError> {
+        let hash = self.call(rpc::eth_send_raw_transaction(raw_transaction))?;
+        Ok(PendingTransaction::new(hash))
+    }
+
+    /// Installs a log filter and returns a [watcher](FilterWatcher) which polls it for new logs.
+    ///
+    /// Unlike subscriptions, this works over http as well.
+    pub fn watch_logs(
+        &mut self,
+        filter: Filter,
+    ) -> Result<FilterWatcher<'_, T, Log>, ConnectionError> {
+        FilterWatcher::logs(self, filter)
+    }
+
+    /// Installs a filter for the hashes of new blocks and returns a [watcher](FilterWatcher).
+    pub fn watch_blocks(&mut self) -> Result<FilterWatcher<'_, T, H256>, ConnectionError> {
+        FilterWatcher::blocks(self)
+    }
+
+    /// Installs a filter for the hashes of new pending transactions and returns a
+    /// [watcher](FilterWatcher).
+    pub fn watch_pending_transactions(
+        &mut self,
+    ) -> Result<FilterWatcher<'_, T, H256>, ConnectionError> {
+        FilterWatcher::pending_transactions(self)
+    }
 }
 
 impl<T> Connection<T>
@@ -0,0 +1,480 @@
+use super::{Connection, ConnectionError, Request};
+
+use crate::rpc::{self, Rpc};
+use crate::types::{BlockParameter, Filter, Log, H256, U128, U64};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The items which were missed while an expired filter was not installed
+enum Backfill {
+    /// The logs of the reinstalled filter
+    Logs,
+    /// The hashes of the blocks from `next` up to the head when the filter was reinstalled
+    Blocks { next: u64, head: Option<u64> },
+}
+
+/// The filter which is (re)installed by a [FilterWatcher]
+enum FilterKind {
+    Logs(Filter),
+    Blocks,
+    PendingTransactions,
+}
+
+/// A filter which is polled with `eth_getFilterChanges`
+///
+/// Works over any connection, so it can be used where [subscriptions](super::Subscription) are
+/// not available, e.g. over http. Can be created by calling
+/// [watch_logs](crate::Connection::watch_logs), [watch_blocks](crate::Connection::watch_blocks)
+/// or [watch_pending_transactions](crate::Connection::watch_pending_transactions).
+/// In order to yield the next item call [next_item](Self::next_item).
+///
+/// Nodes uninstall filters which are not polled for a while. An expired filter is installed
+/// again and continues after the last block which was polled successfully: log filters get the
+/// missed logs with `eth_getFilterLogs`, block filters the hashes of the missed blocks with
+/// `eth_getBlockByNumber`. If fetching them fails, the next poll continues where it stopped.
+/// Items of blocks which are mined while the filter is reinstalled may be yielded twice. The
+/// filter is uninstalled when the watcher is dropped.
+pub struct FilterWatcher<'a, T: Request, U: DeserializeOwned + Debug> {
+    /// The id of the currently installed filter
+    pub id: U128,
+    connection: &'a mut Connection<T>,
+    kind: FilterKind,
+    poll_interval: Duration,
+    items: VecDeque<U>,
+    /// The last block whose items were received, not tracked for pending transactions
+    synced_block: Option<u64>,
+    backfill: Option<Backfill>,
+    block_of: fn(&U) -> Option<U64>,
+    installed: bool,
+}
+
+impl<'a, T: Request> FilterWatcher<'a, T, Log> {
+    pub(crate) fn logs(
+        connection: &'a mut Connection<T>,
+        filter: Filter,
+    ) -> Result<Self, ConnectionError> {
+        Self::new(connection, FilterKind::Logs(filter), |log| log.block_number)
+    }
+}
+
+impl<'a, T: Request> FilterWatcher<'a, T, H256> {
+    pub(crate) fn blocks(connection: &'a mut Connection<T>) -> Result<Self, ConnectionError> {
+        Self::new(connection, FilterKind::Blocks, |_| None)
+    }
+
+    pub(crate) fn pending_transactions(
+        connection: &'a mut Connection<T>,
+    ) -> Result<Self, ConnectionError> {
+        Self::new(connection, FilterKind::PendingTransactions, |_| None)
+    }
+}
+
+impl<'a, T: Request, U: DeserializeOwned + Debug> FilterWatcher<'a, T, U> {
+    fn new(
+        connection: &'a mut Connection<T>,
+        kind: FilterKind,
+        block_of: fn(&U) -> Option<U64>,
+    ) -> Result<Self, ConnectionError> {
+        let synced_block = kind.head(connection)?;
+        let id = kind.install(connection)?;
+        Ok(Self {
+            id,
+            connection,
+            kind,
+            poll_interval: Duration::from_secs(1),
+            items: VecDeque::new(),
+            synced_block,
+            backfill: None,
+            block_of,
+            installed: true,
+        })
+    }
+
+    /// Sets the time to wait between two polls if there are no new items, defaults to 1 second.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Yields the next item of this filter, polling the node until there is one.
+    pub fn next_item(&mut self) -> Result<U, ConnectionError> {
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                return Ok(item);
+            }
+            self.poll()?;
+            if self.items.is_empty() {
+                std::thread::sleep(self.poll_interval);
+            }
+        }
+    }
+
+    /// The connection of the watcher, to make other calls while the filter is installed
+    pub(crate) fn connection(&mut self) -> &mut Connection<T> {
+        self.connection
+    }
+
+    /// Uninstalls the filter.
+    pub fn close(mut self) -> Result<(), ConnectionError> {
+        self.installed = false;
+        self.connection
+            .call(rpc::eth_uninstall_filter(self.id))
+            .map(|_| ())
+    }
+
+    fn poll(&mut self) -> Result<(), ConnectionError> {
+        // the changes contain at least the items up to the head before the poll
+        let head = self.kind.head(self.connection)?;
+        // an interrupted backfill is completed before the changes are polled again
+        let polled = self.backfill.is_none();
+        let changes = if !polled {
+            self.backfill()?
+        } else {
+            match self.connection.call(filter_changes(self.id)) {
+                Err(ConnectionError::JsonRpc(err)) if is_filter_not_found(&err.message) => {
+                    self.reinstall()?;
+                    self.backfill()?
+                }
+                changes => changes?,
+            }
+        };
+        for item in changes {
+            if let Some(block) = (self.block_of)(&item) {
+                self.synced_block = self.synced_block.max(Some(to_u64(block)));
+            }
+            self.items.push_back(item);
+        }
+        if polled {
+            self.synced_block = self.synced_block.max(head);
+        }
+        Ok(())
+    }
+
+    /// Installs the filter again after the last synced block, the missed items are fetched by
+    /// [backfill](Self::backfill).
+    fn reinstall(&mut self) -> Result<(), ConnectionError> {
+        let next_block = self.synced_block.map(|block| block + 1);
+        if let (FilterKind::Logs(filter), Some(next_block)) = (&mut self.kind, next_block) {
+            // a later start block of the filter itself is kept
+            let from_block = match &filter.from_block {
+                Some(BlockParameter::Custom(block)) => next_block.max(to_u64(*block)),
+                _ => next_block,
+            };
+            filter.from_block = Some(BlockParameter::Custom(U64::from_int_unchecked(from_block)));
+        }
+        self.id = self.kind.install(self.connection)?;
+        self.backfill = match (&self.kind, next_block) {
+            (FilterKind::Logs(_), _) => Some(Backfill::Logs),
+            (FilterKind::Blocks, Some(next)) => Some(Backfill::Blocks { next, head: None }),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Returns the items which were missed before the filter was reinstalled.
+    ///
+    /// On an error the hashes of the blocks which were fetched so far are queued, so the next
+    /// call continues with the failed block.
+    fn backfill(&mut self) -> Result<Vec<U>, ConnectionError> {
+        match self.backfill {
+            Some(Backfill::Logs) => match self.connection.call(filter_logs(self.id)) {
+                Err(ConnectionError::JsonRpc(err)) if is_filter_not_found(&err.message) => {
+                    self.reinstall()?;
+                    self.backfill()
+                }
+                logs => {
+                    let logs = logs?;
+                    self.backfill = None;
+                    Ok(logs)
+                }
+            },
+            Some(Backfill::Blocks { next, head }) => {
+                let head = match head {
+                    Some(head) => head,
+                    None => to_u64(self.connection.call(rpc::eth_block_number())?),
+                };
+                for number in next..=head {
+                    let block = match self.connection.call(block_hash(number)) {
+                        Ok(block) => block,
+                        Err(err) => {
+                            self.backfill = Some(Backfill::Blocks {
+                                next: number,
+                                head: Some(head),
+                            });
+                            return Err(err);
+                        }
+                    };
+                    self.items.extend(block.and_then(|block| block.hash));
+                }
+                self.backfill = None;
+                // the reinstalled filter yields the blocks after the head
+                self.synced_block = self.synced_block.max(Some(head));
+                Ok(Vec::new())
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl<'a, T: Request, U: DeserializeOwned + Debug> Drop for FilterWatcher<'a, T, U> {
+    fn drop(&mut self) {
+        if self.installed {
+            // the node removes the filter after a while anyway
+            let _ = self.connection.call(rpc::eth_uninstall_filter(self.id));
+        }
+    }
+}
+
+impl FilterKind {
+    /// The current block number, if the filter yields items of mined blocks
+    fn head<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Option<u64>, ConnectionError> {
+        match self {
+            FilterKind::PendingTransactions => Ok(None),
+            _ => Ok(Some(to_u64(connection.call(rpc::eth_block_number())?))),
+        }
+    }
+
+    fn install<T: Request>(&self, connection: &mut Connection<T>) -> Result<U128, ConnectionError> {
+        match self {
+            FilterKind::Logs(filter) => connection.call(rpc::eth_new_filter(filter.clone())),
+            FilterKind::Blocks => connection.call(rpc::eth_new_block_filter()),
+            FilterKind::PendingTransactions => {
+                connection.call(rpc::eth_new_pending_transaction_filter())
+            }
+        }
+    }
+}
+
+/// Same as [eth_get_filter_changes](rpc::eth_get_filter_changes), but with the item type of the
+/// filter.
+fn filter_changes<U: DeserializeOwned + Debug>(filter_id: U128) -> Rpc<Vec<U>> {
+    let mut rpc = Rpc::new("eth_getFilterChanges");
+    rpc.add_param(filter_id);
+    rpc
+}
+
+fn filter_logs<U: DeserializeOwned + Debug>(filter_id: U128) -> Rpc<Vec<U>> {
+    let mut rpc = Rpc::new("eth_getFilterLogs");
+    rpc.add_param(filter_id);
+    rpc
+}
+
+/// The hash of a block, with the item type of a block filter
+#[derive(Debug, Deserialize)]
+struct BlockHash<U> {
+    hash: Option<U>,
+}
+
+fn block_hash<U: DeserializeOwned + Debug>(number: u64) -> Rpc<Option<BlockHash<U>>> {
+    let mut rpc = Rpc::new("eth_getBlockByNumber");
+    rpc.add_param(BlockParameter::Custom(U64::from_int_unchecked(number)));
+    rpc.add_param(false);
+    rpc
+}
+
+fn to_u64(number: U64) -> u64 {
+    u64::from_be_bytes(number.into_bytes())
+}
+
+// geth and erigon respond with "filter not found", nethermind with "Filter with id: 1 does not exist"
+fn is_filter_not_found(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("filter not found")
+        || (message.contains("filter") && message.contains("does not exist"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::{json, Value};
+
+    fn log(block_number: &str) -> Value {
+        json!({
+            "address": "0x01", "topics": [], "data": "0x", "blockHash": "0x02",
+            "blockNumber": block_number, "transactionHash": "0x03", "transactionIndex": "0x0",
+            "logIndex": "0x0", "transactionLogIndex": "0x0", "removed": false,
+        })
+    }
+
+    fn methods(mock: &MockTransport) -> Vec<String> {
+        mock.requests()
+            .into_iter()
+            .map(|request| request.method)
+            .collect()
+    }
+
+    #[test]
+    fn filter_watcher_reinstalls_expired_filter() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x9");
+        mock.push_result("eth_newFilter", "0x1");
+        mock.push_result("eth_blockNumber", "0xa");
+        mock.push_result_for("eth_getFilterChanges", ["0x1"], [log("0xa")]);
+        mock.push_result("eth_blockNumber", "0xb");
+        mock.push_error("eth_getFilterChanges", -32000, "filter not found");
+        mock.push_result("eth_newFilter", "0x2");
+        mock.push_result_for("eth_getFilterLogs", ["0x2"], [log("0xc")]);
+        let mut connection = Connection::new(mock.clone());
+
+        let mut watcher = FilterWatcher::logs(&mut connection, Filter::default()).unwrap();
+        let first = watcher.next_item().unwrap();
+        assert_eq!(first.block_number, Some(U64::from_int_unchecked(10_u8)));
+        let second = watcher.next_item().unwrap();
+        assert_eq!(second.block_number, Some(U64::from_int_unchecked(12_u8)));
+        assert_eq!(watcher.id, U128::from_int_unchecked(2_u8));
+        drop(watcher);
+
+        assert_eq!(
+            methods(&mock),
+            vec![
+                "eth_blockNumber",
+                "eth_newFilter",
+                "eth_blockNumber",
+                "eth_getFilterChanges",
+                "eth_blockNumber",
+                "eth_getFilterChanges",
+                "eth_newFilter",
+                "eth_getFilterLogs",
+                "eth_uninstallFilter",
+            ]
+        );
+        // the filter is installed again after the last polled block
+        let filters = mock.requests_of("eth_newFilter");
+        assert_eq!(filters[1].params[0]["fromBlock"], "0xb");
+        assert_eq!(
+            mock.requests_of("eth_uninstallFilter")[0].params,
+            json!(["0x2"])
+        );
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn filter_watcher_reinstalls_filter_without_logs() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x5");
+        mock.push_result("eth_newFilter", "0x1");
+        mock.push_result("eth_blockNumber", "0x8");
+        mock.push_result("eth_getFilterChanges", json!([]));
+        mock.push_result("eth_blockNumber", "0x9");
+        mock.push_error("eth_getFilterChanges", -32000, "filter not found");
+        mock.push_result("eth_newFilter", "0x2");
+        mock.push_result("eth_getFilterLogs", [log("0x9")]);
+        let mut connection = Connection::new(mock.clone());
+
+        let filter = Filter {
+            from_block: Some(BlockParameter::Custom(U64::from_int_unchecked(7_u8))),
+            ..Filter::default()
+        };
+        let mut watcher = FilterWatcher::logs(&mut connection, filter)
+            .unwrap()
+            .poll_interval(Duration::from_millis(0));
+        let log = watcher.next_item().unwrap();
+        assert_eq!(log.block_number, Some(U64::from_int_unchecked(9_u8)));
+
+        // the blocks up to the head of the empty poll are skipped, not the ones since the install
+        let filters = mock.requests_of("eth_newFilter");
+        assert_eq!(filters[0].params[0]["fromBlock"], "0x7");
+        assert_eq!(filters[1].params[0]["fromBlock"], "0x9");
+    }
+
+    #[test]
+    fn filter_watcher_backfills_missed_blocks() {
+        let block = |hash: u8| json!({ "hash": H256::from_int_unchecked(hash) });
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x5");
+        mock.push_result("eth_newBlockFilter", "0x1");
+        mock.push_result("eth_blockNumber", "0x6");
+        mock.push_error("eth_getFilterChanges", -32000, "filter not found");
+        mock.push_result("eth_newBlockFilter", "0x2");
+        mock.push_result("eth_blockNumber", "0x7");
+        mock.push_result_for("eth_getBlockByNumber", ("0x6", false), block(6));
+        mock.push_result_for("eth_getBlockByNumber", ("0x7", false), block(7));
+        mock.push_result("eth_blockNumber", "0x8");
+        mock.push_result_for(
+            "eth_getFilterChanges",
+            ["0x2"],
+            [H256::from_int_unchecked(8_u8)],
+        );
+        let mut connection = Connection::new(mock.clone());
+
+        let mut watcher = FilterWatcher::blocks(&mut connection).unwrap();
+        let hashes = (0..3)
+            .map(|_| watcher.next_item().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hashes,
+            vec![
+                H256::from_int_unchecked(6_u8),
+                H256::from_int_unchecked(7_u8),
+                H256::from_int_unchecked(8_u8),
+            ]
+        );
+        assert_eq!(mock.requests_of("eth_getBlockByNumber").len(), 2);
+    }
+
+    #[test]
+    fn filter_watcher_continues_interrupted_backfill() {
+        let block = |hash: u8| json!({ "hash": H256::from_int_unchecked(hash) });
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x5");
+        mock.push_result("eth_newBlockFilter", "0x1");
+        mock.push_result("eth_blockNumber", "0x6");
+        mock.push_error("eth_getFilterChanges", -32000, "filter not found");
+        mock.push_result("eth_newBlockFilter", "0x2");
+        mock.push_result("eth_blockNumber", "0x7");
+        mock.push_result_for("eth_getBlockByNumber", ("0x6", false), block(6));
+        mock.push_error("eth_getBlockByNumber", -32005, "limit exceeded");
+        mock.push_result("eth_blockNumber", "0x8");
+        mock.push_result("eth_getBlockByNumber", block(7));
+        let mut connection = Connection::new(mock.clone());
+
+        let mut watcher = FilterWatcher::blocks(&mut connection).unwrap();
+        // the error is not mistaken for a missing block
+        match watcher.next_item() {
+            Err(ConnectionError::JsonRpc(err)) => assert_eq!(err.code, -32005),
+            other => panic!("Expected a json rpc error, got {:?}", other),
+        }
+        assert_eq!(watcher.next_item().unwrap(), H256::from_int_unchecked(6_u8));
+        assert_eq!(watcher.next_item().unwrap(), H256::from_int_unchecked(7_u8));
+        assert!(mock.is_done());
+        let blocks = mock.requests_of("eth_getBlockByNumber");
+        assert_eq!(blocks[2].params, json!(["0x7", false]));
+    }
+
+    #[test]
+    fn filter_watcher_close() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x1");
+        mock.push_result("eth_newBlockFilter", "0x1");
+        mock.push_result("eth_blockNumber", "0x1");
+        mock.push_result("eth_getFilterChanges", [H256::from_int_unchecked(1_u8)]);
+        mock.push_result("eth_uninstallFilter", true);
+        let mut connection = Connection::new(mock.clone());
+
+        let mut watcher = FilterWatcher::blocks(&mut connection)
+            .unwrap()
+            .poll_interval(Duration::from_millis(0));
+        assert_eq!(watcher.next_item().unwrap(), H256::from_int_unchecked(1_u8));
+        watcher.close().unwrap();
+
+        // closing uninstalls the filter only once
+        assert_eq!(
+            methods(&mock),
+            vec![
+                "eth_blockNumber",
+                "eth_newBlockFilter",
+                "eth_blockNumber",
+                "eth_getFilterChanges",
+                "eth_uninstallFilter"
+            ]
+        );
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "blocking")]
 mod blocking;
 mod credentials;
+#[cfg(feature = "blocking")]
+mod filter_watcher;
 #[cfg(feature = "non-blocking")]
 mod non_blocking;
 #[cfg(feature = "blocking")]
@@ -10,18 +12,30 @@ mod transport;
 #[cfg(feature = "blocking")]
 pub use blocking::Connection;
 pub use credentials::Credentials;
+#[cfg(feature = "blocking")]
+pub use filter_watcher::FilterWatcher;
 #[cfg(feature = "non-blocking")]
 pub use non_blocking::Connection as AsyncConnection;
 #[cfg(feature = "blocking")]
 pub use subscription::Subscription;
+pub use transport::cassette::Cassette;
 #[cfg(feature = "non-blocking")]
 pub use transport::http::AsyncHttp;
 #[cfg(feature = "blocking")]
 pub use transport::http::Http;
+pub use transport::mock::{MockRequest, MockTransport};
 #[cfg(target_family = "unix")]
 pub use transport::uds::Uds;
 pub use transport::websocket::WebSocket;
 
+use crate::rpc::RpcError;
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+use serde::de::{DeserializeOwned, Deserializer};
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+use serde::Deserialize;
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+use serde_json::Value;
+
 pub trait Request {
     fn request(&mut self, cmd: String) -> Result<String, ConnectionError>;
 }
@@ -40,8 +54,101 @@ pub enum ConnectionError {
     WebSocketError(String),
     HttpError(String),
     UdsError(String),
-    JsonRpc(String),
+    CassetteError(String),
+    JsonRpc(RpcError),
     Serde(String),
     SubscriptionError(String),
     NoTicketId,
 }
+
+/// Extracts the result from a raw response.
+///
+/// If the node responded with an error object, it is returned as [JsonRpc](ConnectionError::JsonRpc).
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+pub(crate) fn parse_response<U: DeserializeOwned>(response: &str) -> Result<U, ConnectionError> {
+    let envelope = serde_json::from_str::<ResponseEnvelope>(response)
+        .map_err(|e| ConnectionError::Serde(e.to_string()))?;
+    // the error is checked first, a missing result would deserialize into `None` otherwise
+    if let Some(error) = envelope.error {
+        return Err(ConnectionError::JsonRpc(error));
+    }
+    let result = envelope
+        .result
+        .ok_or_else(|| ConnectionError::Serde("The response contains no result".to_owned()))?;
+    serde_json::from_value(result).map_err(|e| ConnectionError::Serde(e.to_string()))
+}
+
+/// Either the result or the error of a response
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+#[derive(Deserialize)]
+struct ResponseEnvelope {
+    /// `Some(Value::Null)` if the result is `null`, `None` if it is missing
+    #[serde(default, deserialize_with = "present")]
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "non-blocking")))]
+mod tests {
+    use super::*;
+    use crate::types::U256;
+
+    #[test]
+    fn parse_result_response() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"0x1f"}"#;
+        let result = parse_response::<U256>(response).unwrap();
+        assert_eq!(result, U256::from_int_unchecked(31_u8));
+    }
+
+    #[test]
+    fn parse_error_response() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"0x"}}"#;
+        match parse_response::<U256>(response) {
+            Err(ConnectionError::JsonRpc(err)) => {
+                assert_eq!(err.code, 3);
+                assert_eq!(err.message, "execution reverted");
+                assert_eq!(err.data, Some(serde_json::Value::from("0x")));
+            }
+            other => panic!("Expected a json rpc error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_null_result_response() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+        assert_eq!(parse_response::<Option<U256>>(response).unwrap(), None);
+        assert!(matches!(
+            parse_response::<U256>(response),
+            Err(ConnectionError::Serde(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn parse_error_response_of_optional_result() {
+        use crate::{rpc, types::H256};
+
+        let mock = MockTransport::new();
+        mock.push_error("eth_getTransactionReceipt", -32005, "limit exceeded");
+        let mut connection = crate::Connection::new(mock);
+        let receipt = connection.call(rpc::eth_get_transaction_receipt(H256::zero()));
+        match receipt {
+            Err(ConnectionError::JsonRpc(err)) => assert_eq!(err.code, -32005),
+            other => panic!("Expected a json rpc error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_invalid_response() {
+        let response = r#"{"jsonrpc":"2.0","id":1}"#;
+        assert!(matches!(
+            parse_response::<U256>(response),
+            Err(ConnectionError::Serde(_))
+        ));
+    }
+}
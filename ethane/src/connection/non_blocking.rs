@@ -1,6 +1,6 @@
 use super::transport::http::AsyncHttp;
-use super::ConnectionError;
-use crate::rpc::{Rpc, RpcResponse};
+use super::{parse_response, ConnectionError};
+use crate::rpc::Rpc;
 
 use serde::de::DeserializeOwned;
 
@@ -31,9 +31,7 @@ impl Connection {
                         .map_err(|e| ConnectionError::Serde(e.to_string()))?,
                 )
                 .await?;
-            let result = serde_json::from_str::<RpcResponse<U>>(&result_data)
-                .map_err(|e| ConnectionError::Serde(e.to_string()))?;
-            Ok(result.result)
+            parse_response(&result_data)
         } else {
             Err(ConnectionError::NoTicketId)
         }
@@ -0,0 +1,223 @@
+//! Implementation of a transport which records requests to a file and replays them
+
+use super::super::{ConnectionError, Request};
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A recorded request and the response of the node
+struct Interaction {
+    method: String,
+    params: Value,
+    response: Value,
+}
+
+enum Mode<T: Request> {
+    Record { transport: T, file: File },
+    Replay { interactions: VecDeque<Interaction> },
+}
+
+/// A transport which records the requests of a live run to a cassette file and replays them
+/// later without a node
+///
+/// The cassette contains one JSON object per line with the `method`, `params` and `response`
+/// of a request. In replay mode the first unplayed response recorded for the same method and
+/// params is returned, so repeated requests get their responses in the recorded order. A
+/// request which was not recorded fails with a [CassetteError](ConnectionError::CassetteError).
+///
+/// Subscriptions are not supported.
+///
+/// ```no_run
+/// use ethane::{Cassette, Connection, Http};
+/// use ethane::rpc::eth_block_number;
+///
+/// let cassette = Cassette::replay_or_record("tests/cassettes/block_number.jsonl", || {
+///     Http::new("http://localhost:8545", None)
+/// })
+/// .unwrap();
+/// let mut connection = Connection::new(cassette);
+/// let block_number = connection.call(eth_block_number()).unwrap();
+/// ```
+pub struct Cassette<T: Request> {
+    path: PathBuf,
+    mode: Mode<T>,
+}
+
+impl<T: Request> Cassette<T> {
+    /// Sends the requests over `transport` and records them, overwriting an existing cassette.
+    pub fn record(transport: T, path: impl AsRef<Path>) -> Result<Self, ConnectionError> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).map_err(|e| cassette_error(&path, e))?;
+        Ok(Self {
+            path,
+            mode: Mode::Record { transport, file },
+        })
+    }
+
+    /// Answers the requests from a recorded cassette.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, ConnectionError> {
+        let path = path.as_ref().to_path_buf();
+        let content = fs::read_to_string(&path).map_err(|e| cassette_error(&path, e))?;
+        let mut interactions = VecDeque::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let mut value: Value =
+                serde_json::from_str(line).map_err(|e| cassette_error(&path, e))?;
+            interactions.push_back(Interaction {
+                method: value["method"].as_str().unwrap_or_default().to_owned(),
+                params: value["params"].take(),
+                response: value["response"].take(),
+            });
+        }
+        Ok(Self {
+            path,
+            mode: Mode::Replay { interactions },
+        })
+    }
+
+    /// Replays the cassette if it exists, otherwise records a new one with the transport
+    /// created by `connect`.
+    pub fn replay_or_record<F: FnOnce() -> T>(
+        path: impl AsRef<Path>,
+        connect: F,
+    ) -> Result<Self, ConnectionError> {
+        if path.as_ref().exists() {
+            Self::replay(path)
+        } else {
+            Self::record(connect(), path)
+        }
+    }
+
+    /// Returns `true` if the cassette is being recorded.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, Mode::Record { .. })
+    }
+
+    /// The number of recorded requests which were not replayed yet
+    pub fn unplayed(&self) -> usize {
+        match &self.mode {
+            Mode::Record { .. } => 0,
+            Mode::Replay { interactions } => interactions.len(),
+        }
+    }
+}
+
+impl<T: Request> Request for Cassette<T> {
+    fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+        let request: Value =
+            serde_json::from_str(&cmd).map_err(|e| ConnectionError::Serde(e.to_string()))?;
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let path = &self.path;
+        match &mut self.mode {
+            Mode::Record { transport, file } => {
+                let response = transport.request(cmd.clone())?;
+                let mut response_value: Value = serde_json::from_str(&response)
+                    .map_err(|e| ConnectionError::Serde(e.to_string()))?;
+                // the id depends on the order of the requests, so it is not recorded
+                if let Some(response) = response_value.as_object_mut() {
+                    response.remove("id");
+                }
+                let line = json!({
+                    "method": method,
+                    "params": params,
+                    "response": response_value,
+                });
+                writeln!(file, "{}", line).map_err(|e| cassette_error(path, e))?;
+                Ok(response)
+            }
+            Mode::Replay { interactions } => {
+                let position = interactions
+                    .iter()
+                    .position(|interaction| {
+                        interaction.method == method && interaction.params == params
+                    })
+                    .ok_or_else(|| {
+                        ConnectionError::CassetteError(format!(
+                            "No recorded response for {} with params {} in {}",
+                            method,
+                            params,
+                            path.display()
+                        ))
+                    })?;
+                let mut response = interactions.remove(position).unwrap().response;
+                response["id"] = request["id"].clone();
+                Ok(response.to_string())
+            }
+        }
+    }
+}
+
+fn cassette_error(path: &Path, err: impl ToString) -> ConnectionError {
+    ConnectionError::CassetteError(format!("{}: {}", path.display(), err.to_string()))
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use crate::rpc;
+    use crate::types::{Address, U256, U64};
+    use crate::{Connection, MockTransport};
+
+    #[test]
+    fn cassette_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("ethane-cassette-{}.jsonl", std::process::id()));
+        let address = Address::from_int_unchecked(1_u8);
+
+        let mock = MockTransport::new();
+        mock.push_result("eth_blockNumber", "0x1");
+        mock.push_result("eth_blockNumber", "0x2");
+        mock.push_result("eth_getBalance", "0x64");
+        mock.push_error("eth_chainId", -32601, "method not found");
+        let mut connection = Connection::new(Cassette::record(mock, &path).unwrap());
+        assert!(connection.transport.is_recording());
+        connection.call(rpc::eth_block_number()).unwrap();
+        connection.call(rpc::eth_block_number()).unwrap();
+        connection
+            .call(rpc::eth_get_balance(address, None))
+            .unwrap();
+        assert!(connection.call(rpc::eth_chain_id()).is_err());
+        drop(connection);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 4);
+
+        let mut connection = Connection::new(Cassette::<MockTransport>::replay(&path).unwrap());
+        assert!(!connection.transport.is_recording());
+        // the order of different requests doesn't matter
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(address, None))
+                .unwrap(),
+            U256::from_int_unchecked(100_u8)
+        );
+        assert_eq!(
+            connection.call(rpc::eth_block_number()).unwrap(),
+            U64::from_int_unchecked(1_u8)
+        );
+        assert_eq!(
+            connection.call(rpc::eth_block_number()).unwrap(),
+            U64::from_int_unchecked(2_u8)
+        );
+        // recorded errors are replayed as well
+        assert!(matches!(
+            connection.call(rpc::eth_chain_id()),
+            Err(ConnectionError::JsonRpc(_))
+        ));
+        assert_eq!(connection.transport.unplayed(), 0);
+
+        // unmatched requests fail
+        assert!(matches!(
+            connection.call(rpc::eth_block_number()),
+            Err(ConnectionError::CassetteError(_))
+        ));
+        let other_block = Some(crate::types::BlockParameter::Earliest);
+        assert!(matches!(
+            connection.call(rpc::eth_get_balance(address, other_block)),
+            Err(ConnectionError::CassetteError(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
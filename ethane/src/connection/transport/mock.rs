@@ -0,0 +1,304 @@
+//! Implementation of an in-memory transport for tests without a node
+
+use super::super::{ConnectionError, Request, Subscribe};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A request received by a [MockTransport]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockRequest {
+    pub method: String,
+    pub params: Value,
+}
+
+/// A queued response, only matching requests with the given params if they are set
+struct MockResponse {
+    method: String,
+    params: Option<Value>,
+    response: Value,
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<MockRequest>,
+    notifications: VecDeque<(Value, Value)>,
+}
+
+/// A transport which answers requests with queued responses instead of a node
+///
+/// Responses are queued by method name and optionally by params. Every request takes the first
+/// response queued for its params out of the queue, or else the first one queued for any
+/// params. Requests without a matching response fail with a "method not found"
+/// [JsonRpc](ConnectionError::JsonRpc) error. The received requests can be
+/// inspected with [requests](Self::requests).
+///
+/// Clones share the queues, so a clone can be kept to queue responses and inspect requests
+/// after the transport was moved into a [connection](crate::Connection).
+///
+/// Subscriptions read the notifications which were pushed with
+/// [push_notification](Self::push_notification) for their id. The id is taken from the
+/// queued `eth_subscribe` response. `eth_unsubscribe` is answered with `true` if there is no
+/// queued response for it.
+///
+/// ```
+/// use ethane::{Connection, MockTransport};
+/// use ethane::rpc::eth_block_number;
+/// use ethane::types::U64;
+///
+/// let mock = MockTransport::new();
+/// mock.push_result("eth_blockNumber", "0x10");
+/// let mut connection = Connection::new(mock.clone());
+///
+/// let block_number = connection.call(eth_block_number()).unwrap();
+/// assert_eq!(block_number, U64::from_int_unchecked(16_u8));
+/// assert_eq!(mock.requests()[0].method, "eth_blockNumber");
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+    subscription: Option<Value>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the result of the next request of `method`.
+    pub fn push_result<U: Serialize>(&self, method: &str, result: U) {
+        self.push(method, None, json!({ "result": result }));
+    }
+
+    /// Queues the result of the next request of `method` with exactly the given params.
+    pub fn push_result_for<P: Serialize, U: Serialize>(&self, method: &str, params: P, result: U) {
+        self.push(method, Some(to_value(params)), json!({ "result": result }));
+    }
+
+    /// Queues an error response for the next request of `method`.
+    pub fn push_error(&self, method: &str, code: i64, message: &str) {
+        self.push(
+            method,
+            None,
+            json!({ "error": { "code": code, "message": message } }),
+        );
+    }
+
+    /// Queues a notification for the subscription with the given id.
+    pub fn push_notification<I: Serialize, U: Serialize>(&self, subscription_id: I, result: U) {
+        self.state()
+            .notifications
+            .push_back((to_value(subscription_id), to_value(result)));
+    }
+
+    /// The requests received so far, oldest first
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    /// The requests of the given method received so far, oldest first
+    pub fn requests_of(&self, method: &str) -> Vec<MockRequest> {
+        self.state()
+            .requests
+            .iter()
+            .filter(|request| request.method == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns `true` if all queued responses and notifications were consumed.
+    pub fn is_done(&self) -> bool {
+        let state = self.state();
+        state.responses.is_empty() && state.notifications.is_empty()
+    }
+
+    fn push(&self, method: &str, params: Option<Value>, response: Value) {
+        self.state().responses.push_back(MockResponse {
+            method: method.to_owned(),
+            params,
+            response,
+        });
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // a panicking test must not poison the other clones
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Request for MockTransport {
+    fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+        let request: Value =
+            serde_json::from_str(&cmd).map_err(|e| ConnectionError::Serde(e.to_string()))?;
+        let method = request["method"].as_str().unwrap_or_default().to_owned();
+        let params = request
+            .get("params")
+            .cloned()
+            .unwrap_or_else(|| Value::Array(Vec::new()));
+
+        let mut state = self.state();
+        state.requests.push(MockRequest {
+            method: method.clone(),
+            params: params.clone(),
+        });
+        // responses for the exact params take precedence
+        let position = state
+            .responses
+            .iter()
+            .position(|response| {
+                response.method == method && response.params.as_ref() == Some(&params)
+            })
+            .or_else(|| {
+                state
+                    .responses
+                    .iter()
+                    .position(|response| response.method == method && response.params.is_none())
+            });
+        let mut response = match position.and_then(|position| state.responses.remove(position)) {
+            Some(response) => response.response,
+            None if method == "eth_unsubscribe" => json!({ "result": true }),
+            None => json!({ "error": {
+                "code": -32601,
+                "message": format!("No mock response for {} with params {}", method, params),
+            }}),
+        };
+        drop(state);
+
+        if method == "eth_subscribe" {
+            self.subscription = response.get("result").cloned();
+        }
+        response["jsonrpc"] = json!("2.0");
+        response["id"] = request["id"].clone();
+        Ok(response.to_string())
+    }
+}
+
+impl Subscribe for MockTransport {
+    /// Returns the next notification of the subscription, or a
+    /// [SubscriptionError](ConnectionError::SubscriptionError) if there is none.
+    fn read_next(&mut self) -> Result<String, ConnectionError> {
+        let mut state = self.state();
+        let subscription = self.subscription.as_ref();
+        let position = state
+            .notifications
+            .iter()
+            .position(|(id, _)| Some(id) == subscription)
+            .ok_or_else(|| {
+                ConnectionError::SubscriptionError(format!(
+                    "No mock notification for subscription {:?}",
+                    subscription
+                ))
+            })?;
+        let (id, result) = state.notifications.remove(position).unwrap();
+        Ok(json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscription",
+            "params": { "subscription": id, "result": result },
+        })
+        .to_string())
+    }
+
+    fn fork(&self) -> Result<Self, ConnectionError> {
+        Ok(Self {
+            state: self.state.clone(),
+            subscription: None,
+        })
+    }
+}
+
+fn to_value<U: Serialize>(value: U) -> Value {
+    serde_json::to_value(value).expect("Mock values must serialize to JSON")
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use crate::rpc;
+    use crate::types::{Address, BlockHeader, U256};
+    use crate::Connection;
+
+    #[test]
+    fn mock_transport_responses() {
+        let mock = MockTransport::new();
+        let address = Address::from_int_unchecked(1_u8);
+        mock.push_result("eth_getBalance", "0x1");
+        mock.push_result_for(
+            "eth_getBalance",
+            (address, "latest"),
+            U256::from_int_unchecked(2_u8),
+        );
+        mock.push_error("eth_chainId", -32000, "oops");
+        let mut connection = Connection::new(mock.clone());
+
+        // the response for the params is taken even though it was queued later
+        let balance = connection
+            .call(rpc::eth_get_balance(address, None))
+            .unwrap();
+        assert_eq!(balance, U256::from_int_unchecked(2_u8));
+        let balance = connection
+            .call(rpc::eth_get_balance(address, None))
+            .unwrap();
+        assert_eq!(balance, U256::from_int_unchecked(1_u8));
+        match connection.call(rpc::eth_chain_id()) {
+            Err(ConnectionError::JsonRpc(err)) => assert_eq!(err.message, "oops"),
+            result => panic!("Expected an error, got {:?}", result),
+        }
+        match connection.call(rpc::eth_chain_id()) {
+            Err(ConnectionError::JsonRpc(err)) => assert_eq!(err.code, -32601),
+            result => panic!("Expected an error, got {:?}", result),
+        }
+
+        assert!(mock.is_done());
+        assert_eq!(mock.requests().len(), 4);
+        assert_eq!(
+            mock.requests_of("eth_getBalance")[0],
+            MockRequest {
+                method: "eth_getBalance".to_owned(),
+                params: json!(["0x0000000000000000000000000000000000000001", "latest"]),
+            }
+        );
+    }
+
+    #[test]
+    fn mock_transport_subscriptions() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_subscribe", "0x1");
+        mock.push_result("eth_subscribe", "0x2");
+        let mut connection = Connection::new(mock.clone());
+        let mut heads = connection
+            .subscribe(rpc::eth_subscribe_new_heads())
+            .unwrap();
+        let mut transactions = connection
+            .subscribe(rpc::eth_subscribe_new_pending_transactions())
+            .unwrap();
+
+        let hash = format!("0x{:064x}", 3);
+        mock.push_notification("0x2", &hash);
+        mock.push_notification(
+            "0x1",
+            json!({
+                "number": "0x1", "hash": hash, "parentHash": hash, "transactionsRoot": hash,
+                "stateRoot": hash, "receiptsRoot": hash, "difficulty": "0x0", "sha3Uncles": hash,
+                "miner": "0x0000000000000000000000000000000000000000", "gasLimit": "0x0",
+                "gasUsed": "0x0", "extraData": "0x"
+            }),
+        );
+        let head: BlockHeader = heads.next_item().unwrap();
+        assert_eq!(head.parent_hash.to_string(), hash);
+        assert_eq!(transactions.next_item().unwrap().to_string(), hash);
+        assert!(matches!(
+            heads.next_item(),
+            Err(ConnectionError::SubscriptionError(_))
+        ));
+
+        drop(heads);
+        drop(transactions);
+        assert_eq!(mock.requests_of("eth_unsubscribe").len(), 2);
+        assert!(mock.is_done());
+    }
+}
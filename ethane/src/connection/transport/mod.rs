@@ -1,4 +1,6 @@
+pub mod cassette;
 pub mod http;
+pub mod mock;
 #[cfg(target_family = "unix")]
 pub mod uds;
 pub mod websocket;
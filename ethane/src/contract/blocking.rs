@@ -1,9 +1,9 @@
-use crate::types::{Address, Bytes, Call, TransactionRequest};
+use crate::types::Address;
 use crate::{rpc, Connection, Request};
 use ethane_abi::{Abi, Parameter, StateMutability};
 use std::path::Path;
 
-use super::{CallOpts, CallResult, CallType};
+use super::{CallOpts, CallResult, CallType, ContractError};
 
 pub struct Caller<T: Request> {
     abi: Abi,
@@ -19,29 +19,29 @@ where
         connection: Connection<T>,
         abi_json: serde_json::Value,
         contract_address: Address,
-    ) -> Caller<T> {
+    ) -> Result<Caller<T>, ContractError> {
         let mut abi = Abi::new();
-        abi.parse_json(abi_json).expect("unable to parse abi");
-        Caller {
+        abi.parse_json(abi_json).map_err(ContractError::Abi)?;
+        Ok(Caller {
             abi,
             contract_address,
             connection,
-        }
+        })
     }
 
     pub fn new_from_path(
         connection: Connection<T>,
         path: &str,
         contract_address: Address,
-    ) -> Caller<T> {
+    ) -> Result<Caller<T>, ContractError> {
         let mut abi = Abi::new();
         abi.parse_file(Path::new(path))
-            .expect("unable to parse abi");
-        Caller {
+            .map_err(ContractError::Abi)?;
+        Ok(Caller {
             abi,
             contract_address,
             connection,
-        }
+        })
     }
 
     pub fn call(
@@ -49,7 +49,7 @@ where
         function_name: &str,
         params: Vec<Parameter>,
         opts: Option<CallOpts>,
-    ) -> CallResult {
+    ) -> Result<CallResult, ContractError> {
         let mut call_type = if let Some(m) = self.abi.get_state_mutability(function_name) {
             match m {
                 StateMutability::Pure => CallType::Call,
@@ -61,49 +61,59 @@ where
             CallType::Transaction
         };
 
-        let mut from_address: Address = Default::default();
-        if let Some(o) = opts {
-            from_address = o.from.unwrap();
-            if let Some(ct) = o.force_call_type {
-                call_type = ct;
-            }
+        let mut opts = opts.unwrap_or_default();
+        if let Some(ct) = opts.force_call_type.take() {
+            call_type = ct;
         }
 
-        let data = self.abi.encode(function_name, params).unwrap();
+        let data = self
+            .abi
+            .encode(function_name, params)
+            .map_err(ContractError::Encoding)?;
 
         match call_type {
-            CallType::Transaction => self.eth_send_transaction(data, from_address),
-            CallType::Call => self.eth_call(function_name, data),
+            CallType::Transaction => self.eth_send_transaction(data, opts),
+            CallType::Call => self.eth_call(function_name, data, opts),
         }
     }
 
-    fn eth_call(&mut self, function_name: &str, data: Vec<u8>) -> CallResult {
-        let payload = Call {
-            to: self.contract_address,
-            data: Some(Bytes::from_slice(&data)),
-            ..Default::default()
-        };
-
-        let call_result = self.connection.call(rpc::eth_call(payload, None)).unwrap();
-        CallResult::Call(
+    fn eth_call(
+        &mut self,
+        function_name: &str,
+        data: Vec<u8>,
+        opts: CallOpts,
+    ) -> Result<CallResult, ContractError> {
+        let payload = opts.call(self.contract_address, &data);
+        let call_result = self
+            .connection
+            .call(rpc::eth_call(payload, opts.block))
+            .map_err(|e| ContractError::from_connection_error(e, &self.abi))?;
+        Ok(CallResult::Call(
             self.abi
                 .decode(function_name, call_result.0.as_slice())
-                .unwrap(),
-        )
+                .map_err(ContractError::Decoding)?,
+        ))
     }
 
-    fn eth_send_transaction(&mut self, data: Vec<u8>, from_address: Address) -> CallResult {
-        let payload = TransactionRequest {
-            from: from_address,
-            to: Some(self.contract_address),
-            data: Some(Bytes::from_slice(&data)),
-            ..Default::default()
-        };
+    fn eth_send_transaction(
+        &mut self,
+        data: Vec<u8>,
+        opts: CallOpts,
+    ) -> Result<CallResult, ContractError> {
+        let mut payload = opts.transaction(self.contract_address, &data);
+        if payload.gas.is_none() {
+            let gas_call = opts.gas_call(self.contract_address, &data);
+            payload.gas = Some(
+                self.connection
+                    .call(rpc::eth_estimate_gas(gas_call, None))
+                    .map_err(|e| ContractError::from_connection_error(e, &self.abi))?,
+            );
+        }
 
-        CallResult::Transaction(
+        Ok(CallResult::Transaction(
             self.connection
                 .call(rpc::eth_send_transaction(payload))
-                .unwrap(),
-        )
+                .map_err(|e| ContractError::from_connection_error(e, &self.abi))?,
+        ))
     }
 }
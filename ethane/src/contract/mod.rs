@@ -2,17 +2,78 @@
 mod blocking;
 #[cfg(feature = "blocking")]
 pub use blocking::Caller;
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+mod multicall;
+#[cfg(any(feature = "blocking", feature = "non-blocking"))]
+pub use multicall::{Multicall, MulticallResult, MULTICALL3_ADDRESS};
 #[cfg(feature = "non-blocking")]
 mod non_blocking;
 #[cfg(feature = "non-blocking")]
 pub use non_blocking::Caller as AsyncCaller;
 
-use crate::types::{Address, H256};
-use ethane_abi::Parameter;
+use crate::types::{Address, BlockParameter, Bytes, Call, GasCall, TransactionRequest, H256, U256};
+use crate::ConnectionError;
+use ethane_abi::{Abi, AbiParserError, Parameter, Revert};
+use std::convert::TryFrom;
 
+/// Options of a contract call.
+///
+/// Fields which are not set are left for the node to decide, except for `gas`
+/// which is estimated with `eth_estimateGas` before sending a transaction.
+#[derive(Default)]
 pub struct CallOpts {
     pub force_call_type: Option<CallType>,
     pub from: Option<Address>,
+    pub value: Option<U256>,
+    pub gas: Option<U256>,
+    pub gas_price: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub nonce: Option<U256>,
+    /// The block at which an `eth_call` is executed, defaults to `BlockParameter::Latest`
+    pub block: Option<BlockParameter>,
+}
+
+impl CallOpts {
+    fn call(&self, to: Address, data: &[u8]) -> Call {
+        Call {
+            from: self.from,
+            to,
+            gas: self.gas,
+            gas_price: self.gas_price,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            value: self.value,
+            data: Some(Bytes::from_slice(data)),
+        }
+    }
+
+    fn gas_call(&self, to: Address, data: &[u8]) -> GasCall {
+        GasCall {
+            from: self.from,
+            to: Some(to),
+            gas: None,
+            gas_price: self.gas_price,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            value: self.value,
+            data: Some(Bytes::from_slice(data)),
+        }
+    }
+
+    fn transaction(&self, to: Address, data: &[u8]) -> TransactionRequest {
+        TransactionRequest {
+            from: self.from.unwrap_or_default(),
+            to: Some(to),
+            gas: self.gas,
+            gas_price: self.gas_price,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            value: self.value,
+            data: Some(Bytes::from_slice(data)),
+            nonce: self.nonce,
+        }
+    }
 }
 
 pub enum CallType {
@@ -24,3 +85,174 @@ pub enum CallResult {
     Transaction(H256),
     Call(Vec<Parameter>),
 }
+
+/// Wraps the different errors that may occur during a contract call.
+#[derive(Debug)]
+pub enum ContractError {
+    /// The contract ABI could not be parsed
+    Abi(AbiParserError),
+    /// The function call could not be encoded with the given parameters
+    Encoding(AbiParserError),
+    /// The returned data could not be decoded into the function outputs
+    Decoding(AbiParserError),
+    /// The request failed on the transport or the node returned an error
+    Transport(ConnectionError),
+    /// The execution was reverted by the contract.
+    ///
+    /// Contains the message of the node and the decoded reason, if the node returned the revert data.
+    Revert {
+        message: String,
+        reason: Option<Revert>,
+    },
+}
+
+impl ContractError {
+    /// Converts a connection error and decodes the revert data with the given ABI, so custom
+    /// errors declared in the ABI are recognized.
+    pub fn from_connection_error(err: ConnectionError, abi: &Abi) -> Self {
+        match err {
+            // geth responds with "execution reverted" and code 3, ganache with "VM Exception [...] revert"
+            ConnectionError::JsonRpc(rpc_error)
+                if rpc_error.code == 3 || rpc_error.message.contains("revert") =>
+            {
+                let reason = rpc_error
+                    .data
+                    .as_ref()
+                    .and_then(revert_data)
+                    .and_then(|data| abi.decode_revert(&data.0).ok());
+                Self::Revert {
+                    message: rpc_error.message,
+                    reason,
+                }
+            }
+            err => Self::Transport(err),
+        }
+    }
+}
+
+impl From<ConnectionError> for ContractError {
+    /// Only the builtin `Error(string)` and `Panic(uint256)` reasons are decoded.
+    fn from(err: ConnectionError) -> Self {
+        Self::from_connection_error(err, &Abi::new())
+    }
+}
+
+/// Extracts the revert data from the `data` field of an error response.
+///
+/// Geth returns the hex encoded data directly, while other nodes wrap it into an object,
+/// e.g. `{"data": "0x..."}` or ganache's `{"0x<tx hash>": {"return": "0x..."}}`.
+fn revert_data(data: &serde_json::Value) -> Option<Bytes> {
+    match data {
+        serde_json::Value::String(hex) if hex.starts_with("0x") => {
+            Bytes::try_from(hex.as_str()).ok()
+        }
+        serde_json::Value::Object(map) => map.get("data").and_then(revert_data).or_else(|| {
+            map.values()
+                .find_map(|value| value.get("return").and_then(revert_data))
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcError;
+    use ethane_abi::PanicCode;
+
+    #[test]
+    fn call_opts_payloads() {
+        let from = Address::from_int_unchecked(1_u8);
+        let to = Address::from_int_unchecked(2_u8);
+        let opts = CallOpts {
+            from: Some(from),
+            value: Some(U256::from_int_unchecked(100_u8)),
+            max_fee_per_gas: Some(U256::from_int_unchecked(30_u8)),
+            nonce: Some(U256::from_int_unchecked(7_u8)),
+            ..Default::default()
+        };
+
+        let call = opts.call(to, &[0xab]);
+        assert_eq!(call.from, Some(from));
+        assert_eq!(call.to, to);
+        assert_eq!(call.value, Some(U256::from_int_unchecked(100_u8)));
+        assert_eq!(call.data, Some(Bytes::from_slice(&[0xab])));
+
+        let gas_call = opts.gas_call(to, &[0xab]);
+        assert_eq!(gas_call.to, Some(to));
+        assert_eq!(gas_call.gas, None);
+        assert_eq!(
+            gas_call.max_fee_per_gas,
+            Some(U256::from_int_unchecked(30_u8))
+        );
+
+        let transaction = opts.transaction(to, &[0xab]);
+        assert_eq!(transaction.from, from);
+        assert_eq!(transaction.gas, None);
+        assert_eq!(transaction.nonce, Some(U256::from_int_unchecked(7_u8)));
+        assert_eq!(
+            serde_json::to_string(&transaction).unwrap(),
+            "{\"from\":\"0x0000000000000000000000000000000000000001\",\
+            \"to\":\"0x0000000000000000000000000000000000000002\",\
+            \"maxFeePerGas\":\"0x1e\",\"value\":\"0x64\",\"data\":\"0xab\",\"nonce\":\"0x7\"}"
+        );
+    }
+
+    #[test]
+    fn contract_error_from_connection_error() {
+        let reverted = ConnectionError::JsonRpc(RpcError {
+            code: 3,
+            message: String::from("execution reverted: not enough balance"),
+            data: Some(serde_json::Value::from(
+                "0x08c379a0\
+                0000000000000000000000000000000000000000000000000000000000000020\
+                0000000000000000000000000000000000000000000000000000000000000012\
+                6e6f7420656e6f7567682062616c616e63650000000000000000000000000000",
+            )),
+        });
+        match ContractError::from(reverted) {
+            ContractError::Revert {
+                message,
+                reason: Some(Revert::Error(reason)),
+            } => {
+                assert_eq!(message, "execution reverted: not enough balance");
+                assert_eq!(reason, "not enough balance");
+            }
+            err => panic!("Expected a revert, got {:?}", err),
+        }
+
+        let reverted = ConnectionError::JsonRpc(RpcError {
+            code: -32000,
+            message: String::from("VM Exception while processing transaction: revert"),
+            data: Some(serde_json::json!({
+                "0x1c2ac2bd9d1b6c9ec9ca0c1cd6b1f1b0c4e6a1ffd1cf9f4a2a1b2b8c3d4e5f60": {
+                    "error": "revert",
+                    "return": "0x4e487b710000000000000000000000000000000000000000000000000000000000000001"
+                }
+            })),
+        });
+        match ContractError::from(reverted) {
+            ContractError::Revert {
+                reason: Some(Revert::Panic(code)),
+                ..
+            } => assert_eq!(code, PanicCode::AssertionFailed),
+            err => panic!("Expected a panic, got {:?}", err),
+        }
+
+        let failed = ConnectionError::JsonRpc(RpcError {
+            code: -32000,
+            message: String::from("insufficient funds for transfer"),
+            data: None,
+        });
+        assert!(matches!(
+            ContractError::from(failed),
+            ContractError::Transport(ConnectionError::JsonRpc(_))
+        ));
+
+        let http = ConnectionError::HttpError(String::from("connection refused"));
+        assert!(matches!(
+            ContractError::from(http),
+            ContractError::Transport(ConnectionError::HttpError(_))
+        ));
+    }
+}
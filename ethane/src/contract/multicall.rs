@@ -0,0 +1,328 @@
+use super::ContractError;
+use crate::types::{Address, BlockParameter, Bytes, Call};
+#[cfg(feature = "non-blocking")]
+use crate::AsyncConnection;
+#[cfg(feature = "blocking")]
+use crate::{Connection, Request};
+use ethane_abi::{Abi, AbiParserError, Parameter};
+use std::convert::TryFrom;
+
+/// Address of the [Multicall3](https://github.com/mds1/multicall) contract, which is deployed at
+/// the same address on most chains.
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+const MULTICALL3_ABI: &str = r#"[{
+    "inputs": [{
+        "components": [
+            { "name": "target", "type": "address" },
+            { "name": "allowFailure", "type": "bool" },
+            { "name": "callData", "type": "bytes" }
+        ],
+        "name": "calls",
+        "type": "tuple[]"
+    }],
+    "name": "aggregate3",
+    "outputs": [{
+        "components": [
+            { "name": "success", "type": "bool" },
+            { "name": "returnData", "type": "bytes" }
+        ],
+        "name": "returnData",
+        "type": "tuple[]"
+    }],
+    "stateMutability": "payable",
+    "type": "function"
+}]"#;
+
+/// The decoded outputs of a single call or the reason of its failure.
+pub type MulticallResult = Result<Vec<Parameter>, ContractError>;
+
+struct MulticallEntry<'a> {
+    target: Address,
+    abi: &'a Abi,
+    function_name: String,
+    data: Vec<u8>,
+}
+
+/// Batches contract reads into a single `eth_call` of the Multicall3 `aggregate3` function.
+///
+/// Every call is decoded with its own ABI. A failing call doesn't fail the whole batch, its
+/// revert reason is returned in place of its outputs instead.
+pub struct Multicall<'a> {
+    abi: Abi,
+    address: Address,
+    block: Option<BlockParameter>,
+    entries: Vec<MulticallEntry<'a>>,
+}
+
+impl<'a> Default for Multicall<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Multicall<'a> {
+    /// Creates a new batch which is executed by the contract at [`MULTICALL3_ADDRESS`].
+    pub fn new() -> Self {
+        // unwrap is fine, the address is a valid constant
+        Self::with_address(Address::try_from(MULTICALL3_ADDRESS).unwrap())
+    }
+
+    /// Creates a new batch which is executed by a Multicall3 contract deployed at `address`.
+    pub fn with_address(address: Address) -> Self {
+        let mut abi = Abi::new();
+        // unwrap is fine, the ABI is a valid constant
+        abi.parse_json(serde_json::from_str(MULTICALL3_ABI).unwrap())
+            .unwrap();
+        Self {
+            abi,
+            address,
+            block: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets the block at which the batch is executed, defaults to `BlockParameter::Latest`.
+    pub fn at_block(mut self, block: BlockParameter) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Adds a call of `function_name` on the contract at `target` to the batch.
+    ///
+    /// The call is encoded immediately, so invalid parameters are reported here.
+    pub fn add(
+        &mut self,
+        target: Address,
+        abi: &'a Abi,
+        function_name: &str,
+        params: Vec<Parameter>,
+    ) -> Result<&mut Self, ContractError> {
+        let data = abi
+            .encode(function_name, params)
+            .map_err(ContractError::Encoding)?;
+        self.entries.push(MulticallEntry {
+            target,
+            abi,
+            function_name: function_name.to_owned(),
+            data,
+        });
+        Ok(self)
+    }
+
+    /// Returns the number of calls in the batch.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Executes the batch over a blocking connection.
+    ///
+    /// The results are in the same order as the calls were added.
+    #[cfg(feature = "blocking")]
+    pub fn call<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Vec<MulticallResult>, ContractError> {
+        let return_data = connection.call(crate::rpc::eth_call(self.payload()?, self.block))?;
+        self.decode(&return_data.0)
+    }
+
+    /// Executes the batch over a non-blocking connection.
+    ///
+    /// The results are in the same order as the calls were added.
+    #[cfg(feature = "non-blocking")]
+    pub async fn call_async(
+        &self,
+        connection: &mut AsyncConnection,
+    ) -> Result<Vec<MulticallResult>, ContractError> {
+        let return_data = connection
+            .call(crate::rpc::eth_call(self.payload()?, self.block))
+            .await?;
+        self.decode(&return_data.0)
+    }
+
+    fn payload(&self) -> Result<Call, ContractError> {
+        let calls = self
+            .entries
+            .iter()
+            .map(|entry| {
+                Parameter::Tuple(vec![
+                    Parameter::from(entry.target),
+                    Parameter::from(true),
+                    Parameter::new_bytes(&entry.data),
+                ])
+            })
+            .collect();
+        let data = self
+            .abi
+            .encode("aggregate3", vec![Parameter::Array(calls)])
+            .map_err(ContractError::Encoding)?;
+
+        Ok(Call {
+            to: self.address,
+            data: Some(Bytes::from_slice(&data)),
+            ..Default::default()
+        })
+    }
+
+    fn decode(&self, return_data: &[u8]) -> Result<Vec<MulticallResult>, ContractError> {
+        let results = match self
+            .abi
+            .decode("aggregate3", return_data)
+            .map_err(ContractError::Decoding)?
+            .pop()
+        {
+            Some(Parameter::Array(results)) if results.len() == self.entries.len() => results,
+            _ => {
+                return Err(ContractError::Decoding(AbiParserError::InvalidAbiEncoding(
+                    format!(
+                        "Multicall returned an invalid number of results, expected {}",
+                        self.entries.len()
+                    ),
+                )))
+            }
+        };
+
+        Ok(self
+            .entries
+            .iter()
+            .zip(results)
+            .map(|(entry, result)| match result {
+                Parameter::Tuple(mut result) if result.len() == 2 => {
+                    let return_data = match result.pop() {
+                        Some(Parameter::Bytes(data)) => data,
+                        _ => Vec::new(),
+                    };
+                    match result.pop() {
+                        Some(Parameter::Bool(success)) if success.as_bytes()[31] != 0 => entry
+                            .abi
+                            .decode(&entry.function_name, &return_data)
+                            .map_err(ContractError::Decoding),
+                        _ => Err(ContractError::Revert {
+                            message: format!("Call of {} reverted", entry.function_name),
+                            reason: entry.abi.decode_revert(&return_data).ok(),
+                        }),
+                    }
+                }
+                _ => Err(ContractError::Decoding(AbiParserError::InvalidAbiEncoding(
+                    "Invalid multicall result".to_owned(),
+                ))),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethane_abi::Revert;
+    use hex_literal::hex;
+
+    fn erc20_abi() -> Abi {
+        let mut abi = Abi::new();
+        abi.parse_json(serde_json::json!([{
+            "inputs": [{ "name": "account", "type": "address" }],
+            "name": "balanceOf",
+            "outputs": [{ "name": "", "type": "uint256" }],
+            "stateMutability": "view",
+            "type": "function"
+        }]))
+        .unwrap();
+        abi
+    }
+
+    #[test]
+    fn multicall_payload() {
+        let abi = erc20_abi();
+        let mut multicall = Multicall::new();
+        multicall
+            .add(
+                Address::from_int_unchecked(0xaa_u8),
+                &abi,
+                "balanceOf",
+                vec![Parameter::from(Address::from_int_unchecked(0xbb_u8))],
+            )
+            .unwrap();
+        assert!(multicall
+            .add(Address::zero(), &abi, "balanceOf", vec![])
+            .is_err());
+        assert_eq!(multicall.len(), 1);
+
+        let call = multicall.payload().unwrap();
+        assert_eq!(call.to, Address::try_from(MULTICALL3_ADDRESS).unwrap());
+        let expected = hex!(
+            "82ad56cb
+            0000000000000000000000000000000000000000000000000000000000000020
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000020
+            00000000000000000000000000000000000000000000000000000000000000aa
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000060
+            0000000000000000000000000000000000000000000000000000000000000024
+            70a0823100000000000000000000000000000000000000000000000000000000
+            000000bb00000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(call.data.unwrap().0, expected.to_vec());
+    }
+
+    #[test]
+    fn multicall_results() {
+        let abi = erc20_abi();
+        let mut multicall = Multicall::new().at_block(BlockParameter::Earliest);
+        for _ in 0..2 {
+            multicall
+                .add(
+                    Address::zero(),
+                    &abi,
+                    "balanceOf",
+                    vec![Parameter::from(Address::zero())],
+                )
+                .unwrap();
+        }
+        assert_eq!(multicall.block, Some(BlockParameter::Earliest));
+
+        // the first call returns 42, the second one panics with an arithmetic overflow
+        let return_data = hex!(
+            "0000000000000000000000000000000000000000000000000000000000000020
+            0000000000000000000000000000000000000000000000000000000000000002
+            0000000000000000000000000000000000000000000000000000000000000040
+            00000000000000000000000000000000000000000000000000000000000000c0
+            0000000000000000000000000000000000000000000000000000000000000001
+            0000000000000000000000000000000000000000000000000000000000000040
+            0000000000000000000000000000000000000000000000000000000000000020
+            000000000000000000000000000000000000000000000000000000000000002a
+            0000000000000000000000000000000000000000000000000000000000000000
+            0000000000000000000000000000000000000000000000000000000000000040
+            0000000000000000000000000000000000000000000000000000000000000024
+            4e487b7100000000000000000000000000000000000000000000000000000000
+            0000001100000000000000000000000000000000000000000000000000000000"
+        );
+        let results = multicall.decode(&return_data).unwrap();
+        assert_eq!(results.len(), 2);
+        match &results[0] {
+            Ok(outputs) => assert_eq!(outputs[0].to_string(), "42"),
+            Err(err) => panic!("Expected a successful call, got {:?}", err),
+        }
+        match &results[1] {
+            Err(ContractError::Revert {
+                reason: Some(Revert::Panic(code)),
+                ..
+            }) => assert_eq!(code.code(), 0x11),
+            _ => panic!("Expected a reverted call"),
+        }
+
+        // the number of results doesn't match the number of calls
+        let return_data = hex!(
+            "0000000000000000000000000000000000000000000000000000000000000020
+            0000000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert!(matches!(
+            multicall.decode(&return_data),
+            Err(ContractError::Decoding(_))
+        ));
+    }
+}
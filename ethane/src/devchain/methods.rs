@@ -0,0 +1,516 @@
+//! The JSON-RPC methods of a [DevChain](super::DevChain)
+
+use super::state::{
+    keccak, rpc_error, ChainState, DevFilter, DevSubscription, DevTransaction, LogFilter,
+    SubscriptionKind, GAS_PRICE, TRANSFER_GAS,
+};
+use crate::rpc::RpcError;
+use crate::types::{Address, Bytes, H256, U128, U256, U64};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+
+/// The fields of a transaction in `eth_sendTransaction`, `eth_call` and `eth_estimateGas`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionParams {
+    from: Option<Address>,
+    to: Option<Address>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    value: Option<U256>,
+    #[serde(alias = "input")]
+    data: Option<Bytes>,
+    nonce: Option<U256>,
+}
+
+/// Executes a JSON-RPC method and returns its result.
+pub(super) fn handle(
+    chain: &mut ChainState,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "web3_clientVersion" => Ok(json!(format!(
+            "ethane-devchain/{}",
+            env!("CARGO_PKG_VERSION")
+        ))),
+        "web3_sha3" => Ok(json!(keccak(&param::<Bytes>(params, 0)?.0))),
+        "net_version" => Ok(json!(chain.chain_id.to_string())),
+        "net_listening" => Ok(json!(true)),
+        "net_peerCount" => Ok(json!("0x0")),
+        "eth_chainId" => Ok(json!(U64::from_int_unchecked(chain.chain_id))),
+        "eth_syncing" => Ok(json!(false)),
+        "eth_mining" => Ok(json!(true)),
+        "eth_hashrate" => Ok(json!("0x0")),
+        "eth_coinbase" => Ok(json!(chain.coinbase())),
+        "eth_gasPrice" | "eth_maxPriorityFeePerGas" => {
+            Ok(json!(U256::from_int_unchecked(GAS_PRICE)))
+        }
+        "eth_accounts" => Ok(json!(chain.accounts)),
+        "eth_blockNumber" => Ok(json!(U64::from_int_unchecked(chain.head().number))),
+        "eth_getBalance" => {
+            let account = chain.account_at(&param(params, 0)?, param(params, 1)?)?;
+            Ok(json!(U256::from_int_unchecked(account.balance)))
+        }
+        "eth_getTransactionCount" => {
+            let account = chain.account_at(&param(params, 0)?, param(params, 1)?)?;
+            Ok(json!(U64::from_int_unchecked(account.nonce)))
+        }
+        "eth_getCode" => {
+            let account = chain.account_at(&param(params, 0)?, param(params, 1)?)?;
+            Ok(json!(Bytes(account.code)))
+        }
+        "eth_getStorageAt" => {
+            let account = chain.account_at(&param(params, 0)?, param(params, 2)?)?;
+            let slot: U256 = param(params, 1)?;
+            Ok(json!(account
+                .storage
+                .get(&slot)
+                .copied()
+                .unwrap_or_default()))
+        }
+        "eth_getBlockByNumber" => Ok(chain
+            .find_block(param(params, 0)?)
+            .map(|block| chain.block_json(block, param(params, 1).unwrap_or(false)))
+            .unwrap_or(Value::Null)),
+        "eth_getBlockByHash" => Ok(chain
+            .find_block_by_hash(&param(params, 0)?)
+            .map(|block| chain.block_json(block, param(params, 1).unwrap_or(false)))
+            .unwrap_or(Value::Null)),
+        "eth_getBlockTransactionCountByNumber" => {
+            let block = chain.block(param(params, 0)?)?;
+            Ok(json!(U64::from_int_unchecked(
+                block.transactions.len() as u64
+            )))
+        }
+        "eth_getBlockTransactionCountByHash" => {
+            let block = chain
+                .find_block_by_hash(&param(params, 0)?)
+                .ok_or_else(|| rpc_error(-32000, "Unknown block"))?;
+            Ok(json!(U64::from_int_unchecked(
+                block.transactions.len() as u64
+            )))
+        }
+        "eth_getUncleCountByBlockNumber" | "eth_getUncleCountByBlockHash" => Ok(json!("0x0")),
+        "eth_getTransactionByHash" => Ok(chain
+            .data
+            .transactions
+            .get(&param(params, 0)?)
+            .map(|transaction| chain.transaction_json(transaction))
+            .unwrap_or(Value::Null)),
+        "eth_getTransactionByBlockNumberAndIndex" | "eth_getTransactionByBlockHashAndIndex" => {
+            let block = if method == "eth_getTransactionByBlockHashAndIndex" {
+                chain.find_block_by_hash(&param(params, 0)?)
+            } else {
+                chain.find_block(param(params, 0)?)
+            };
+            let index = quantity(&params[1])? as usize;
+            Ok(block
+                .and_then(|block| block.transactions.get(index))
+                .map(|hash| chain.transaction_json(&chain.data.transactions[hash]))
+                .unwrap_or(Value::Null))
+        }
+        "eth_getTransactionReceipt" => Ok(chain
+            .data
+            .transactions
+            .get(&param(params, 0)?)
+            .map(|transaction| chain.receipt_json(transaction))
+            .unwrap_or(Value::Null)),
+        "eth_getBlockReceipts" => Ok(chain
+            .find_block(param(params, 0)?)
+            .map(|block| {
+                block
+                    .transactions
+                    .iter()
+                    .map(|hash| chain.receipt_json(&chain.data.transactions[hash]))
+                    .collect()
+            })
+            .unwrap_or(Value::Null)),
+        "eth_sendTransaction" => send_transaction(chain, param(params, 0)?),
+        "eth_call" => {
+            let call: TransactionParams = param(params, 0)?;
+            let account = chain.account_at(&call.to.unwrap_or_default(), param(params, 1)?)?;
+            if account.code.is_empty() {
+                Ok(json!("0x"))
+            } else {
+                Err(unsupported_execution())
+            }
+        }
+        "eth_estimateGas" => {
+            let call: TransactionParams = param(params, 0)?;
+            match call.to {
+                Some(to) if chain.account(&to).code.is_empty() => {
+                    let input = call.data.map(|data| data.0).unwrap_or_default();
+                    Ok(json!(U64::from_int_unchecked(intrinsic_gas(&input))))
+                }
+                _ => Err(unsupported_execution()),
+            }
+        }
+        "eth_getLogs" => Ok(json!(chain.filter_logs(&log_filter(&params[0])?)?)),
+        "eth_newFilter" => {
+            let filter = DevFilter::Logs {
+                filter: log_filter(&params[0])?,
+                next_block: chain.head().number + 1,
+            };
+            Ok(json!(install_filter(chain, filter)))
+        }
+        "eth_newBlockFilter" => {
+            let filter = DevFilter::Blocks {
+                next_block: chain.head().number + 1,
+            };
+            Ok(json!(install_filter(chain, filter)))
+        }
+        "eth_newPendingTransactionFilter" => {
+            let filter = DevFilter::PendingTransactions { hashes: Vec::new() };
+            Ok(json!(install_filter(chain, filter)))
+        }
+        "eth_getFilterChanges" => filter_changes(chain, quantity(&params[0])?),
+        "eth_getFilterLogs" => match chain.filters.get(&quantity(&params[0])?) {
+            Some(DevFilter::Logs { filter, .. }) => Ok(json!(chain.filter_logs(filter)?)),
+            _ => Err(rpc_error(-32000, "filter not found")),
+        },
+        "eth_uninstallFilter" => Ok(json!(chain
+            .filters
+            .remove(&quantity(&params[0])?)
+            .is_some())),
+        "eth_subscribe" => {
+            let kind = match param::<String>(params, 0)?.as_str() {
+                "newHeads" => SubscriptionKind::NewHeads,
+                "newPendingTransactions" => SubscriptionKind::NewPendingTransactions,
+                "logs" => SubscriptionKind::Logs(log_filter(&params[1])?),
+                kind => {
+                    return Err(rpc_error(
+                        -32602,
+                        format!("Unsupported subscription {}", kind),
+                    ))
+                }
+            };
+            let id = chain.next_id();
+            chain.subscriptions.insert(
+                id,
+                DevSubscription {
+                    kind,
+                    notifications: VecDeque::new(),
+                },
+            );
+            Ok(json!(U128::from_int_unchecked(id)))
+        }
+        "eth_unsubscribe" => Ok(json!(chain
+            .subscriptions
+            .remove(&quantity(&params[0])?)
+            .is_some())),
+        "evm_snapshot" => {
+            let id = chain.next_id();
+            chain.snapshots.insert(id, chain.data.clone());
+            Ok(json!(U128::from_int_unchecked(id)))
+        }
+        "evm_revert" => {
+            let id = quantity(&params[0])?;
+            match chain.snapshots.remove(&id) {
+                Some(data) => {
+                    chain.data = data;
+                    // later snapshots are invalidated as well
+                    chain.snapshots.retain(|snapshot, _| *snapshot < id);
+                    Ok(json!(true))
+                }
+                None => Ok(json!(false)),
+            }
+        }
+        "evm_mine" => {
+            let timestamp = optional_quantity(&params[0])?.map(|timestamp| timestamp as u64);
+            chain.mine(Vec::new(), timestamp);
+            Ok(json!("0x0"))
+        }
+        "anvil_mine" | "hardhat_mine" => {
+            let blocks = optional_quantity(&params[0])?.unwrap_or(1);
+            let interval = optional_quantity(&params[1])?;
+            for _ in 0..blocks {
+                let timestamp = interval.map(|interval| chain.head().timestamp + interval as u64);
+                chain.mine(Vec::new(), timestamp);
+            }
+            Ok(cheatcode_result(method))
+        }
+        "evm_increaseTime" => {
+            chain.data.time_offset += quantity(&params[0])? as i64;
+            Ok(json!(chain.data.time_offset))
+        }
+        "evm_setNextBlockTimestamp" => {
+            chain.data.next_timestamp = Some(quantity(&params[0])? as u64);
+            Ok(Value::Null)
+        }
+        "anvil_setBalance" | "hardhat_setBalance" => {
+            let balance = to_u128(param(params, 1)?)?;
+            chain.account_mut(param(params, 0)?).balance = balance;
+            Ok(cheatcode_result(method))
+        }
+        "anvil_setCode" | "hardhat_setCode" => {
+            let code: Bytes = param(params, 1)?;
+            chain.account_mut(param(params, 0)?).code = code.0;
+            Ok(cheatcode_result(method))
+        }
+        "anvil_setNonce" | "hardhat_setNonce" => {
+            let nonce = to_u64(param(params, 1)?)?;
+            chain.account_mut(param(params, 0)?).nonce = nonce;
+            Ok(cheatcode_result(method))
+        }
+        "anvil_setStorageAt" | "hardhat_setStorageAt" => {
+            let slot: U256 = param(params, 1)?;
+            let value: H256 = param(params, 2)?;
+            chain
+                .account_mut(param(params, 0)?)
+                .storage
+                .insert(slot, value);
+            Ok(json!(true))
+        }
+        "anvil_impersonateAccount" | "hardhat_impersonateAccount" => {
+            chain.impersonated.insert(param(params, 0)?);
+            Ok(cheatcode_result(method))
+        }
+        "anvil_stopImpersonatingAccount" | "hardhat_stopImpersonatingAccount" => {
+            chain.impersonated.remove(&param(params, 0)?);
+            Ok(cheatcode_result(method))
+        }
+        _ => Err(rpc_error(
+            -32601,
+            format!("Method {} is not supported by the dev chain", method),
+        )),
+    }
+}
+
+/// Applies a value transfer and mines it in a new block.
+fn send_transaction(
+    chain: &mut ChainState,
+    transaction: TransactionParams,
+) -> Result<Value, RpcError> {
+    let from = transaction
+        .from
+        .ok_or_else(|| rpc_error(-32602, "Missing from address"))?;
+    if !chain.accounts.contains(&from) && !chain.impersonated.contains(&from) {
+        return Err(rpc_error(-32000, format!("Unknown account {}", from)));
+    }
+    let to = match transaction.to {
+        Some(to) if chain.account(&to).code.is_empty() => to,
+        Some(_) => return Err(unsupported_execution()),
+        None => {
+            return Err(rpc_error(
+                -32000,
+                "Contract creation is not supported by the dev chain",
+            ))
+        }
+    };
+    let input = transaction.data.map(|data| data.0).unwrap_or_default();
+    let gas_used = intrinsic_gas(&input);
+    let gas = transaction.gas.map(to_u64).transpose()?.unwrap_or(gas_used);
+    if gas < gas_used {
+        return Err(rpc_error(-32000, "intrinsic gas too low"));
+    }
+    let gas_price = transaction
+        .gas_price
+        .or(transaction.max_fee_per_gas)
+        .map(to_u128)
+        .transpose()?
+        .unwrap_or(GAS_PRICE);
+    let value = transaction.value.map(to_u128).transpose()?.unwrap_or(0);
+
+    let sender = chain.account(&from);
+    if let Some(nonce) = transaction.nonce {
+        let nonce = to_u64(nonce)?;
+        if nonce != sender.nonce {
+            return Err(rpc_error(
+                -32000,
+                format!("Invalid nonce {}, expected {}", nonce, sender.nonce),
+            ));
+        }
+    }
+    let fee = (gas_used as u128).saturating_mul(gas_price);
+    match value.checked_add(fee) {
+        Some(cost) if cost <= sender.balance => {
+            let sender = chain.account_mut(from);
+            sender.balance -= cost;
+            sender.nonce += 1;
+        }
+        _ => {
+            return Err(rpc_error(
+                -32000,
+                "insufficient funds for gas * price + value",
+            ))
+        }
+    }
+    let receiver = chain.account_mut(to);
+    receiver.balance = receiver.balance.saturating_add(value);
+    let miner = chain.account_mut(chain.coinbase());
+    miner.balance = miner.balance.saturating_add(fee);
+
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(&chain.chain_id.to_be_bytes());
+    preimage.extend_from_slice(from.as_bytes());
+    preimage.extend_from_slice(&sender.nonce.to_be_bytes());
+    preimage.extend_from_slice(to.as_bytes());
+    preimage.extend_from_slice(&value.to_be_bytes());
+    preimage.extend_from_slice(&gas.to_be_bytes());
+    preimage.extend_from_slice(&gas_price.to_be_bytes());
+    preimage.extend_from_slice(&input);
+    let hash = keccak(&preimage);
+
+    chain.notify_transaction(hash);
+    chain.mine(
+        vec![DevTransaction {
+            hash,
+            from,
+            to: Some(to),
+            value,
+            nonce: sender.nonce,
+            gas,
+            gas_price,
+            input,
+            block_number: 0,
+            index: 0,
+            gas_used,
+            cumulative_gas_used: 0,
+            logs: Vec::new(),
+        }],
+        None,
+    );
+    Ok(json!(hash))
+}
+
+fn install_filter(chain: &mut ChainState, filter: DevFilter) -> U128 {
+    let id = chain.next_id();
+    chain.filters.insert(id, filter);
+    U128::from_int_unchecked(id)
+}
+
+/// Returns the blocks, transactions or logs of a filter since it was polled last.
+fn filter_changes(chain: &mut ChainState, id: u128) -> Result<Value, RpcError> {
+    let head = chain.head().number;
+    let log_filter = match chain.filters.get_mut(&id) {
+        Some(DevFilter::Blocks { next_block }) => {
+            let hashes: Vec<H256> = chain.data.blocks
+                [(*next_block as usize).min(head as usize + 1)..]
+                .iter()
+                .map(|block| block.hash)
+                .collect();
+            *next_block = head + 1;
+            return Ok(json!(hashes));
+        }
+        Some(DevFilter::PendingTransactions { hashes }) => {
+            return Ok(json!(std::mem::take(hashes)));
+        }
+        Some(DevFilter::Logs { filter, next_block }) => {
+            let from = *next_block;
+            *next_block = head + 1;
+            (filter.clone(), from)
+        }
+        None => return Err(rpc_error(-32000, "filter not found")),
+    };
+    let (filter, from) = log_filter;
+    let (first, last) = chain.block_range(&filter)?;
+    Ok(json!(chain.logs(&filter, from.max(first), last.min(head))))
+}
+
+fn log_filter(value: &Value) -> Result<LogFilter, RpcError> {
+    let addresses = match &value["address"] {
+        Value::Null => Vec::new(),
+        Value::Array(_) => from_value(&value["address"])?,
+        address => vec![from_value(address)?],
+    };
+    let topics = match &value["topics"] {
+        Value::Array(topics) => topics
+            .iter()
+            .map(|topic| match topic {
+                Value::Null => Ok(Vec::new()),
+                Value::Array(_) => from_value(topic),
+                topic => Ok(vec![from_value(topic)?]),
+            })
+            .collect::<Result<_, _>>()?,
+        _ => Vec::new(),
+    };
+    Ok(LogFilter {
+        from_block: from_value(&value["fromBlock"])?,
+        to_block: from_value(&value["toBlock"])?,
+        block_hash: from_value(&value["blockHash"])?,
+        addresses,
+        topics,
+    })
+}
+
+/// The gas of a value transfer with the given input
+fn intrinsic_gas(input: &[u8]) -> u64 {
+    TRANSFER_GAS
+        + input
+            .iter()
+            .map(|byte| if *byte == 0 { 4 } else { 16 })
+            .sum::<u64>()
+}
+
+/// `anvil_` cheatcodes return `null`, `hardhat_` cheatcodes `true`
+fn cheatcode_result(method: &str) -> Value {
+    if method.starts_with("anvil_") {
+        Value::Null
+    } else {
+        json!(true)
+    }
+}
+
+fn unsupported_execution() -> RpcError {
+    rpc_error(
+        -32000,
+        "Contract execution is not supported by the dev chain",
+    )
+}
+
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, RpcError> {
+    from_value(&params[index])
+}
+
+fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, RpcError> {
+    serde_json::from_value(value.clone()).map_err(|e| rpc_error(-32602, e.to_string()))
+}
+
+/// Parses a number, which may be given as JSON number, hex or decimal string.
+fn quantity(value: &Value) -> Result<u128, RpcError> {
+    optional_quantity(value)?.ok_or_else(|| rpc_error(-32602, "Missing number"))
+}
+
+pub(super) fn optional_quantity(value: &Value) -> Result<Option<u128>, RpcError> {
+    let invalid = || rpc_error(-32602, format!("Invalid number {}", value));
+    match value {
+        Value::Null => Ok(None),
+        Value::Number(number) => number.as_u64().map(|n| Some(n as u128)).ok_or_else(invalid),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16),
+            None => string.parse(),
+        }
+        .map(Some)
+        .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+pub(super) fn to_u128(value: U256) -> Result<u128, RpcError> {
+    let bytes = value.into_bytes();
+    if bytes[..16].iter().any(|byte| *byte != 0) {
+        return Err(rpc_error(
+            -32602,
+            format!("{} is too large for the dev chain", value),
+        ));
+    }
+    let mut low = [0_u8; 16];
+    low.copy_from_slice(&bytes[16..]);
+    Ok(u128::from_be_bytes(low))
+}
+
+fn to_u64(value: U256) -> Result<u64, RpcError> {
+    let value = to_u128(value)?;
+    if value > u64::MAX as u128 {
+        return Err(rpc_error(
+            -32602,
+            format!("{} is too large for the dev chain", value),
+        ));
+    }
+    Ok(value as u64)
+}
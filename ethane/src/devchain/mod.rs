@@ -0,0 +1,473 @@
+//! An in-memory development chain to run tests without node binaries
+//!
+//! [DevChain] implements the core `eth_` methods for balances, nonces, blocks, value
+//! transfers, receipts, filters and logs, as well as the `evm_`, `anvil_` and `hardhat_`
+//! [cheatcodes](crate::rpc::dev). It is a transport itself, so it can be used directly with a
+//! [Connection](crate::Connection), or served over http and websockets by a [DevServer].
+//!
+//! Transactions are mined instantly, one block per transaction, like the automine mode of
+//! Ganache or Anvil. The dev chain does not execute contract code, so it rejects contract
+//! creations and calls of accounts with code. Transactions are not signed, so only its dev
+//! accounts and impersonated accounts can send them with `eth_sendTransaction`. Hashes and
+//! roots are unique, but not computed as in Ethereum, and balances are limited to 128 bits.
+
+mod methods;
+mod server;
+mod state;
+
+pub use server::DevServer;
+
+use crate::types::{Address, U256};
+use crate::{ConnectionError, Request, Subscribe};
+use state::ChainState;
+
+use serde_json::{json, Value};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+/// The chain id of a new [DevChain]
+pub const DEFAULT_CHAIN_ID: u64 = 1337;
+/// The number of prefunded dev accounts of a new [DevChain]
+pub const DEFAULT_ACCOUNTS: usize = 10;
+/// The balance of the dev accounts of a new [DevChain], 10 000 ether
+const DEFAULT_BALANCE: u128 = 10_000 * 1_000_000_000_000_000_000;
+
+struct Shared {
+    state: Mutex<ChainState>,
+    notifications: Condvar,
+}
+
+/// An in-memory chain which answers requests like a development node
+///
+/// Clones share the chain, but every clone has its own subscriptions, like separate
+/// websocket connections to the same node. [read_next](Subscribe::read_next) blocks until
+/// there is a notification for one of the subscriptions of the clone.
+///
+/// ```
+/// use ethane::devchain::DevChain;
+/// use ethane::rpc::{eth_get_balance, eth_send_transaction};
+/// use ethane::types::{TransactionRequest, U256};
+/// use ethane::Connection;
+///
+/// let chain = DevChain::new();
+/// let accounts = chain.accounts();
+/// let mut connection = Connection::new(chain);
+///
+/// let transaction = TransactionRequest {
+///     from: accounts[0],
+///     to: Some(accounts[1]),
+///     value: Some(U256::from_int_unchecked(1_000_u16)),
+///     ..Default::default()
+/// };
+/// connection.call(eth_send_transaction(transaction)).unwrap();
+/// ```
+pub struct DevChain {
+    shared: Arc<Shared>,
+    subscriptions: Vec<u128>,
+}
+
+impl DevChain {
+    /// Creates a chain with [DEFAULT_ACCOUNTS] dev accounts, which own 10 000 ether each.
+    pub fn new() -> Self {
+        Self::with_accounts(DEFAULT_ACCOUNTS, U256::from_int_unchecked(DEFAULT_BALANCE))
+    }
+
+    /// Creates a chain with the given number of dev accounts, which own `balance` each.
+    ///
+    /// # Panics
+    ///
+    /// If the balance does not fit into 128 bits.
+    pub fn with_accounts(accounts: usize, balance: U256) -> Self {
+        let balance = methods::to_u128(balance).expect("The balance must fit into 128 bits");
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(ChainState::new(DEFAULT_CHAIN_ID, accounts, balance)),
+                notifications: Condvar::new(),
+            }),
+            subscriptions: Vec::new(),
+        }
+    }
+
+    /// Sets the chain id, which defaults to [DEFAULT_CHAIN_ID].
+    pub fn chain_id(self, chain_id: u64) -> Self {
+        self.state().chain_id = chain_id;
+        self
+    }
+
+    /// The dev accounts which can send transactions
+    pub fn accounts(&self) -> Vec<Address> {
+        self.state().accounts.clone()
+    }
+
+    /// Serves the chain over http and websockets on the given address, see [DevServer::start].
+    pub fn serve(&self, address: &str) -> Result<DevServer, ConnectionError> {
+        DevServer::start(self.clone(), address)
+    }
+
+    /// Returns the next notification of the subscriptions of this clone without blocking.
+    pub(crate) fn try_read_next(&mut self) -> Option<String> {
+        let mut state = lock(&self.shared);
+        next_notification(&mut self.subscriptions, &mut state)
+    }
+
+    /// Cancels the subscriptions of this clone.
+    pub(crate) fn unsubscribe_all(&mut self) {
+        let mut state = lock(&self.shared);
+        for id in self.subscriptions.drain(..) {
+            state.subscriptions.remove(&id);
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ChainState> {
+        lock(&self.shared)
+    }
+}
+
+impl Default for DevChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for DevChain {
+    /// Returns a handle to the same chain without the subscriptions of this one.
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            subscriptions: Vec::new(),
+        }
+    }
+}
+
+impl Request for DevChain {
+    fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+        let request: Value =
+            serde_json::from_str(&cmd).map_err(|e| ConnectionError::Serde(e.to_string()))?;
+        let method = request["method"].as_str().unwrap_or_default();
+        let result = methods::handle(&mut self.state(), method, &request["params"]);
+        self.shared.notifications.notify_all();
+
+        let mut response = json!({ "jsonrpc": "2.0", "id": request["id"] });
+        match result {
+            Ok(result) => {
+                if method == "eth_subscribe" {
+                    self.subscriptions
+                        .extend(methods::optional_quantity(&result).ok().flatten());
+                } else if method == "eth_unsubscribe" {
+                    let id = methods::optional_quantity(&request["params"][0])
+                        .ok()
+                        .flatten();
+                    self.subscriptions
+                        .retain(|subscription| Some(*subscription) != id);
+                }
+                response["result"] = result;
+            }
+            Err(error) => {
+                response["error"] = json!({ "code": error.code, "message": error.message });
+            }
+        }
+        Ok(response.to_string())
+    }
+}
+
+impl Subscribe for DevChain {
+    /// Waits for the next notification of the subscriptions of this clone, or returns a
+    /// [SubscriptionError](ConnectionError::SubscriptionError) if it has none.
+    fn read_next(&mut self) -> Result<String, ConnectionError> {
+        let mut state = lock(&self.shared);
+        loop {
+            if let Some(notification) = next_notification(&mut self.subscriptions, &mut state) {
+                return Ok(notification);
+            }
+            if self.subscriptions.is_empty() {
+                return Err(ConnectionError::SubscriptionError(
+                    "No active subscription".to_owned(),
+                ));
+            }
+            state = self
+                .shared
+                .notifications
+                .wait(state)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    fn fork(&self) -> Result<Self, ConnectionError> {
+        Ok(self.clone())
+    }
+}
+
+fn lock(shared: &Shared) -> MutexGuard<'_, ChainState> {
+    // a panicking test must not poison the other clones
+    shared
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Takes the next notification of the given subscriptions out of the queue and forgets
+/// subscriptions which were cancelled.
+fn next_notification(subscriptions: &mut Vec<u128>, state: &mut ChainState) -> Option<String> {
+    subscriptions.retain(|id| state.subscriptions.contains_key(id));
+    subscriptions.iter().find_map(|id| {
+        let result = state.subscriptions.get_mut(id)?.notifications.pop_front()?;
+        Some(
+            json!({
+                "jsonrpc": "2.0",
+                "method": "eth_subscription",
+                "params": { "subscription": format!("0x{:x}", id), "result": result },
+            })
+            .to_string(),
+        )
+    })
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::state::{DevLog, DevTransaction};
+    use super::*;
+    use crate::logs::FilterBuilder;
+    use crate::rpc::{self, dev};
+    use crate::types::{BlockHeader, BlockParameter, HashOrLog, TransactionRequest, H256, U64};
+    use crate::Connection;
+
+    fn transfer(from: Address, to: Address, value: u64) -> TransactionRequest {
+        TransactionRequest {
+            from,
+            to: Some(to),
+            value: Some(U256::from_int_unchecked(value)),
+            ..Default::default()
+        }
+    }
+
+    /// Mines a block with a transaction which emitted the given log.
+    fn mine_log(chain: &DevChain, address: Address, topics: Vec<H256>) {
+        let mut state = chain.state();
+        let hash = state::keccak(&state.head().number.to_be_bytes());
+        state.mine(
+            vec![DevTransaction {
+                hash,
+                from: Address::zero(),
+                to: Some(address),
+                value: 0,
+                nonce: 0,
+                gas: 30_000,
+                gas_price: 0,
+                input: Vec::new(),
+                block_number: 0,
+                index: 0,
+                gas_used: 30_000,
+                cumulative_gas_used: 0,
+                logs: vec![DevLog {
+                    address,
+                    topics,
+                    data: vec![1, 2, 3],
+                }],
+            }],
+            None,
+        );
+        drop(state);
+        chain.shared.notifications.notify_all();
+    }
+
+    #[test]
+    fn dev_chain_transfers() {
+        let chain = DevChain::new().chain_id(5);
+        let accounts = chain.accounts();
+        let receiver = Address::from_int_unchecked(42_u8);
+        let mut connection = Connection::new(chain);
+        assert_eq!(accounts.len(), DEFAULT_ACCOUNTS);
+        assert_eq!(connection.call(rpc::eth_accounts()).unwrap(), accounts);
+        assert_eq!(
+            connection.call(rpc::eth_chain_id()).unwrap(),
+            U64::from_int_unchecked(5_u8)
+        );
+        let initial_balance = connection
+            .call(rpc::eth_get_balance(accounts[1], None))
+            .unwrap();
+        assert_eq!(initial_balance, U256::from_int_unchecked(DEFAULT_BALANCE));
+
+        let hash = connection
+            .call(rpc::eth_send_transaction(transfer(
+                accounts[1],
+                receiver,
+                1_000,
+            )))
+            .unwrap();
+        let receipt = connection
+            .call(rpc::eth_get_transaction_receipt(hash))
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.block_number, U64::from_int_unchecked(1_u8));
+        assert_eq!(receipt.gas_used, U256::from_int_unchecked(21_000_u16));
+        assert_eq!(receipt.status, U64::from_int_unchecked(1_u8));
+        let transaction = connection
+            .call(rpc::eth_get_transaction_by_hash(hash))
+            .unwrap();
+        assert_eq!(transaction.block_hash, Some(receipt.block_hash));
+        assert_eq!(transaction.to, Some(receiver));
+
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(receiver, None))
+                .unwrap(),
+            U256::from_int_unchecked(1_000_u16)
+        );
+        let fee = 21_000 * state::GAS_PRICE;
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(accounts[1], None))
+                .unwrap(),
+            U256::from_int_unchecked(DEFAULT_BALANCE - 1_000 - fee)
+        );
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_transaction_count(accounts[1], None))
+                .unwrap(),
+            U256::from_int_unchecked(1_u8)
+        );
+        // the state of older blocks is kept
+        let genesis = Some(BlockParameter::Earliest);
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(accounts[1], genesis))
+                .unwrap(),
+            initial_balance
+        );
+
+        let block = connection
+            .call(rpc::eth_get_block_by_number(None, true))
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.number, Some(U64::from_int_unchecked(1_u8)));
+        assert_eq!(block.transactions.len(), 1);
+        let parent = connection
+            .call(rpc::eth_get_block_by_hash(block.parent_hash, false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parent.number, Some(U64::zero()));
+
+        // invalid transactions are rejected
+        let too_much = transfer(receiver, accounts[1], 1);
+        assert!(connection
+            .call(rpc::eth_send_transaction(too_much))
+            .is_err());
+        let mut poor = transfer(accounts[2], receiver, 0);
+        poor.value = Some(U256::from_int_unchecked(DEFAULT_BALANCE));
+        match connection.call(rpc::eth_send_transaction(poor)) {
+            Err(ConnectionError::JsonRpc(err)) => assert!(err.message.contains("insufficient")),
+            result => panic!("Expected an error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn dev_chain_cheatcodes() {
+        let chain = DevChain::with_accounts(1, U256::zero());
+        let mut connection = Connection::new(chain);
+        let address = Address::from_int_unchecked(7_u8);
+        let balance = U256::from_int_unchecked(10_u8);
+
+        let snapshot = connection.call(dev::evm_snapshot()).unwrap();
+        connection
+            .call(dev::hardhat_set_balance(address, balance))
+            .unwrap();
+        connection
+            .call(dev::anvil_impersonate_account(address))
+            .unwrap();
+        connection.call(dev::anvil_mine(None, None)).unwrap();
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(address, None))
+                .unwrap(),
+            balance
+        );
+        connection
+            .call(rpc::eth_send_transaction(TransactionRequest {
+                gas_price: Some(U256::zero()),
+                ..transfer(address, Address::zero(), 10)
+            }))
+            .unwrap();
+        assert_eq!(
+            connection.call(rpc::eth_block_number()).unwrap(),
+            U64::from_int_unchecked(2_u8)
+        );
+
+        assert!(connection.call(dev::evm_revert(snapshot)).unwrap());
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_balance(address, None))
+                .unwrap(),
+            U256::zero()
+        );
+        assert_eq!(
+            connection.call(rpc::eth_block_number()).unwrap(),
+            U64::zero()
+        );
+        assert!(!connection.call(dev::evm_revert(snapshot)).unwrap());
+    }
+
+    #[test]
+    fn dev_chain_filters_and_subscriptions() {
+        let chain = DevChain::new();
+        let accounts = chain.accounts();
+        let mut connection = Connection::new(chain.clone());
+        let contract = Address::from_int_unchecked(1_u8);
+        let topic = H256::from([1_u8; 32]);
+
+        let block_filter = connection.call(rpc::eth_new_block_filter()).unwrap();
+        let filter = FilterBuilder::new().address(contract).topic0(topic).build();
+        let log_filter = connection.call(rpc::eth_new_filter(filter)).unwrap();
+        let mut heads = connection
+            .subscribe(rpc::eth_subscribe_new_heads())
+            .unwrap();
+
+        connection
+            .call(rpc::eth_send_transaction(transfer(
+                accounts[0],
+                accounts[1],
+                1,
+            )))
+            .unwrap();
+        mine_log(&chain, contract, vec![H256::zero()]);
+        mine_log(&chain, contract, vec![topic, H256::zero()]);
+
+        let head: BlockHeader = heads.next_item().unwrap();
+        assert_eq!(head.number, Some(U64::from_int_unchecked(1_u8)));
+        assert_eq!(
+            connection
+                .call(rpc::eth_get_filter_changes(block_filter))
+                .unwrap()
+                .len(),
+            3
+        );
+        match &connection
+            .call(rpc::eth_get_filter_changes(log_filter))
+            .unwrap()[..]
+        {
+            [HashOrLog::Log(log)] => {
+                assert_eq!(log.block_number, Some(U64::from_int_unchecked(3_u8)));
+                assert_eq!(log.topics, vec![topic, H256::zero()]);
+                assert_eq!(log.data.0, vec![1, 2, 3]);
+            }
+            changes => panic!("Expected a log, got {:?}", changes),
+        }
+        assert!(connection
+            .call(rpc::eth_get_filter_changes(log_filter))
+            .unwrap()
+            .is_empty());
+
+        let all_logs = FilterBuilder::new()
+            .address(contract)
+            .from_block(BlockParameter::Earliest)
+            .build();
+        assert_eq!(
+            connection.call(rpc::eth_get_logs(all_logs)).unwrap().len(),
+            2
+        );
+        assert!(connection
+            .call(rpc::eth_uninstall_filter(log_filter))
+            .unwrap());
+        assert!(connection
+            .call(rpc::eth_get_filter_changes(log_filter))
+            .is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Serves a [DevChain] over http and websockets
+
+use super::DevChain;
+use crate::{ConnectionError, Request};
+
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tungstenite::Message;
+
+/// How often websocket connections check for subscription notifications
+const NOTIFICATION_INTERVAL: Duration = Duration::from_millis(10);
+/// How often idle http connections check whether the server was stopped
+const IDLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A local JSON-RPC server for a [DevChain]
+///
+/// Http and websocket connections are accepted on the same port, every connection is served
+/// in its own thread. The server stops when it is dropped, which also closes its connections.
+///
+/// ```no_run
+/// use ethane::devchain::DevChain;
+/// use ethane::rpc::eth_block_number;
+/// use ethane::{Connection, Http};
+///
+/// let server = DevChain::new().serve("127.0.0.1:0").unwrap();
+/// let mut connection = Connection::new(Http::new(&server.http_url(), None));
+/// let block_number = connection.call(eth_block_number()).unwrap();
+/// ```
+pub struct DevServer {
+    address: SocketAddr,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DevServer {
+    /// Starts serving the chain on the given address, e.g. `127.0.0.1:0` for any free port.
+    pub fn start(chain: DevChain, address: &str) -> Result<Self, ConnectionError> {
+        let listener =
+            TcpListener::bind(address).map_err(|e| ConnectionError::HttpError(e.to_string()))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| ConnectionError::HttpError(e.to_string()))?;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let running = running.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if !running.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let chain = chain.clone();
+                        let running = running.clone();
+                        thread::spawn(move || serve(stream, chain, &running));
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            address,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address the server listens on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn http_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.address)
+    }
+}
+
+impl Drop for DevServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // wakes up the listener, which is blocked in accept
+        let _ = TcpStream::connect(self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Serves a connection until it is closed. Errors only close the connection.
+fn serve(stream: TcpStream, chain: DevChain, running: &AtomicBool) {
+    if let Ok(true) = is_websocket(&stream) {
+        let _ = serve_websocket(stream, chain, running);
+    } else {
+        let _ = serve_http(stream, chain, running);
+    }
+}
+
+/// Peeks at the request headers to check for a websocket upgrade.
+fn is_websocket(stream: &TcpStream) -> io::Result<bool> {
+    let mut buffer = [0_u8; 4096];
+    loop {
+        let read = stream.peek(&mut buffer)?;
+        let head = String::from_utf8_lossy(&buffer[..read]).to_ascii_lowercase();
+        if read == 0 || read == buffer.len() || head.contains("\r\n\r\n") {
+            return Ok(head.contains("upgrade: websocket"));
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn serve_http(stream: TcpStream, mut chain: DevChain, running: &AtomicBool) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    loop {
+        // the timeout lets a kept alive connection notice that the server was stopped
+        writer.set_read_timeout(Some(IDLE_INTERVAL))?;
+        loop {
+            match reader.fill_buf() {
+                Ok([]) => return Ok(()),
+                Ok(_) => break,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(err) => return Err(err),
+            }
+            if !running.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+        }
+        writer.set_read_timeout(None)?;
+
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut content_length = 0;
+        let mut close = false;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                    "connection" => close = value.trim().eq_ignore_ascii_case("close"),
+                    _ => (),
+                }
+            }
+        }
+        let mut body = vec![0_u8; content_length];
+        reader.read_exact(&mut body)?;
+
+        let (status, response) = if request_line.starts_with("POST ") {
+            match chain.request(String::from_utf8_lossy(&body).into_owned()) {
+                Ok(response) => ("200 OK", response),
+                Err(err) => ("400 Bad Request", parse_error(err)),
+            }
+        } else {
+            ("405 Method Not Allowed", String::new())
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            response.len(),
+            response
+        )?;
+        writer.flush()?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn serve_websocket(
+    stream: TcpStream,
+    mut chain: DevChain,
+    running: &AtomicBool,
+) -> Result<(), tungstenite::Error> {
+    let mut websocket = tungstenite::accept(stream).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(err) => err,
+        tungstenite::HandshakeError::Interrupted(_) => {
+            tungstenite::Error::Io(io::ErrorKind::WouldBlock.into())
+        }
+    })?;
+    // the timeout lets the connection deliver notifications while waiting for requests
+    websocket
+        .get_ref()
+        .set_read_timeout(Some(NOTIFICATION_INTERVAL))?;
+    let result = loop {
+        match websocket.read_message() {
+            Ok(Message::Text(request)) => {
+                let response = chain.request(request).unwrap_or_else(parse_error);
+                websocket.write_message(Message::Text(response))?;
+            }
+            Ok(Message::Close(_)) => break Ok(()),
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(err) => break Err(err),
+        }
+        while let Some(notification) = chain.try_read_next() {
+            websocket.write_message(Message::Text(notification))?;
+        }
+        if !running.load(Ordering::SeqCst) {
+            break Ok(());
+        }
+    };
+    chain.unsubscribe_all();
+    result
+}
+
+fn parse_error(err: ConnectionError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": -32700, "message": format!("{:?}", err) },
+    })
+    .to_string()
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use crate::rpc;
+    use crate::types::{BlockHeader, TransactionRequest, U256, U64};
+    use crate::{Connection, Http, WebSocket};
+
+    #[test]
+    fn dev_server_http_and_websocket() {
+        let chain = DevChain::new();
+        let accounts = chain.accounts();
+        let server = chain.serve("127.0.0.1:0").unwrap();
+
+        let mut websocket = Connection::new(WebSocket::new(&server.ws_url(), None).unwrap());
+        let mut heads = websocket.subscribe(rpc::eth_subscribe_new_heads()).unwrap();
+
+        let mut http = Connection::new(Http::new(&server.http_url(), None));
+        http.call(rpc::eth_send_transaction(TransactionRequest {
+            from: accounts[0],
+            to: Some(accounts[1]),
+            value: Some(U256::from_int_unchecked(1_u8)),
+            ..Default::default()
+        }))
+        .unwrap();
+        assert_eq!(
+            http.call(rpc::eth_block_number()).unwrap(),
+            U64::from_int_unchecked(1_u8)
+        );
+
+        let head: BlockHeader = heads.next_item().unwrap();
+        assert_eq!(head.number, Some(U64::from_int_unchecked(1_u8)));
+        assert_eq!(
+            websocket.call(rpc::eth_block_number()).unwrap(),
+            U64::from_int_unchecked(1_u8)
+        );
+    }
+
+    #[test]
+    fn dev_server_closes_kept_alive_connections() {
+        let server = DevChain::new().serve("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        let body = r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = [0_u8; 1024];
+        assert!(stream.read(&mut response).unwrap() > 0);
+
+        drop(server);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut rest = Vec::new();
+        // the connection is closed instead of waiting for the next request
+        assert!(stream.read_to_end(&mut rest).is_ok());
+    }
+}
@@ -0,0 +1,543 @@
+//! The in-memory state of a [DevChain](super::DevChain)
+
+use crate::rpc::RpcError;
+use crate::types::{Address, BlockParameter, Bloom, Bytes, H256, H64, U256, U64};
+
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tiny_keccak::{Hasher, Keccak};
+
+pub(super) const TRANSFER_GAS: u64 = 21_000;
+pub(super) const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+pub(super) const GAS_PRICE: u128 = 1_000_000_000;
+
+/// The Keccak hash of the RLP encoding of an empty list, i.e. of no uncles
+const EMPTY_UNCLES_HASH: &str =
+    "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
+
+#[derive(Clone, Default)]
+pub(super) struct Account {
+    pub balance: u128,
+    pub nonce: u64,
+    pub code: Vec<u8>,
+    pub storage: HashMap<U256, H256>,
+}
+
+#[derive(Clone)]
+pub(super) struct DevBlock {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub timestamp: u64,
+    pub transactions: Vec<H256>,
+    pub gas_used: u64,
+    pub logs_bloom: Bloom,
+    /// The state of the accounts after this block
+    pub state: HashMap<Address, Account>,
+}
+
+#[derive(Clone)]
+pub(super) struct DevTransaction {
+    pub hash: H256,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: u128,
+    pub nonce: u64,
+    pub gas: u64,
+    pub gas_price: u128,
+    pub input: Vec<u8>,
+    pub block_number: u64,
+    pub index: u64,
+    pub gas_used: u64,
+    pub cumulative_gas_used: u64,
+    pub logs: Vec<DevLog>,
+}
+
+#[derive(Clone)]
+pub(super) struct DevLog {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
+
+/// The criteria of `eth_getLogs`, log filters and log subscriptions
+#[derive(Clone, Default)]
+pub(super) struct LogFilter {
+    pub from_block: Option<BlockParameter>,
+    pub to_block: Option<BlockParameter>,
+    pub block_hash: Option<H256>,
+    pub addresses: Vec<Address>,
+    /// The accepted values of every topic position, an empty list accepts any value
+    pub topics: Vec<Vec<H256>>,
+}
+
+impl LogFilter {
+    fn matches(&self, log: &DevLog) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&log.address))
+            && self.topics.iter().enumerate().all(|(i, topics)| {
+                topics.is_empty()
+                    || matches!(log.topics.get(i), Some(topic) if topics.contains(topic))
+            })
+    }
+}
+
+pub(super) enum DevFilter {
+    /// Yields the hashes of the blocks after `next_block - 1`
+    Blocks {
+        next_block: u64,
+    },
+    PendingTransactions {
+        hashes: Vec<H256>,
+    },
+    Logs {
+        filter: LogFilter,
+        next_block: u64,
+    },
+}
+
+pub(super) enum SubscriptionKind {
+    NewHeads,
+    NewPendingTransactions,
+    Logs(LogFilter),
+}
+
+pub(super) struct DevSubscription {
+    pub kind: SubscriptionKind,
+    pub notifications: VecDeque<Value>,
+}
+
+/// The part of the state which is restored by `evm_revert`
+#[derive(Clone)]
+pub(super) struct ChainData {
+    /// The state of the accounts after the latest block and the cheatcodes applied since
+    pub state: HashMap<Address, Account>,
+    pub blocks: Vec<DevBlock>,
+    pub transactions: HashMap<H256, DevTransaction>,
+    pub time_offset: i64,
+    pub next_timestamp: Option<u64>,
+}
+
+pub(super) struct ChainState {
+    pub chain_id: u64,
+    pub accounts: Vec<Address>,
+    pub impersonated: HashSet<Address>,
+    pub data: ChainData,
+    pub filters: HashMap<u128, DevFilter>,
+    pub subscriptions: HashMap<u128, DevSubscription>,
+    pub snapshots: BTreeMap<u128, ChainData>,
+    next_id: u128,
+}
+
+impl ChainState {
+    pub fn new(chain_id: u64, accounts: usize, balance: u128) -> Self {
+        let accounts: Vec<Address> = (0..accounts).map(dev_account).collect();
+        let state = accounts
+            .iter()
+            .map(|address| {
+                let account = Account {
+                    balance,
+                    ..Account::default()
+                };
+                (*address, account)
+            })
+            .collect();
+        let mut chain = Self {
+            chain_id,
+            accounts,
+            impersonated: HashSet::new(),
+            data: ChainData {
+                state,
+                blocks: Vec::new(),
+                transactions: HashMap::new(),
+                time_offset: 0,
+                next_timestamp: None,
+            },
+            filters: HashMap::new(),
+            subscriptions: HashMap::new(),
+            snapshots: BTreeMap::new(),
+            next_id: 1,
+        };
+        chain.mine(Vec::new(), None);
+        chain
+    }
+
+    pub fn next_id(&mut self) -> u128 {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    pub fn head(&self) -> &DevBlock {
+        // there is always the genesis block
+        self.data.blocks.last().unwrap()
+    }
+
+    pub fn coinbase(&self) -> Address {
+        self.accounts.first().copied().unwrap_or_else(Address::zero)
+    }
+
+    pub fn account(&self, address: &Address) -> Account {
+        self.data.state.get(address).cloned().unwrap_or_default()
+    }
+
+    pub fn account_mut(&mut self, address: Address) -> &mut Account {
+        self.data.state.entry(address).or_default()
+    }
+
+    /// Returns the block of the parameter or `None` if it is unknown.
+    pub fn find_block(&self, block: Option<BlockParameter>) -> Option<&DevBlock> {
+        match block.unwrap_or_default() {
+            BlockParameter::Latest
+            | BlockParameter::Pending
+            | BlockParameter::Safe
+            | BlockParameter::Finalized => Some(self.head()),
+            BlockParameter::Earliest => self.data.blocks.first(),
+            BlockParameter::Custom(number) => self
+                .data
+                .blocks
+                .get(u64::from_be_bytes(number.into_bytes()) as usize),
+            BlockParameter::Hash { block_hash, .. } => self.find_block_by_hash(&block_hash),
+        }
+    }
+
+    pub fn find_block_by_hash(&self, hash: &H256) -> Option<&DevBlock> {
+        self.data.blocks.iter().find(|block| &block.hash == hash)
+    }
+
+    pub fn block(&self, block: Option<BlockParameter>) -> Result<&DevBlock, RpcError> {
+        self.find_block(block)
+            .ok_or_else(|| rpc_error(-32000, "Unknown block"))
+    }
+
+    /// Returns the state of an account at the given block.
+    pub fn account_at(
+        &self,
+        address: &Address,
+        block: Option<BlockParameter>,
+    ) -> Result<Account, RpcError> {
+        let state = match block.unwrap_or_default() {
+            BlockParameter::Latest
+            | BlockParameter::Pending
+            | BlockParameter::Safe
+            | BlockParameter::Finalized => &self.data.state,
+            block => {
+                let block = self.block(Some(block))?;
+                // cheatcodes change the state of the head block without mining a new one
+                if block.number + 1 == self.data.blocks.len() as u64 {
+                    &self.data.state
+                } else {
+                    &block.state
+                }
+            }
+        };
+        Ok(state.get(address).cloned().unwrap_or_default())
+    }
+
+    /// Mines a block with the given transactions, which were already applied to the state.
+    pub fn mine(&mut self, mut transactions: Vec<DevTransaction>, timestamp: Option<u64>) -> H256 {
+        let (number, parent_hash, parent_timestamp) = match self.data.blocks.last() {
+            Some(parent) => (parent.number + 1, parent.hash, parent.timestamp),
+            None => (0, H256::zero(), 0),
+        };
+        let timestamp = timestamp
+            .or_else(|| self.data.next_timestamp.take())
+            .unwrap_or_else(|| {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs() as i64)
+                    .unwrap_or_default();
+                ((now + self.data.time_offset) as u64).max(parent_timestamp + 1)
+            });
+
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(parent_hash.as_bytes());
+        preimage.extend_from_slice(&number.to_be_bytes());
+        preimage.extend_from_slice(&timestamp.to_be_bytes());
+        let mut gas_used = 0;
+        let mut bloom = [0_u8; 256];
+        for (index, transaction) in transactions.iter_mut().enumerate() {
+            gas_used += transaction.gas_used;
+            transaction.block_number = number;
+            transaction.index = index as u64;
+            transaction.cumulative_gas_used = gas_used;
+            for log in &transaction.logs {
+                accrue_bloom(&mut bloom, log.address.as_bytes());
+                for topic in &log.topics {
+                    accrue_bloom(&mut bloom, topic.as_bytes());
+                }
+            }
+            preimage.extend_from_slice(transaction.hash.as_bytes());
+        }
+        let hash = keccak(&preimage);
+
+        let block = DevBlock {
+            number,
+            hash,
+            parent_hash,
+            timestamp,
+            transactions: transactions.iter().map(|tx| tx.hash).collect(),
+            gas_used,
+            logs_bloom: Bloom::from(bloom),
+            state: self.data.state.clone(),
+        };
+        for transaction in transactions {
+            self.data.transactions.insert(transaction.hash, transaction);
+        }
+        self.data.blocks.push(block);
+        self.notify_block();
+        hash
+    }
+
+    /// Queues the notifications of the subscriptions for the latest block.
+    fn notify_block(&mut self) {
+        let head = self.head();
+        let header = self.header_json(head);
+        let logs: Vec<(DevLog, Value)> = self
+            .block_logs(head)
+            .map(|(log, json)| (log.clone(), json))
+            .collect();
+        for subscription in self.subscriptions.values_mut() {
+            match &subscription.kind {
+                SubscriptionKind::NewHeads => subscription.notifications.push_back(header.clone()),
+                SubscriptionKind::Logs(filter) => subscription.notifications.extend(
+                    logs.iter()
+                        .filter(|(log, _)| filter.matches(log))
+                        .map(|(_, json)| json.clone()),
+                ),
+                SubscriptionKind::NewPendingTransactions => (),
+            }
+        }
+    }
+
+    /// Notifies the pending transaction filters and subscriptions about a new transaction.
+    pub fn notify_transaction(&mut self, hash: H256) {
+        for filter in self.filters.values_mut() {
+            if let DevFilter::PendingTransactions { hashes } = filter {
+                hashes.push(hash);
+            }
+        }
+        for subscription in self.subscriptions.values_mut() {
+            if let SubscriptionKind::NewPendingTransactions = subscription.kind {
+                subscription.notifications.push_back(json!(hash));
+            }
+        }
+    }
+
+    /// Returns the logs of a block together with their JSON representation.
+    fn block_logs<'a>(&'a self, block: &'a DevBlock) -> impl Iterator<Item = (&'a DevLog, Value)> {
+        block
+            .transactions
+            .iter()
+            .filter_map(move |hash| self.data.transactions.get(hash))
+            .flat_map(|transaction| {
+                transaction
+                    .logs
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, log)| (transaction, index, log))
+            })
+            .enumerate()
+            .map(move |(log_index, (transaction, transaction_log_index, log))| {
+                let json = json!({
+                    "address": log.address,
+                    "topics": log.topics,
+                    "data": Bytes(log.data.clone()),
+                    "blockHash": block.hash,
+                    "blockNumber": U64::from_int_unchecked(block.number),
+                    "transactionHash": transaction.hash,
+                    "transactionIndex": U64::from_int_unchecked(transaction.index),
+                    "logIndex": U64::from_int_unchecked(log_index as u64),
+                    "transactionLogIndex": U64::from_int_unchecked(transaction_log_index as u64),
+                    "removed": false,
+                });
+                (log, json)
+            })
+    }
+
+    /// Returns the logs matching the filter in the given range of blocks.
+    pub fn logs(&self, filter: &LogFilter, from: u64, to: u64) -> Vec<Value> {
+        self.data
+            .blocks
+            .iter()
+            .filter(|block| block.number >= from && block.number <= to)
+            .flat_map(|block| self.block_logs(block))
+            .filter(|(log, _)| filter.matches(log))
+            .map(|(_, json)| json)
+            .collect()
+    }
+
+    /// Returns the logs matching all criteria of the filter, including its blocks.
+    pub fn filter_logs(&self, filter: &LogFilter) -> Result<Vec<Value>, RpcError> {
+        if let Some(block_hash) = filter.block_hash {
+            let number = self
+                .find_block_by_hash(&block_hash)
+                .ok_or_else(|| rpc_error(-32000, "Unknown block"))?
+                .number;
+            return Ok(self.logs(filter, number, number));
+        }
+        let (from, to) = self.block_range(filter)?;
+        Ok(self.logs(filter, from, to))
+    }
+
+    /// The first and last block of a filter, which default to the latest block
+    pub fn block_range(&self, filter: &LogFilter) -> Result<(u64, u64), RpcError> {
+        let from = match filter.from_block {
+            Some(block) => self.block(Some(block))?.number,
+            None => self.head().number,
+        };
+        let to = match filter.to_block {
+            // a range may reach beyond the latest block
+            Some(BlockParameter::Custom(number)) => u64::from_be_bytes(number.into_bytes()),
+            Some(block) => self.block(Some(block))?.number,
+            None => self.head().number,
+        };
+        Ok((from, to))
+    }
+
+    pub fn header_json(&self, block: &DevBlock) -> Value {
+        let mut state_preimage = Vec::new();
+        let mut addresses: Vec<&Address> = block.state.keys().collect();
+        addresses.sort_by_key(|address| address.as_bytes());
+        for address in addresses {
+            let account = &block.state[address];
+            state_preimage.extend_from_slice(address.as_bytes());
+            state_preimage.extend_from_slice(&account.balance.to_be_bytes());
+            state_preimage.extend_from_slice(&account.nonce.to_be_bytes());
+            state_preimage.extend_from_slice(&account.code);
+        }
+        let transactions_root = keccak(
+            &block
+                .transactions
+                .iter()
+                .flat_map(|hash| hash.into_bytes())
+                .collect::<Vec<u8>>(),
+        );
+        json!({
+            "number": U64::from_int_unchecked(block.number),
+            "hash": block.hash,
+            "parentHash": block.parent_hash,
+            "nonce": H64::zero(),
+            "mixHash": H256::zero(),
+            "sha3Uncles": EMPTY_UNCLES_HASH,
+            "logsBloom": block.logs_bloom,
+            "transactionsRoot": transactions_root,
+            "stateRoot": keccak(&state_preimage),
+            "receiptsRoot": transactions_root,
+            "miner": self.coinbase(),
+            "difficulty": "0x0",
+            "extraData": "0x",
+            "gasLimit": U64::from_int_unchecked(BLOCK_GAS_LIMIT),
+            "gasUsed": U64::from_int_unchecked(block.gas_used),
+            "timestamp": U64::from_int_unchecked(block.timestamp),
+        })
+    }
+
+    pub fn block_json(&self, block: &DevBlock, full_transactions: bool) -> Value {
+        let mut json = self.header_json(block);
+        let transactions: Vec<Value> = block
+            .transactions
+            .iter()
+            .map(|hash| {
+                if full_transactions {
+                    self.transaction_json(&self.data.transactions[hash])
+                } else {
+                    json!(hash)
+                }
+            })
+            .collect();
+        json["totalDifficulty"] = json!("0x0");
+        json["size"] = json!(U64::from_int_unchecked(
+            512 + 128 * block.transactions.len() as u64
+        ));
+        json["transactions"] = json!(transactions);
+        json["uncles"] = json!([]);
+        json
+    }
+
+    pub fn transaction_json(&self, transaction: &DevTransaction) -> Value {
+        json!({
+            "hash": transaction.hash,
+            "nonce": U64::from_int_unchecked(transaction.nonce),
+            "blockHash": self.data.blocks[transaction.block_number as usize].hash,
+            "blockNumber": U64::from_int_unchecked(transaction.block_number),
+            "transactionIndex": U64::from_int_unchecked(transaction.index),
+            "from": transaction.from,
+            "to": transaction.to,
+            "value": U256::from_int_unchecked(transaction.value),
+            "gas": U64::from_int_unchecked(transaction.gas),
+            "gasPrice": U256::from_int_unchecked(transaction.gas_price),
+            "input": Bytes(transaction.input.clone()),
+            "type": "0x0",
+            "chainId": U64::from_int_unchecked(self.chain_id),
+            "v": "0x0",
+            "r": "0x0",
+            "s": "0x0",
+        })
+    }
+
+    pub fn receipt_json(&self, transaction: &DevTransaction) -> Value {
+        let block = &self.data.blocks[transaction.block_number as usize];
+        let logs: Vec<Value> = self
+            .block_logs(block)
+            .filter(|(_, json)| json["transactionHash"] == json!(transaction.hash))
+            .map(|(_, json)| json)
+            .collect();
+        let mut bloom = [0_u8; 256];
+        for log in &transaction.logs {
+            accrue_bloom(&mut bloom, log.address.as_bytes());
+            for topic in &log.topics {
+                accrue_bloom(&mut bloom, topic.as_bytes());
+            }
+        }
+        json!({
+            "transactionHash": transaction.hash,
+            "transactionIndex": U64::from_int_unchecked(transaction.index),
+            "blockHash": block.hash,
+            "blockNumber": U64::from_int_unchecked(block.number),
+            "from": transaction.from,
+            "to": transaction.to,
+            "cumulativeGasUsed": U64::from_int_unchecked(transaction.cumulative_gas_used),
+            "gasUsed": U64::from_int_unchecked(transaction.gas_used),
+            "effectiveGasPrice": U256::from_int_unchecked(transaction.gas_price),
+            "contractAddress": null,
+            "logs": logs,
+            "logsBloom": Bloom::from(bloom),
+            "status": "0x1",
+            "type": "0x0",
+        })
+    }
+}
+
+pub(super) fn rpc_error(code: i64, message: impl Into<String>) -> RpcError {
+    RpcError {
+        code,
+        message: message.into(),
+        data: None,
+    }
+}
+
+pub(super) fn keccak(data: &[u8]) -> H256 {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0_u8; 32];
+    hasher.finalize(&mut out);
+    H256::from(out)
+}
+
+/// The address of the dev account with the given index
+fn dev_account(index: usize) -> Address {
+    let hash = keccak(format!("ethane dev account {}", index).as_bytes());
+    let mut address = [0_u8; 20];
+    address.copy_from_slice(&hash.as_bytes()[12..]);
+    Address::from(address)
+}
+
+/// Adds an address or topic to a logs bloom as specified in the yellow paper.
+fn accrue_bloom(bloom: &mut [u8; 256], input: &[u8]) {
+    let hash = keccak(input);
+    let hash = hash.as_bytes();
+    for i in [0, 2, 4].iter() {
+        let bit = ((hash[*i] as usize) << 8 | hash[i + 1] as usize) & 2047;
+        bloom[255 - bit / 8] |= 1 << (bit % 8);
+    }
+}
@@ -0,0 +1,165 @@
+//! The state of a forked block, fetched lazily from a node
+
+use crate::rpc::{self, Rpc};
+use crate::types::{Address, BlockParameter, H256, U256, U64};
+use crate::{Connection, ConnectionError, Request};
+
+use revm::primitives::{
+    keccak256, AccountInfo, Address as EvmAddress, BlobExcessGasAndPrice, BlockEnv, Bytecode,
+    Bytes as EvmBytes, B256, KECCAK_EMPTY, U256 as EvmU256,
+};
+use revm::Database;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The cached state of a block
+pub(super) struct ForkCache {
+    pub block: BlockEnv,
+    accounts: HashMap<EvmAddress, AccountInfo>,
+    code: HashMap<B256, Bytecode>,
+    storage: HashMap<(EvmAddress, EvmU256), EvmU256>,
+}
+
+impl ForkCache {
+    /// Fetches the header of a block, which is used as environment of the calls.
+    pub fn fetch<T: Request>(
+        connection: &mut Connection<T>,
+        block: Option<BlockParameter>,
+    ) -> Result<Self, ConnectionError> {
+        let header: Value = match block {
+            Some(BlockParameter::Hash { block_hash, .. }) => {
+                connection.call(Rpc::custom("eth_getBlockByHash", (block_hash, false)))?
+            }
+            block => connection.call(Rpc::custom(
+                "eth_getBlockByNumber",
+                (block.unwrap_or_default(), false),
+            ))?,
+        };
+        if header.is_null() {
+            return Err(ConnectionError::Serde(format!("Unknown block {:?}", block)));
+        }
+        let field = |name: &str| -> Result<EvmU256, ConnectionError> {
+            match &header[name] {
+                Value::Null => Ok(EvmU256::ZERO),
+                value => serde_json::from_value::<U256>(value.clone())
+                    .map(to_evm_u256)
+                    .map_err(|e| ConnectionError::Serde(e.to_string())),
+            }
+        };
+        let miner: Address = serde_json::from_value(header["miner"].clone()).unwrap_or_default();
+        let mix_hash: H256 = serde_json::from_value(header["mixHash"].clone()).unwrap_or_default();
+        let block = BlockEnv {
+            number: field("number")?,
+            coinbase: to_evm_address(miner),
+            timestamp: field("timestamp")?,
+            gas_limit: field("gasLimit")?,
+            basefee: field("baseFeePerGas")?,
+            difficulty: field("difficulty")?,
+            prevrandao: Some(B256::from(mix_hash.into_bytes())),
+            blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(
+                field("excessBlobGas")?.saturating_to(),
+            )),
+        };
+        Ok(Self {
+            block,
+            accounts: HashMap::new(),
+            code: HashMap::new(),
+            storage: HashMap::new(),
+        })
+    }
+
+    pub fn number(&self) -> u64 {
+        self.block.number.saturating_to()
+    }
+}
+
+/// A [Database] which reads the state of a block from the cache, or else from the node
+pub(super) struct ForkDb<'a, T: Request> {
+    pub connection: &'a mut Connection<T>,
+    pub cache: &'a mut ForkCache,
+    pub block_hashes: &'a mut HashMap<u64, B256>,
+}
+
+impl<'a, T: Request> ForkDb<'a, T> {
+    fn block(&self) -> Option<BlockParameter> {
+        Some(BlockParameter::Custom(U64::from_int_unchecked(
+            self.cache.number(),
+        )))
+    }
+}
+
+impl<'a, T: Request> Database for ForkDb<'a, T> {
+    type Error = ConnectionError;
+
+    fn basic(&mut self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.cache.accounts.get(&address) {
+            return Ok(Some(account.clone()));
+        }
+        let block = self.block();
+        let target = Address::from(address.into_array());
+        let balance = self.connection.call(rpc::eth_get_balance(target, block))?;
+        let nonce = self
+            .connection
+            .call(rpc::eth_get_transaction_count(target, block))?;
+        let code = self.connection.call(rpc::eth_get_code(target, block))?;
+
+        let (code_hash, bytecode) = if code.0.is_empty() {
+            (KECCAK_EMPTY, Bytecode::new())
+        } else {
+            let bytecode = Bytecode::new_raw(EvmBytes::from(code.0));
+            (keccak256(bytecode.original_byte_slice()), bytecode)
+        };
+        self.cache.code.insert(code_hash, bytecode.clone());
+        let account = AccountInfo::new(
+            to_evm_u256(balance),
+            to_evm_u256(nonce).saturating_to(),
+            code_hash,
+            bytecode,
+        );
+        self.cache.accounts.insert(address, account.clone());
+        Ok(Some(account))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // the code is cached together with its account, which is always loaded first
+        Ok(self.cache.code.get(&code_hash).cloned().unwrap_or_default())
+    }
+
+    fn storage(&mut self, address: EvmAddress, index: EvmU256) -> Result<EvmU256, Self::Error> {
+        if let Some(value) = self.cache.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+        let value = self.connection.call(rpc::eth_get_storage_at(
+            Address::from(address.into_array()),
+            U256::from(index.to_be_bytes::<32>()),
+            self.block(),
+        ))?;
+        let value = EvmU256::try_from_be_slice(&value.0).unwrap_or_default();
+        self.cache.storage.insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: EvmU256) -> Result<B256, Self::Error> {
+        let number: u64 = number.saturating_to();
+        if let Some(hash) = self.block_hashes.get(&number) {
+            return Ok(*hash);
+        }
+        let block = Some(BlockParameter::Custom(U64::from_int_unchecked(number)));
+        let hash = self
+            .connection
+            .call(rpc::eth_get_block_by_number(block, false))?
+            .and_then(|block| block.hash)
+            .map(|hash| B256::from(hash.into_bytes()))
+            .unwrap_or_default();
+        self.block_hashes.insert(number, hash);
+        Ok(hash)
+    }
+}
+
+pub(super) fn to_evm_address(address: Address) -> EvmAddress {
+    EvmAddress::from(address.into_bytes())
+}
+
+pub(super) fn to_evm_u256(value: U256) -> EvmU256 {
+    EvmU256::from_be_bytes(value.into_bytes())
+}
@@ -0,0 +1,422 @@
+//! An embedded EVM to simulate calls locally against the state of a node
+//!
+//! [Evm] executes calls with [revm](https://github.com/bluealloy/revm) on top of a block of the
+//! connected node. The state of the block is fetched lazily, i.e. accounts, code and storage
+//! slots are only requested when the execution reads them, and cached per block. Repeated
+//! calls against the same block, e.g. a search for the best parameters of a trade, only hit
+//! the node for the state they have not read before.
+//!
+//! Calls are never committed, the state of the forked block does not change between calls.
+//! Like `eth_call`, the balance of the sender, its nonce and the base fee are not checked.
+//!
+//! The rules of the hardfork which is active at the forked block are used on mainnet, other
+//! chains use [Cancun](SpecId::CANCUN) unless another [spec](Evm::spec_id) is set.
+//!
+//! ```no_run
+//! use ethane::evm::Evm;
+//! use ethane::types::{Address, Call};
+//! use ethane::{Connection, Http};
+//! use std::convert::TryFrom;
+//!
+//! let connection = Connection::new(Http::new("http://127.0.0.1:8545", None));
+//! let mut evm = Evm::new(connection);
+//!
+//! let call = Call {
+//!     to: Address::try_from("0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a").unwrap(),
+//!     ..Default::default()
+//! };
+//! let execution = evm.simulate(call, None).unwrap();
+//! println!("used {} gas and emitted {} logs", execution.gas_used, execution.logs.len());
+//! ```
+
+mod fork;
+
+use crate::rpc;
+use crate::types::{Address, BlockParameter, Bytes, Call, Log, H256, U64};
+use crate::{Connection, ConnectionError, Request};
+use fork::{to_evm_address, to_evm_u256, ForkCache, ForkDb};
+
+use revm::primitives::{
+    BlockEnv, Bytes as EvmBytes, EVMError, ExecutionResult, TxKind, B256, U256 as EvmU256,
+};
+use std::collections::HashMap;
+
+pub use revm::primitives::SpecId;
+
+/// The blocks from which on the hardforks before the merge were active on mainnet
+const MAINNET_BLOCK_FORKS: [(u64, SpecId); 14] = [
+    (15_537_394, SpecId::MERGE),
+    (15_050_000, SpecId::GRAY_GLACIER),
+    (13_773_000, SpecId::ARROW_GLACIER),
+    (12_965_000, SpecId::LONDON),
+    (12_244_000, SpecId::BERLIN),
+    (9_200_000, SpecId::MUIR_GLACIER),
+    (9_069_000, SpecId::ISTANBUL),
+    (7_280_000, SpecId::PETERSBURG),
+    (4_370_000, SpecId::BYZANTIUM),
+    (2_675_000, SpecId::SPURIOUS_DRAGON),
+    (2_463_000, SpecId::TANGERINE),
+    (1_920_000, SpecId::DAO_FORK),
+    (1_150_000, SpecId::HOMESTEAD),
+    (0, SpecId::FRONTIER),
+];
+/// The timestamps from which on the hardforks after the merge were active on mainnet
+const MAINNET_TIME_FORKS: [(u64, SpecId); 2] = [
+    (1_710_338_135, SpecId::CANCUN),
+    (1_681_338_455, SpecId::SHANGHAI),
+];
+
+/// Executes calls locally against the state of the blocks of a node
+pub struct Evm<T: Request> {
+    connection: Connection<T>,
+    chain_id: Option<u64>,
+    spec_id: Option<SpecId>,
+    forks: HashMap<u64, ForkCache>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+/// The outcome of a simulated call
+#[derive(Clone, Debug, PartialEq)]
+pub struct Execution {
+    pub status: ExecutionStatus,
+    /// The returned data, or the revert data if the call was reverted
+    pub output: Bytes,
+    pub gas_used: u64,
+    /// The logs emitted by the call, they are empty if the call did not succeed
+    pub logs: Vec<Log>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    Success,
+    Reverted,
+    /// The execution halted exceptionally, e.g. it ran out of gas
+    Halted(String),
+}
+
+/// Wraps the different errors that may occur during a simulation.
+#[derive(Debug)]
+pub enum EvmError {
+    /// The state could not be fetched from the node
+    Connection(ConnectionError),
+    /// The call was reverted, contains the revert data
+    Revert(Bytes),
+    /// The execution halted exceptionally
+    Halt(String),
+    /// The call is invalid, e.g. its gas limit is too low
+    InvalidCall(String),
+}
+
+impl From<ConnectionError> for EvmError {
+    fn from(err: ConnectionError) -> Self {
+        Self::Connection(err)
+    }
+}
+
+impl From<EVMError<ConnectionError>> for EvmError {
+    fn from(err: EVMError<ConnectionError>) -> Self {
+        match err {
+            EVMError::Database(err) => Self::Connection(err),
+            EVMError::Custom(message) | EVMError::Precompile(message) => Self::InvalidCall(message),
+            err => Self::InvalidCall(format!("{:?}", err)),
+        }
+    }
+}
+
+impl<T: Request> Evm<T> {
+    pub fn new(connection: Connection<T>) -> Self {
+        Self {
+            connection,
+            chain_id: None,
+            spec_id: None,
+            forks: HashMap::new(),
+            block_hashes: HashMap::new(),
+        }
+    }
+
+    /// Executes calls with the rules of the given hardfork, instead of the ones which are active
+    /// at the forked block.
+    pub fn spec_id(mut self, spec_id: SpecId) -> Self {
+        self.spec_id = Some(spec_id);
+        self
+    }
+
+    /// Executes a call like `eth_call` and returns its output.
+    ///
+    /// Reverts are returned as [Revert](EvmError::Revert), their data can be decoded with
+    /// [decode_revert](ethane_abi::Abi::decode_revert).
+    pub fn call(
+        &mut self,
+        call: Call,
+        block_param: Option<BlockParameter>,
+    ) -> Result<Bytes, EvmError> {
+        let execution = self.simulate(call, block_param)?;
+        match execution.status {
+            ExecutionStatus::Success => Ok(execution.output),
+            ExecutionStatus::Reverted => Err(EvmError::Revert(execution.output)),
+            ExecutionStatus::Halted(reason) => Err(EvmError::Halt(reason)),
+        }
+    }
+
+    /// Executes a call and returns its outcome, including the used gas and the emitted logs.
+    ///
+    /// If no gas limit is given, the call may use the gas limit of the block.
+    pub fn simulate(
+        &mut self,
+        call: Call,
+        block_param: Option<BlockParameter>,
+    ) -> Result<Execution, EvmError> {
+        let chain_id = self.chain_id()?;
+        let number = self.fork(block_param)?;
+        let cache = self.forks.get_mut(&number).expect("fork is cached");
+        let block = cache.block.clone();
+        let spec_id = self
+            .spec_id
+            .unwrap_or_else(|| active_spec_id(chain_id, &block));
+        let gas_limit = match call.gas {
+            Some(gas) => to_evm_u256(gas).saturating_to(),
+            None => block.gas_limit.saturating_to(),
+        };
+        let db = ForkDb {
+            connection: &mut self.connection,
+            cache,
+            block_hashes: &mut self.block_hashes,
+        };
+
+        let mut evm = revm::Evm::builder()
+            .with_db(db)
+            .with_spec_id(spec_id)
+            .modify_cfg_env(|cfg| {
+                cfg.chain_id = chain_id;
+                cfg.disable_balance_check = true;
+                cfg.disable_block_gas_limit = true;
+                cfg.disable_eip3607 = true;
+                cfg.disable_base_fee = true;
+            })
+            .with_block_env(block)
+            .modify_tx_env(|tx| {
+                tx.caller = to_evm_address(call.from.unwrap_or_default());
+                tx.transact_to = TxKind::Call(to_evm_address(call.to));
+                tx.value = call.value.map(to_evm_u256).unwrap_or_default();
+                tx.data = EvmBytes::from(call.data.unwrap_or_default().0);
+                tx.gas_limit = gas_limit;
+                tx.gas_price = call
+                    .gas_price
+                    .or(call.max_fee_per_gas)
+                    .map(to_evm_u256)
+                    .unwrap_or(EvmU256::ZERO);
+                tx.gas_priority_fee = call.max_priority_fee_per_gas.map(to_evm_u256);
+                tx.chain_id = Some(chain_id);
+                tx.nonce = None;
+            })
+            .build();
+        let result = evm.transact()?.result;
+
+        Ok(match result {
+            ExecutionResult::Success {
+                gas_used,
+                logs,
+                output,
+                ..
+            } => Execution {
+                status: ExecutionStatus::Success,
+                output: Bytes(output.into_data().to_vec()),
+                gas_used,
+                logs: logs
+                    .into_iter()
+                    .map(|log| Log {
+                        address: Address::from(log.address.into_array()),
+                        topics: log.topics().iter().map(|t| H256::from(t.0)).collect(),
+                        data: Bytes(log.data.data.to_vec()),
+                        block_hash: None,
+                        block_number: Some(U64::from_int_unchecked(number)),
+                        transaction_hash: None,
+                        transaction_index: None,
+                        log_index: None,
+                        transaction_log_index: None,
+                        removed: false,
+                    })
+                    .collect(),
+            },
+            ExecutionResult::Revert { gas_used, output } => Execution {
+                status: ExecutionStatus::Reverted,
+                output: Bytes(output.to_vec()),
+                gas_used,
+                logs: Vec::new(),
+            },
+            ExecutionResult::Halt { reason, gas_used } => Execution {
+                status: ExecutionStatus::Halted(format!("{:?}", reason)),
+                output: Bytes(Vec::new()),
+                gas_used,
+                logs: Vec::new(),
+            },
+        })
+    }
+
+    /// Drops the cached state, so it is fetched again from the node.
+    ///
+    /// Blocks are cached by their number, so the cache should be cleared after a reorg.
+    pub fn clear_cache(&mut self) {
+        self.forks.clear();
+        self.block_hashes.clear();
+    }
+
+    /// Returns the connection to the node.
+    pub fn into_inner(self) -> Connection<T> {
+        self.connection
+    }
+
+    fn chain_id(&mut self) -> Result<u64, ConnectionError> {
+        if let Some(chain_id) = self.chain_id {
+            return Ok(chain_id);
+        }
+        let chain_id = u64::from_be_bytes(self.connection.call(rpc::eth_chain_id())?.into_bytes());
+        self.chain_id = Some(chain_id);
+        Ok(chain_id)
+    }
+
+    /// Resolves the block and makes sure its state is cached, returns the block number.
+    fn fork(&mut self, block_param: Option<BlockParameter>) -> Result<u64, ConnectionError> {
+        if let Some(BlockParameter::Custom(number)) = block_param {
+            let number = u64::from_be_bytes(number.into_bytes());
+            if self.forks.contains_key(&number) {
+                return Ok(number);
+            }
+        }
+        let fork = ForkCache::fetch(&mut self.connection, block_param)?;
+        let number = fork.number();
+        self.forks.entry(number).or_insert(fork);
+        Ok(number)
+    }
+}
+
+/// The hardfork which is active at the block, the latest supported one on chains other than
+/// mainnet
+fn active_spec_id(chain_id: u64, block: &BlockEnv) -> SpecId {
+    if chain_id != 1 {
+        return SpecId::CANCUN;
+    }
+    let timestamp: u64 = block.timestamp.saturating_to();
+    let number: u64 = block.number.saturating_to();
+    MAINNET_TIME_FORKS
+        .iter()
+        .find(|(activation, _)| timestamp >= *activation)
+        .or_else(|| {
+            MAINNET_BLOCK_FORKS
+                .iter()
+                .find(|(activation, _)| number >= *activation)
+        })
+        .map_or(SpecId::FRONTIER, |(_, spec_id)| *spec_id)
+}
+
+#[cfg(all(test, feature = "dev-chain"))]
+mod tests {
+    use super::*;
+    use crate::devchain::DevChain;
+    use crate::rpc::dev;
+    use crate::types::U256;
+    use std::convert::TryFrom;
+
+    /// Returns storage slot 0 plus one and logs it with topic 0x2a
+    const INCREMENT: &str = "0x60005460010180600052602a60206000a160206000f3";
+    /// Reverts without data
+    const REVERT: &str = "0x60006000fd";
+    /// Returns nothing with `PUSH0`, which exists since Shanghai
+    const PUSH0: &str = "0x5f5ff3";
+
+    fn setup(code: &str) -> (Evm<DevChain>, Address) {
+        let mut connection = Connection::new(DevChain::new());
+        let contract = Address::try_from("0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a").unwrap();
+        connection
+            .call(dev::anvil_set_code(
+                contract,
+                Bytes::try_from(code).unwrap(),
+            ))
+            .unwrap();
+        (Evm::new(connection), contract)
+    }
+
+    fn word(value: u8) -> Vec<u8> {
+        let mut word = vec![0_u8; 32];
+        word[31] = value;
+        word
+    }
+
+    #[test]
+    fn simulate_reads_storage_and_returns_logs() {
+        let (mut evm, contract) = setup(INCREMENT);
+        evm.connection
+            .call(dev::anvil_set_storage_at(
+                contract,
+                U256::zero(),
+                H256::from(<[u8; 32]>::try_from(word(41)).unwrap()),
+            ))
+            .unwrap();
+        let call = Call {
+            to: contract,
+            ..Default::default()
+        };
+
+        let execution = evm.simulate(call.clone(), None).unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.output, Bytes(word(42)));
+        assert!(execution.gas_used > 21_000);
+        assert_eq!(execution.logs.len(), 1);
+        assert_eq!(execution.logs[0].address, contract);
+        assert_eq!(execution.logs[0].topics[0].into_bytes()[31], 0x2a);
+        assert_eq!(execution.logs[0].data, Bytes(word(42)));
+
+        // the storage is cached for the block, calls do not change it
+        assert_eq!(evm.call(call, None).unwrap(), Bytes(word(42)));
+    }
+
+    #[test]
+    fn call_returns_revert() {
+        let (mut evm, contract) = setup(REVERT);
+        let call = Call {
+            to: contract,
+            ..Default::default()
+        };
+        match evm.call(call, None) {
+            Err(EvmError::Revert(data)) => assert!(data.0.is_empty()),
+            other => panic!("expected a revert, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spec_id_sets_the_rules() {
+        let (evm, contract) = setup(PUSH0);
+        let call = Call {
+            to: contract,
+            ..Default::default()
+        };
+        let mut evm = evm.spec_id(SpecId::LONDON);
+        assert!(matches!(
+            evm.call(call.clone(), None),
+            Err(EvmError::Halt(_))
+        ));
+        let mut evm = evm.spec_id(SpecId::SHANGHAI);
+        assert_eq!(evm.call(call, None).unwrap(), Bytes(Vec::new()));
+    }
+
+    #[test]
+    fn mainnet_hardforks() {
+        let block = |number: u64, timestamp: u64| BlockEnv {
+            number: EvmU256::from(number),
+            timestamp: EvmU256::from(timestamp),
+            ..Default::default()
+        };
+        assert_eq!(active_spec_id(1, &block(0, 0)), SpecId::FRONTIER);
+        assert_eq!(active_spec_id(1, &block(12_964_999, 0)), SpecId::BERLIN);
+        assert_eq!(active_spec_id(1, &block(12_965_000, 0)), SpecId::LONDON);
+        assert_eq!(
+            active_spec_id(1, &block(17_034_870, 1_681_338_455)),
+            SpecId::SHANGHAI
+        );
+        assert_eq!(
+            active_spec_id(1, &block(19_426_587, 1_710_338_135)),
+            SpecId::CANCUN
+        );
+        assert_eq!(active_spec_id(1337, &block(0, 0)), SpecId::CANCUN);
+    }
+}
@@ -64,7 +64,16 @@
 
 pub use connection::*;
 
+pub mod chain;
 mod connection;
 pub mod contract;
+#[cfg(feature = "dev-chain")]
+pub mod devchain;
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod logs;
 pub mod rpc;
+pub mod transaction;
 pub mod types;
+#[cfg(feature = "wallet")]
+pub mod wallet;
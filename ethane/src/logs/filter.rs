@@ -0,0 +1,347 @@
+use crate::types::{
+    Address, BlockParameter, Filter, FilterSubscription, ValueOrVec, H256, U256, U64,
+};
+
+use tiny_keccak::{Hasher, Keccak};
+
+/// The values a topic position of a [FilterBuilder] matches
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Topic {
+    /// Matches every topic
+    #[default]
+    Any,
+    /// Matches any of the given topics
+    OneOf(Vec<H256>),
+}
+
+impl From<H256> for Topic {
+    fn from(topic: H256) -> Self {
+        Topic::OneOf(vec![topic])
+    }
+}
+
+/// Indexed addresses are left padded to 32 bytes
+impl From<Address> for Topic {
+    fn from(address: Address) -> Self {
+        let mut topic = [0_u8; 32];
+        topic[12..].copy_from_slice(address.as_bytes());
+        Topic::from(H256::from(topic))
+    }
+}
+
+impl From<U256> for Topic {
+    fn from(value: U256) -> Self {
+        Topic::from(H256::from(value.into_bytes()))
+    }
+}
+
+impl Topic {
+    fn into_filter_topic(self) -> Option<ValueOrVec<H256>> {
+        match self {
+            Topic::Any => None,
+            Topic::OneOf(mut topics) if topics.len() == 1 => topics.pop().map(ValueOrVec::Value),
+            Topic::OneOf(topics) => Some(ValueOrVec::Vec(topics)),
+        }
+    }
+}
+
+/// Creates a topic which matches any of the given values
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// use ethane::logs::{any_of, FilterBuilder};
+/// use ethane::types::Address;
+///
+/// let alice = Address::try_from("0x95eDA452256C1190947f9ba1fD19422f0120858a").unwrap();
+/// let bob = Address::try_from("0x1A4C0439ba035DAcf0D573394107597CEEBF9FF8").unwrap();
+/// let filter = FilterBuilder::new()
+///     .event("Transfer(address indexed from, address indexed to, uint256 value)")
+///     .topic1(any_of(vec![alice, bob]))
+///     .build();
+/// ```
+///
+/// An empty list matches every topic, like [Topic::Any].
+pub fn any_of<T: Into<Topic>>(values: impl IntoIterator<Item = T>) -> Topic {
+    let mut topics = Vec::new();
+    for value in values {
+        match value.into() {
+            Topic::Any => return Topic::Any,
+            Topic::OneOf(one_of) => topics.extend(one_of),
+        }
+    }
+    if topics.is_empty() {
+        Topic::Any
+    } else {
+        Topic::OneOf(topics)
+    }
+}
+
+/// Returns the topic of an event, the Keccak hash of its signature
+///
+/// Parameter names, `indexed` and whitespace are ignored, so the event can be copied from
+/// its Solidity declaration, e.g. `Transfer(address indexed from, address indexed to, uint256)`.
+pub fn event_signature(signature: &str) -> H256 {
+    let mut hasher = Keccak::v256();
+    hasher.update(canonical_signature(signature).as_bytes());
+    let mut out = [0_u8; 32];
+    hasher.finalize(&mut out);
+    H256::from(out)
+}
+
+/// Builds a [Filter] for `eth_getLogs` and `eth_newFilter` or a [FilterSubscription] for
+/// `eth_subscribe`
+///
+/// Every topic position defaults to [Topic::Any].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterBuilder {
+    from_block: Option<BlockParameter>,
+    to_block: Option<BlockParameter>,
+    addresses: Vec<Address>,
+    topics: [Topic; 4],
+}
+
+impl FilterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a contract address, logs of any of the added addresses are matched.
+    pub fn address(mut self, address: Address) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    pub fn from_block(mut self, block: BlockParameter) -> Self {
+        self.from_block = Some(block);
+        self
+    }
+
+    pub fn to_block(mut self, block: BlockParameter) -> Self {
+        self.to_block = Some(block);
+        self
+    }
+
+    /// Sets the inclusive block range by block numbers.
+    pub fn block_range(self, from_block: u64, to_block: u64) -> Self {
+        self.from_block(BlockParameter::Custom(U64::from_int_unchecked(from_block)))
+            .to_block(BlockParameter::Custom(U64::from_int_unchecked(to_block)))
+    }
+
+    /// Matches logs of the event with the given signature, see [event_signature].
+    pub fn event(self, signature: &str) -> Self {
+        self.topic0(event_signature(signature))
+    }
+
+    /// Sets the first topic, which is the [event_signature] for non-anonymous events.
+    pub fn topic0(self, topic: impl Into<Topic>) -> Self {
+        self.topic(0, topic)
+    }
+
+    /// Sets the topic of the first indexed parameter.
+    pub fn topic1(self, topic: impl Into<Topic>) -> Self {
+        self.topic(1, topic)
+    }
+
+    /// Sets the topic of the second indexed parameter.
+    pub fn topic2(self, topic: impl Into<Topic>) -> Self {
+        self.topic(2, topic)
+    }
+
+    /// Sets the topic of the third indexed parameter.
+    pub fn topic3(self, topic: impl Into<Topic>) -> Self {
+        self.topic(3, topic)
+    }
+
+    fn topic(mut self, index: usize, topic: impl Into<Topic>) -> Self {
+        self.topics[index] = topic.into();
+        self
+    }
+
+    pub fn build(self) -> Filter {
+        Filter {
+            from_block: self.from_block,
+            to_block: self.to_block,
+            address: addresses(self.addresses),
+            topics: topics(self.topics),
+        }
+    }
+
+    /// Builds the filter of a subscription, which has no block range.
+    pub fn build_subscription(self) -> FilterSubscription {
+        FilterSubscription {
+            address: addresses(self.addresses),
+            topics: topics(self.topics),
+        }
+    }
+}
+
+impl From<FilterBuilder> for Filter {
+    fn from(builder: FilterBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<FilterBuilder> for FilterSubscription {
+    fn from(builder: FilterBuilder) -> Self {
+        builder.build_subscription()
+    }
+}
+
+fn addresses(mut addresses: Vec<Address>) -> Option<ValueOrVec<Address>> {
+    match addresses.len() {
+        0 => None,
+        1 => addresses.pop().map(ValueOrVec::Value),
+        _ => Some(ValueOrVec::Vec(addresses)),
+    }
+}
+
+/// Trailing wildcards are left out, nodes treat missing positions as wildcards.
+fn topics(topics: [Topic; 4]) -> Option<Vec<Option<ValueOrVec<H256>>>> {
+    let mut topics = Vec::from(topics)
+        .into_iter()
+        .map(Topic::into_filter_topic)
+        .collect::<Vec<_>>();
+    while let Some(None) = topics.last() {
+        topics.pop();
+    }
+    if topics.is_empty() {
+        None
+    } else {
+        Some(topics)
+    }
+}
+
+fn canonical_signature(signature: &str) -> String {
+    let signature = signature.trim();
+    match (signature.find('('), signature.rfind(')')) {
+        (Some(open), Some(close)) if open < close => format!(
+            "{}({})",
+            signature[..open].trim(),
+            canonical_parameters(&signature[open + 1..close])
+        ),
+        _ => signature.to_owned(),
+    }
+}
+
+fn canonical_parameters(parameters: &str) -> String {
+    let mut canonical = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in parameters.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                canonical.push(canonical_type(&parameters[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !parameters[start..].trim().is_empty() {
+        canonical.push(canonical_type(&parameters[start..]));
+    }
+    canonical.join(",")
+}
+
+/// Strips the name and `indexed` of a parameter and expands `uint` and `int`.
+fn canonical_type(parameter: &str) -> String {
+    let parameter = parameter.trim();
+    if parameter.starts_with('(') {
+        let mut depth = 0;
+        for (i, c) in parameter.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                let suffix = parameter[i + 1..].split_whitespace().next().unwrap_or("");
+                return format!(
+                    "({}){}",
+                    canonical_parameters(&parameter[1..i]),
+                    if suffix == "indexed" { "" } else { suffix }
+                );
+            }
+        }
+    }
+    let ty = parameter
+        .split_whitespace()
+        .find(|word| *word != "indexed")
+        .unwrap_or("");
+    let array_start = ty.find('[').unwrap_or(ty.len());
+    match &ty[..array_start] {
+        "uint" | "int" => format!("{}256{}", &ty[..array_start], &ty[array_start..]),
+        _ => ty.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn filter_builder_event_signature() {
+        let transfer =
+            H256::try_from("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+                .unwrap();
+        assert_eq!(
+            event_signature("Transfer(address,address,uint256)"),
+            transfer
+        );
+        assert_eq!(
+            event_signature("Transfer(address,indexed address,uint256)"),
+            transfer
+        );
+        assert_eq!(
+            event_signature(" Transfer(address indexed from, address indexed to, uint value) "),
+            transfer
+        );
+        assert_eq!(
+            canonical_signature("Swap(( address a, uint[] b )[2] indexed swaps, bytes32)"),
+            "Swap((address,uint256[])[2],bytes32)"
+        );
+        assert_eq!(canonical_signature("Ping()"), "Ping()");
+    }
+
+    #[test]
+    fn filter_builder_build() {
+        let contract = Address::try_from("0x95eDA452256C1190947f9ba1fD19422f0120858a").unwrap();
+        let alice = Address::try_from("0x1A4C0439ba035DAcf0D573394107597CEEBF9FF8").unwrap();
+        let builder = FilterBuilder::new()
+            .address(contract)
+            .block_range(10, 20)
+            .event("Transfer(address,indexed address,uint256)")
+            .topic2(any_of(vec![alice, contract]));
+
+        let filter = serde_json::to_value(builder.clone().build()).unwrap();
+        assert_eq!(
+            filter,
+            serde_json::json!({
+                "fromBlock": "0xa",
+                "toBlock": "0x14",
+                "address": "0x95eda452256c1190947f9ba1fd19422f0120858a",
+                "topics": [
+                    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                    null,
+                    [
+                        "0x0000000000000000000000001a4c0439ba035dacf0d573394107597ceebf9ff8",
+                        "0x00000000000000000000000095eda452256c1190947f9ba1fd19422f0120858a"
+                    ]
+                ]
+            })
+        );
+
+        let subscription = FilterSubscription::from(builder.topic2(Topic::Any));
+        assert_eq!(
+            serde_json::to_value(subscription).unwrap(),
+            serde_json::json!({
+                "address": "0x95eda452256c1190947f9ba1fd19422f0120858a",
+                "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"]
+            })
+        );
+        assert_eq!(FilterBuilder::new().build(), Filter::default());
+        assert_eq!(any_of(Vec::<H256>::new()), Topic::Any);
+    }
+}
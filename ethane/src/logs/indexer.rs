@@ -0,0 +1,520 @@
+use super::{FilterBuilder, LogScanner, DEFAULT_CHUNK_SIZE};
+
+use crate::rpc;
+use crate::types::{Log, H256};
+use crate::{Connection, ConnectionError, Request, Subscribe};
+
+use ethane_abi::{Abi, AbiParserError, DecodedLog};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Wraps the different errors that may occur while indexing.
+#[derive(Debug)]
+pub enum IndexerError {
+    /// The node could not be queried
+    Connection(ConnectionError),
+    /// A log of an event in the ABI could not be decoded
+    Decoding(AbiParserError),
+    /// The checkpoint could not be loaded or saved
+    Checkpoint(String),
+    /// The handler failed to process an event
+    Handler(String),
+}
+
+impl From<ConnectionError> for IndexerError {
+    fn from(err: ConnectionError) -> Self {
+        Self::Connection(err)
+    }
+}
+
+/// Persists the number of the last block which was indexed completely
+pub trait CheckpointStore {
+    fn load(&mut self) -> Result<Option<u64>, IndexerError>;
+    fn save(&mut self, block_number: u64) -> Result<(), IndexerError>;
+}
+
+/// Stores the checkpoint as a decimal number in a file
+#[derive(Clone, Debug)]
+pub struct FileCheckpoint {
+    path: PathBuf,
+}
+
+impl FileCheckpoint {
+    /// The file is created on the first save.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CheckpointStore for FileCheckpoint {
+    fn load(&mut self) -> Result<Option<u64>, IndexerError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => content.trim().parse().map(Some).map_err(|_| {
+                IndexerError::Checkpoint(format!(
+                    "Invalid checkpoint in {}: {}",
+                    self.path.display(),
+                    content
+                ))
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(IndexerError::Checkpoint(err.to_string())),
+        }
+    }
+
+    fn save(&mut self, block_number: u64) -> Result<(), IndexerError> {
+        // a rename replaces the file atomically, so a crash never leaves a partial checkpoint
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, block_number.to_string())
+            .and_then(|_| fs::rename(&tmp_path, &self.path))
+            .map_err(|err| IndexerError::Checkpoint(err.to_string()))
+    }
+}
+
+/// Keeps the checkpoint in memory only
+#[derive(Clone, Debug, Default)]
+pub struct MemoryCheckpoint(pub Option<u64>);
+
+impl CheckpointStore for MemoryCheckpoint {
+    fn load(&mut self) -> Result<Option<u64>, IndexerError> {
+        Ok(self.0)
+    }
+
+    fn save(&mut self, block_number: u64) -> Result<(), IndexerError> {
+        self.0 = Some(block_number);
+        Ok(())
+    }
+}
+
+/// A log together with the decoded event
+#[derive(Clone, Debug)]
+pub struct IndexedLog {
+    pub log: Log,
+    pub event: DecodedLog,
+}
+
+/// Passed to the handler of an [Indexer]
+#[derive(Clone, Debug)]
+pub enum IndexerEvent {
+    /// A new log
+    Applied(IndexedLog),
+    /// A log which was applied before, but whose block was removed by a chain reorganization
+    Removed(IndexedLog),
+}
+
+/// Indexes the logs of contract events
+///
+/// Fetches the logs matching the filter from the start block, or the block after the last
+/// checkpoint, up to the current block with a [LogScanner]. Then it follows new logs with a
+/// [subscription](Self::run_subscription) or by [polling](Self::run_polling) a filter. Every log
+/// of an event in the ABI is decoded and passed to the handler, logs of other events are
+/// skipped. So are logs whose signature matches an event but whose number of topics does not,
+/// e.g. an ERC-721 `Transfer` with an indexed token id for an ERC-20 ABI.
+///
+/// The checkpoint is saved after all logs of a block were handled, so after a restart the logs
+/// of at most one block are handled again. Handlers should be idempotent. If the handler returns
+/// an error, indexing stops with it before the block of the failed log is checkpointed.
+pub struct Indexer<S: CheckpointStore> {
+    abi: Abi,
+    filter: FilterBuilder,
+    start_block: u64,
+    chunk_size: u64,
+    poll_interval: Duration,
+    checkpoint: S,
+    last_block: Option<u64>,
+}
+
+impl<S: CheckpointStore> Indexer<S> {
+    /// Creates an indexer which starts at the genesis block if there is no checkpoint yet. The
+    /// block range of the filter is ignored.
+    pub fn new(abi: Abi, filter: FilterBuilder, checkpoint: S) -> Self {
+        Self {
+            abi,
+            filter,
+            start_block: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            poll_interval: Duration::from_secs(1),
+            checkpoint,
+            last_block: None,
+        }
+    }
+
+    /// Sets the first block to index, usually the block in which the contract was deployed.
+    pub fn start_block(mut self, start_block: u64) -> Self {
+        self.start_block = start_block;
+        self
+    }
+
+    /// Sets the number of blocks fetched at once while backfilling, see [LogScanner].
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the time between two polls of [run_polling](Self::run_polling), defaults to 1 second.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The number of the last block which was indexed completely
+    pub fn last_block(&self) -> Option<u64> {
+        self.last_block
+    }
+
+    /// Indexes the logs up to the current block and returns its number.
+    pub fn backfill<T: Request, F: FnMut(IndexerEvent) -> Result<(), IndexerError>>(
+        &mut self,
+        connection: &mut Connection<T>,
+        handler: &mut F,
+    ) -> Result<u64, IndexerError> {
+        self.last_block = self.checkpoint.load()?;
+        let from_block = self.last_block.map_or(self.start_block, |block| block + 1);
+        let head = connection.call(rpc::eth_block_number())?;
+        let head = u64::from_be_bytes(head.into_bytes());
+        if from_block > head {
+            return Ok(head);
+        }
+
+        let scanner = LogScanner::new(self.filter.clone().build(), from_block, head)
+            .chunk_size(self.chunk_size);
+        for log in scanner.iter(connection) {
+            let log = log?;
+            if let Some(block) = block_number(&log) {
+                // the logs of the previous blocks are complete
+                if let Some(previous) = block.checked_sub(1) {
+                    self.advance(previous)?;
+                }
+                self.handle(log, handler)?;
+            }
+        }
+        self.advance(head)?;
+        Ok(head)
+    }
+
+    /// Backfills and then follows new logs with a subscription, until an error occurs.
+    pub fn run_subscription<T, F>(
+        &mut self,
+        connection: &mut Connection<T>,
+        mut handler: F,
+    ) -> Result<(), IndexerError>
+    where
+        T: Subscribe + Request,
+        F: FnMut(IndexerEvent) -> Result<(), IndexerError>,
+    {
+        // subscribe first, so no logs are missed between the backfill and the subscription
+        let mut subscription = connection.subscribe(rpc::eth_subscribe_logs(
+            self.filter.clone().build_subscription(),
+        ))?;
+        self.backfill(connection, &mut handler)?;
+        loop {
+            let log = subscription.next_item()?;
+            self.follow(log, &mut handler)?;
+        }
+    }
+
+    /// Backfills and then follows new logs by polling a filter, until an error occurs.
+    ///
+    /// Unlike [run_subscription](Self::run_subscription), this works over http as well.
+    pub fn run_polling<T, F>(
+        &mut self,
+        connection: &mut Connection<T>,
+        mut handler: F,
+    ) -> Result<(), IndexerError>
+    where
+        T: Request,
+        F: FnMut(IndexerEvent) -> Result<(), IndexerError>,
+    {
+        let mut watcher = connection
+            .watch_logs(self.filter.clone().build())?
+            .poll_interval(self.poll_interval);
+        self.backfill(watcher.connection(), &mut handler)?;
+        loop {
+            let log = watcher.next_item()?;
+            self.follow(log, &mut handler)?;
+        }
+    }
+
+    /// Handles a log of the live phase, which may be removed by a reorganization.
+    fn follow<F: FnMut(IndexerEvent) -> Result<(), IndexerError>>(
+        &mut self,
+        log: Log,
+        handler: &mut F,
+    ) -> Result<(), IndexerError> {
+        let block = match block_number(&log) {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        if log.removed {
+            if self.is_indexed(block) {
+                self.last_block = block.checked_sub(1);
+                if let Some(last_block) = self.last_block {
+                    self.checkpoint.save(last_block)?;
+                }
+            }
+            self.handle(log, handler)
+        } else if self.is_indexed(block) {
+            // already handled while backfilling
+            Ok(())
+        } else {
+            if let Some(previous) = block.checked_sub(1) {
+                self.advance(previous)?;
+            }
+            self.handle(log, handler)
+        }
+    }
+
+    fn is_indexed(&self, block: u64) -> bool {
+        matches!(self.last_block, Some(last_block) if last_block >= block)
+    }
+
+    fn handle<F: FnMut(IndexerEvent) -> Result<(), IndexerError>>(
+        &self,
+        log: Log,
+        handler: &mut F,
+    ) -> Result<(), IndexerError> {
+        let event = match self.abi.decode_log(&log.topics, &log.data.0) {
+            Ok(event) => event,
+            // not an event of the ABI
+            Err(AbiParserError::MissingData(_)) => return Ok(()),
+            // an event of the same signature, but with other indexed parameters
+            Err(_) if !self.matches_topics(&log.topics) => return Ok(()),
+            Err(err) => return Err(IndexerError::Decoding(err)),
+        };
+        let removed = log.removed;
+        let indexed_log = IndexedLog { log, event };
+        handler(if removed {
+            IndexerEvent::Removed(indexed_log)
+        } else {
+            IndexerEvent::Applied(indexed_log)
+        })
+    }
+
+    /// Returns `true` if an event of the ABI has the signature and the number of topics of a log.
+    fn matches_topics(&self, topics: &[H256]) -> bool {
+        let (signature, indexed) = match topics.split_first() {
+            Some(split) => split,
+            None => return false,
+        };
+        self.abi.events.iter().any(|(name, event)| {
+            !event.anonymous
+                && event.topic(name) == *signature
+                && event.inputs.iter().filter(|input| input.indexed).count() == indexed.len()
+        })
+    }
+
+    /// Saves the checkpoint if it moved forward.
+    fn advance(&mut self, block: u64) -> Result<(), IndexerError> {
+        if !self.is_indexed(block) {
+            self.checkpoint.save(block)?;
+            self.last_block = Some(block);
+        }
+        Ok(())
+    }
+}
+
+fn block_number(log: &Log) -> Option<u64> {
+    log.block_number
+        .map(|number| u64::from_be_bytes(number.into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn abi() -> Abi {
+        let mut abi = Abi::new();
+        abi.parse_json(serde_json::json!([{
+            "anonymous": false,
+            "inputs": [
+                {"indexed": true, "name": "from", "type": "address"},
+                {"indexed": true, "name": "to", "type": "address"},
+                {"indexed": false, "name": "value", "type": "uint256"}
+            ],
+            "name": "Transfer",
+            "type": "event"
+        }]))
+        .unwrap();
+        abi
+    }
+
+    fn log(block: u64, value: u8, removed: bool) -> String {
+        format!(
+            r#"{{"address":"0x01","topics":["{}","0x{:064x}","0x{:064x}"],"data":"0x{:064x}",
+            "blockHash":"0x02","blockNumber":"0x{:x}","transactionHash":"0x03",
+            "transactionIndex":"0x0","logIndex":"0x0","transactionLogIndex":"0x0","removed":{}}}"#,
+            TRANSFER, 1, 2, value, block, removed
+        )
+    }
+
+    /// Serves the historical logs with `eth_getLogs` and the given batches of filter changes,
+    /// fails when there are no more changes
+    struct Node {
+        head: u64,
+        logs: Vec<(u64, String)>,
+        changes: VecDeque<Vec<String>>,
+    }
+
+    impl Request for Node {
+        fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+            let request: serde_json::Value = serde_json::from_str(&cmd).unwrap();
+            let result = match request["method"].as_str().unwrap() {
+                "eth_blockNumber" => format!(r#""0x{:x}""#, self.head),
+                "eth_newFilter" => r#""0x1""#.to_owned(),
+                "eth_uninstallFilter" => "true".to_owned(),
+                "eth_getLogs" => {
+                    let block = |key: &str| {
+                        let hex = request["params"][0][key].as_str().unwrap();
+                        u64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap()
+                    };
+                    let range = block("fromBlock")..=block("toBlock");
+                    let logs = self
+                        .logs
+                        .iter()
+                        .filter(|(block, _)| range.contains(block))
+                        .map(|(_, log)| log.clone())
+                        .collect::<Vec<_>>();
+                    format!("[{}]", logs.join(","))
+                }
+                "eth_getFilterChanges" => match self.changes.pop_front() {
+                    Some(changes) => format!("[{}]", changes.join(",")),
+                    None => return Err(ConnectionError::HttpError("closed".to_owned())),
+                },
+                method => panic!("Unexpected method {}", method),
+            };
+            Ok(format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, result))
+        }
+    }
+
+    fn summary(event: &IndexerEvent) -> (bool, u64, String) {
+        match event {
+            IndexerEvent::Applied(log) => {
+                (true, block_number(&log.log).unwrap(), log.event.to_string())
+            }
+            IndexerEvent::Removed(log) => (
+                false,
+                block_number(&log.log).unwrap(),
+                log.event.to_string(),
+            ),
+        }
+    }
+
+    fn transfer(value: u8) -> String {
+        format!(
+            "Transfer(0x0000000000000000000000000000000000000001, \
+             0x0000000000000000000000000000000000000002, {})",
+            value
+        )
+    }
+
+    #[test]
+    fn indexer_backfills_and_follows_reorgs() {
+        let other_event = log(3, 0, false).replace(TRANSFER, &format!("0x{:064x}", 7));
+        let mut connection = Connection::new(Node {
+            head: 5,
+            logs: vec![
+                (2, log(2, 20, false)),
+                (3, other_event),
+                (4, log(4, 40, false)),
+            ],
+            changes: vec![
+                // block 5 was backfilled already
+                vec![log(5, 50, false), log(6, 60, false)],
+                // block 4 is replaced
+                vec![log(4, 40, true), log(4, 41, false), log(7, 70, false)],
+            ]
+            .into(),
+        });
+
+        let mut events = Vec::new();
+        let mut indexer = Indexer::new(abi(), FilterBuilder::new(), MemoryCheckpoint(Some(1)))
+            .poll_interval(Duration::from_millis(0));
+        let result = indexer.run_polling(&mut connection, |event| {
+            events.push(summary(&event));
+            Ok(())
+        });
+        assert!(matches!(
+            result,
+            Err(IndexerError::Connection(ConnectionError::HttpError(_)))
+        ));
+
+        assert_eq!(
+            events,
+            vec![
+                (true, 2, transfer(20)),
+                (true, 4, transfer(40)),
+                (true, 6, transfer(60)),
+                (false, 4, transfer(40)),
+                (true, 4, transfer(41)),
+                (true, 7, transfer(70)),
+            ]
+        );
+        assert_eq!(indexer.last_block(), Some(6));
+        assert_eq!(indexer.checkpoint.0, Some(6));
+    }
+
+    #[test]
+    fn indexer_skips_logs_with_other_topics() {
+        // an ERC-721 Transfer, whose token id is indexed as well
+        let erc721 = log(3, 0, false).replace(
+            &format!(r#""],"data":"0x{:064x}""#, 0),
+            &format!(r#"","0x{:064x}"],"data":"0x""#, 30),
+        );
+        let mut connection = Connection::new(Node {
+            head: 4,
+            logs: vec![(3, erc721), (4, log(4, 40, false))],
+            changes: VecDeque::new(),
+        });
+
+        let mut events = Vec::new();
+        let mut indexer = Indexer::new(abi(), FilterBuilder::new(), MemoryCheckpoint(Some(1)));
+        indexer
+            .backfill(&mut connection, &mut |event| {
+                events.push(summary(&event));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(events, vec![(true, 4, transfer(40))]);
+        assert_eq!(indexer.last_block(), Some(4));
+    }
+
+    #[test]
+    fn indexer_does_not_checkpoint_failed_blocks() {
+        let mut connection = Connection::new(Node {
+            head: 5,
+            logs: vec![(2, log(2, 20, false)), (4, log(4, 40, false))],
+            changes: VecDeque::new(),
+        });
+
+        let mut indexer = Indexer::new(abi(), FilterBuilder::new(), MemoryCheckpoint(Some(1)));
+        let result = indexer.backfill(&mut connection, &mut |event| match summary(&event) {
+            (_, 4, _) => Err(IndexerError::Handler("database unavailable".to_owned())),
+            _ => Ok(()),
+        });
+        assert!(matches!(result, Err(IndexerError::Handler(_))));
+        // block 4 is handled again by the next run
+        assert_eq!(indexer.last_block(), Some(3));
+        assert_eq!(indexer.checkpoint.0, Some(3));
+    }
+
+    #[test]
+    fn indexer_file_checkpoint() {
+        let path = std::env::temp_dir().join(format!("ethane-checkpoint-{}", std::process::id()));
+        let mut checkpoint = FileCheckpoint::new(&path);
+        assert_eq!(checkpoint.load().unwrap(), None);
+        checkpoint.save(42).unwrap();
+        assert_eq!(checkpoint.load().unwrap(), Some(42));
+        assert_eq!(FileCheckpoint::new(&path).load().unwrap(), Some(42));
+
+        fs::write(&path, "not a block").unwrap();
+        assert!(matches!(
+            checkpoint.load(),
+            Err(IndexerError::Checkpoint(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+}
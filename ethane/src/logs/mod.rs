@@ -0,0 +1,17 @@
+//! Helpers to fetch and follow the logs of contracts
+
+mod filter;
+#[cfg(feature = "blocking")]
+mod indexer;
+#[cfg(feature = "blocking")]
+mod scanner;
+
+pub use filter::{any_of, event_signature, FilterBuilder, Topic};
+
+#[cfg(feature = "blocking")]
+pub use indexer::{
+    CheckpointStore, FileCheckpoint, IndexedLog, Indexer, IndexerError, IndexerEvent,
+    MemoryCheckpoint,
+};
+#[cfg(feature = "blocking")]
+pub use scanner::{LogScan, LogScanner, DEFAULT_CHUNK_SIZE};
@@ -0,0 +1,299 @@
+use crate::rpc::Rpc;
+use crate::types::{BlockParameter, Filter, Log, U64};
+use crate::{Connection, ConnectionError, Request};
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// Default number of blocks requested with a single `eth_getLogs`
+pub const DEFAULT_CHUNK_SIZE: u64 = 2_000;
+
+/// Fetches the logs of a large block range with several `eth_getLogs` calls
+///
+/// Most nodes and providers limit the number of blocks or results of a single `eth_getLogs`
+/// call. The range is split into chunks of [chunk_size](Self::chunk_size) blocks and a chunk
+/// is bisected until it succeeds if the node responds with such a limit error. Logs are
+/// always returned in block order.
+#[derive(Clone, Debug)]
+pub struct LogScanner {
+    filter: Filter,
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+}
+
+impl LogScanner {
+    /// Creates a scanner for the logs matching `filter` from `from_block` up to and including
+    /// `to_block`. The block range of the filter is ignored.
+    pub fn new(filter: Filter, from_block: u64, to_block: u64) -> Self {
+        Self {
+            filter,
+            from_block,
+            to_block,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Returns an iterator which fetches the logs chunk by chunk while it is consumed.
+    ///
+    /// The iterator stops after the first error.
+    pub fn iter<'a, T: Request>(&'a self, connection: &'a mut Connection<T>) -> LogScan<'a, T> {
+        LogScan {
+            scanner: self,
+            connection,
+            next_block: Some(self.from_block),
+            logs: VecDeque::new(),
+        }
+    }
+
+    /// Fetches all logs of the range.
+    pub fn scan<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Vec<Log>, ConnectionError> {
+        self.iter(connection).collect()
+    }
+
+    /// Fetches all logs of the range with `workers` threads, each with its own connection
+    /// created by `connect`.
+    pub fn scan_parallel<T, F>(
+        &self,
+        connect: F,
+        workers: usize,
+    ) -> Result<Vec<Log>, ConnectionError>
+    where
+        T: Request,
+        F: Fn() -> Connection<T> + Sync,
+    {
+        let chunks = Mutex::new(self.chunks().enumerate().collect::<VecDeque<_>>());
+        let results = Mutex::new(BTreeMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..workers.max(1) {
+                scope.spawn(|| {
+                    let mut connection = connect();
+                    loop {
+                        let next = chunks.lock().unwrap().pop_front();
+                        let (index, (from, to)) = match next {
+                            Some(chunk) => chunk,
+                            None => break,
+                        };
+                        let logs = self.fetch(&mut connection, from, to);
+                        let failed = logs.is_err();
+                        results.lock().unwrap().insert(index, logs);
+                        if failed {
+                            // let the other workers run out of chunks
+                            chunks.lock().unwrap().clear();
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut logs = Vec::new();
+        for (_, chunk_logs) in results.into_inner().unwrap() {
+            logs.extend(chunk_logs?);
+        }
+        Ok(logs)
+    }
+
+    /// Splits the range into inclusive chunks of at most `chunk_size` blocks.
+    fn chunks(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let mut next = Some(self.from_block);
+        std::iter::from_fn(move || {
+            let chunk = self.chunk_from(next?)?;
+            next = chunk.1.checked_add(1);
+            Some(chunk)
+        })
+    }
+
+    /// Returns the chunk starting at `from`, unless it is past the end of the range.
+    fn chunk_from(&self, from: u64) -> Option<(u64, u64)> {
+        if from > self.to_block {
+            return None;
+        }
+        Some((
+            from,
+            from.saturating_add(self.chunk_size - 1).min(self.to_block),
+        ))
+    }
+
+    /// Fetches the logs of an inclusive block range, bisecting it on limit errors.
+    fn fetch<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Log>, ConnectionError> {
+        let mut filter = self.filter.clone();
+        filter.from_block = Some(BlockParameter::Custom(U64::from_int_unchecked(from)));
+        filter.to_block = Some(BlockParameter::Custom(U64::from_int_unchecked(to)));
+        match connection.call(get_logs(filter)) {
+            Err(ConnectionError::JsonRpc(err))
+                if from < to && is_limit_exceeded(err.code, &err.message) =>
+            {
+                let middle = from + (to - from) / 2;
+                let mut logs = self.fetch(connection, from, middle)?;
+                logs.extend(self.fetch(connection, middle + 1, to)?);
+                Ok(logs)
+            }
+            logs => logs,
+        }
+    }
+}
+
+/// Iterator over the logs of a [LogScanner], created by [iter](LogScanner::iter)
+pub struct LogScan<'a, T: Request> {
+    scanner: &'a LogScanner,
+    connection: &'a mut Connection<T>,
+    next_block: Option<u64>,
+    logs: VecDeque<Log>,
+}
+
+impl<'a, T: Request> Iterator for LogScan<'a, T> {
+    type Item = Result<Log, ConnectionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(log) = self.logs.pop_front() {
+                return Some(Ok(log));
+            }
+            let (from, to) = self.scanner.chunk_from(self.next_block?)?;
+            self.next_block = to.checked_add(1);
+            match self.scanner.fetch(self.connection, from, to) {
+                Ok(logs) => self.logs.extend(logs),
+                Err(err) => {
+                    self.next_block = None;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// Same as [eth_get_logs](crate::rpc::eth_get_logs), but returns logs only.
+fn get_logs(filter: Filter) -> Rpc<Vec<Log>> {
+    let mut rpc = Rpc::new("eth_getLogs");
+    rpc.add_param(filter);
+    rpc
+}
+
+/// Checks for the errors of nodes and providers if a range has too many blocks or results, e.g.
+/// "query returned more than 10000 results" or "exceed maximum block range: 5000".
+fn is_limit_exceeded(code: i64, message: &str) -> bool {
+    let message = message.to_lowercase();
+    code == -32005
+        || [
+            "more than",
+            "too many",
+            "block range",
+            "range too",
+            "limit exceeded",
+            "response size",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Answers `eth_getLogs` with one log per block, failing for ranges of more than `limit`
+    /// blocks
+    struct LimitedNode {
+        limit: u64,
+        requests: Rc<Cell<usize>>,
+    }
+
+    fn connect(limit: u64) -> Connection<LimitedNode> {
+        Connection::new(LimitedNode {
+            limit,
+            requests: Rc::new(Cell::new(0)),
+        })
+    }
+
+    impl Request for LimitedNode {
+        fn request(&mut self, cmd: String) -> Result<String, ConnectionError> {
+            self.requests.set(self.requests.get() + 1);
+            let request: serde_json::Value = serde_json::from_str(&cmd).unwrap();
+            let block = |key: &str| {
+                let hex = request["params"][0][key].as_str().unwrap();
+                u64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap()
+            };
+            let (from, to) = (block("fromBlock"), block("toBlock"));
+            if to - from + 1 > self.limit {
+                return Ok(String::from(
+                    r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"query returned more than 10000 results"}}"#,
+                ));
+            }
+            let logs = (from..=to)
+                .map(|block| {
+                    format!(
+                        r#"{{"address":"0x01","topics":[],"data":"0x","blockHash":"0x02",
+                        "blockNumber":"0x{:x}","transactionHash":"0x03","transactionIndex":"0x0",
+                        "logIndex":"0x0","transactionLogIndex":"0x0","removed":false}}"#,
+                        block
+                    )
+                })
+                .collect::<Vec<_>>();
+            Ok(format!(
+                r#"{{"jsonrpc":"2.0","id":1,"result":[{}]}}"#,
+                logs.join(",")
+            ))
+        }
+    }
+
+    fn block_numbers(logs: &[Log]) -> Vec<u64> {
+        logs.iter()
+            .map(|log| u64::from_be_bytes(log.block_number.unwrap().into_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn log_scanner_chunks() {
+        let scanner = LogScanner::new(Filter::default(), 5, 14).chunk_size(4);
+        assert_eq!(
+            scanner.chunks().collect::<Vec<_>>(),
+            vec![(5, 8), (9, 12), (13, 14)]
+        );
+        let scanner = LogScanner::new(Filter::default(), 5, 4);
+        assert_eq!(scanner.chunks().count(), 0);
+    }
+
+    #[test]
+    fn log_scanner_bisects_on_limit() {
+        let requests = Rc::new(Cell::new(0));
+        let mut connection = Connection::new(LimitedNode {
+            limit: 3,
+            requests: requests.clone(),
+        });
+        let scanner = LogScanner::new(Filter::default(), 10, 29).chunk_size(10);
+        let logs = scanner.scan(&mut connection).unwrap();
+        assert_eq!(block_numbers(&logs), (10..=29).collect::<Vec<_>>());
+        // every chunk of 10 is split into 5 + 5, then into 3 + 2
+        assert_eq!(requests.get(), 2 * (1 + 2 + 4));
+
+        let mut connection = connect(0);
+        let mut scan = scanner.iter(&mut connection);
+        assert!(matches!(
+            scan.next(),
+            Some(Err(ConnectionError::JsonRpc(_)))
+        ));
+        assert!(scan.next().is_none());
+    }
+
+    #[test]
+    fn log_scanner_parallel() {
+        let scanner = LogScanner::new(Filter::default(), 0, 99).chunk_size(7);
+        let logs = scanner.scan_parallel(|| connect(5), 4).unwrap();
+        assert_eq!(block_numbers(&logs), (0..=99).collect::<Vec<_>>());
+    }
+}
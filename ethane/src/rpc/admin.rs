@@ -0,0 +1,96 @@
+use super::Rpc;
+use crate::types::{NodeInfo, PeerInfo};
+
+pub fn admin_node_info() -> Rpc<NodeInfo> {
+    Rpc::new("admin_nodeInfo")
+}
+
+pub fn admin_peers() -> Rpc<Vec<PeerInfo>> {
+    Rpc::new("admin_peers")
+}
+
+/// Adds a static peer given by its enode URL
+pub fn admin_add_peer(enode: String) -> Rpc<bool> {
+    let mut rpc = Rpc::new("admin_addPeer");
+    rpc.add_param(enode);
+    rpc
+}
+
+pub fn admin_remove_peer(enode: String) -> Rpc<bool> {
+    let mut rpc = Rpc::new("admin_removePeer");
+    rpc.add_param(enode);
+    rpc
+}
+
+pub fn admin_datadir() -> Rpc<String> {
+    Rpc::new("admin_datadir")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Connection, MockTransport};
+    use serde_json::json;
+
+    const ENODE: &str = "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:30399";
+
+    #[test]
+    fn node_info() {
+        let mock = MockTransport::new();
+        mock.push_result_for(
+            "admin_nodeInfo",
+            json!([]),
+            json!({
+                "enode": ENODE,
+                "enr": "enr:-Je4QHfbMpAqAuTSjh1YbBbcfaWs3EI0KBKRXn8Iv_0",
+                "id": "d8ab9ea4ba1d1dc5f0dcc6aeb2f9c2bfbfb83e5d9fc3ed3bee7b1fcdcc1d0b42",
+                "ip": "127.0.0.1",
+                "listenAddr": "[::]:30303",
+                "name": "Geth/v1.9.25-stable-e7872729/linux-amd64/go1.15.6",
+                "ports": { "discovery": 0, "listener": 30303 },
+                "protocols": {
+                    "eth": {
+                        "network": 1337,
+                        "difficulty": 1,
+                        "genesis": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+                        "config": { "chainId": 1337 },
+                        "head": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add"
+                    },
+                    "snap": {}
+                }
+            }),
+        );
+        let mut connection = Connection::new(mock.clone());
+
+        let node_info = connection.call(admin_node_info()).unwrap();
+        assert_eq!(node_info.enode, ENODE);
+        assert_eq!(node_info.ports.listener, 30303);
+        assert_eq!(node_info.protocols["eth"]["network"], 1337);
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn peers() {
+        let mock = MockTransport::new();
+        mock.push_result_for("admin_peers", json!([]), json!([]));
+        let mut connection = Connection::new(mock.clone());
+        assert!(connection.call(admin_peers()).unwrap().is_empty());
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn add_and_remove_peer() {
+        let mock = MockTransport::new();
+        mock.push_result_for("admin_addPeer", [ENODE], true);
+        mock.push_result_for("admin_removePeer", [ENODE], true);
+        mock.push_result_for("admin_datadir", json!([]), "/tmp/geth");
+        let mut connection = Connection::new(mock.clone());
+
+        assert!(connection.call(admin_add_peer(ENODE.to_owned())).unwrap());
+        assert!(connection
+            .call(admin_remove_peer(ENODE.to_owned()))
+            .unwrap());
+        assert_eq!(connection.call(admin_datadir()).unwrap(), "/tmp/geth");
+        assert!(mock.is_done());
+    }
+}
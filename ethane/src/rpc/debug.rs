@@ -0,0 +1,103 @@
+use super::Rpc;
+use crate::types::{
+    Address, BlockParameter, BlockTrace, Call, CallFrame, PrestateAccount, StructLoggerTrace, H256,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// A tracer of the `debug_trace*` methods, which determines the shape of the returned trace
+pub trait Tracer {
+    type Output: DeserializeOwned + Debug;
+
+    /// The trace options passed to the node
+    fn options(&self) -> Value;
+}
+
+/// The default tracer of geth, which returns every executed opcode
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StructLogger {
+    pub disable_storage: bool,
+    pub disable_stack: bool,
+    pub enable_memory: bool,
+    pub enable_return_data: bool,
+}
+
+impl Tracer for StructLogger {
+    type Output = StructLoggerTrace;
+
+    fn options(&self) -> Value {
+        json!({
+            "disableStorage": self.disable_storage,
+            "disableStack": self.disable_stack,
+            "enableMemory": self.enable_memory,
+            "enableReturnData": self.enable_return_data,
+        })
+    }
+}
+
+/// Returns the tree of internal calls
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CallTracer {
+    /// Only trace the top level call
+    pub only_top_call: bool,
+    /// Include the logs emitted by each call
+    pub with_log: bool,
+}
+
+impl Tracer for CallTracer {
+    type Output = CallFrame;
+
+    fn options(&self) -> Value {
+        json!({
+            "tracer": "callTracer",
+            "tracerConfig": {
+                "onlyTopCall": self.only_top_call,
+                "withLog": self.with_log,
+            },
+        })
+    }
+}
+
+/// Returns the state of all accounts touched by the transaction before its execution
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PrestateTracer;
+
+impl Tracer for PrestateTracer {
+    type Output = HashMap<Address, PrestateAccount>;
+
+    fn options(&self) -> Value {
+        json!({ "tracer": "prestateTracer" })
+    }
+}
+
+pub fn debug_trace_transaction<T: Tracer>(transaction_hash: H256, tracer: T) -> Rpc<T::Output> {
+    let mut rpc = Rpc::new("debug_traceTransaction");
+    rpc.add_param(transaction_hash);
+    rpc.add_param(tracer.options());
+    rpc
+}
+
+pub fn debug_trace_call<T: Tracer>(
+    call: Call,
+    block_param: Option<BlockParameter>,
+    tracer: T,
+) -> Rpc<T::Output> {
+    let block_param = block_param.unwrap_or(BlockParameter::Latest);
+    let mut rpc = Rpc::new("debug_traceCall");
+    rpc.add_param(call);
+    rpc.add_param(block_param);
+    rpc.add_param(tracer.options());
+    rpc
+}
+
+pub fn debug_trace_block_by_number<T: Tracer>(
+    block_param: BlockParameter,
+    tracer: T,
+) -> Rpc<Vec<BlockTrace<T::Output>>> {
+    let mut rpc = Rpc::new("debug_traceBlockByNumber");
+    rpc.add_param(block_param);
+    rpc.add_param(tracer.options());
+    rpc
+}
@@ -0,0 +1,150 @@
+//! Cheatcodes of development nodes like Ganache, Anvil and Hardhat
+//!
+//! The `evm_` methods are supported by all of them, the `anvil_` and `hardhat_` methods only by
+//! the respective node. Anvil additionally supports the `hardhat_` names, but returns `null`
+//! where Hardhat returns `true`.
+
+use super::Rpc;
+use crate::types::{Address, Bytes, H256, U256};
+use serde_json::Value;
+
+/// Saves the current state of the chain and returns an id to [revert](evm_revert) to it
+pub fn evm_snapshot() -> Rpc<U256> {
+    Rpc::new("evm_snapshot")
+}
+
+/// Restores the state of a snapshot, which can only be reverted to once
+pub fn evm_revert(snapshot_id: U256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("evm_revert");
+    rpc.add_param(snapshot_id);
+    rpc
+}
+
+/// Mines a single block, optionally with the given timestamp
+pub fn evm_mine(timestamp: Option<u64>) -> Rpc<String> {
+    let mut rpc = Rpc::new("evm_mine");
+    if let Some(timestamp) = timestamp {
+        rpc.add_param(timestamp);
+    }
+    rpc
+}
+
+/// Moves the time of the following blocks forward and returns the total adjustment in seconds
+pub fn evm_increase_time(seconds: u64) -> Rpc<i64> {
+    let mut rpc = Rpc::new("evm_increaseTime");
+    rpc.add_param(seconds);
+    rpc
+}
+
+/// Sets the timestamp of the next block, not supported by Ganache
+///
+/// The result differs between nodes, Anvil returns `null` and Hardhat the timestamp.
+pub fn evm_set_next_block_timestamp(timestamp: u64) -> Rpc<Value> {
+    let mut rpc = Rpc::new("evm_setNextBlockTimestamp");
+    rpc.add_param(timestamp);
+    rpc
+}
+
+pub fn anvil_set_balance(address: Address, balance: U256) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_setBalance");
+    rpc.add_param(address);
+    rpc.add_param(balance);
+    rpc
+}
+
+pub fn anvil_set_code(address: Address, code: Bytes) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_setCode");
+    rpc.add_param(address);
+    rpc.add_param(code);
+    rpc
+}
+
+pub fn anvil_set_nonce(address: Address, nonce: U256) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_setNonce");
+    rpc.add_param(address);
+    rpc.add_param(nonce);
+    rpc
+}
+
+pub fn anvil_set_storage_at(address: Address, slot: U256, value: H256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("anvil_setStorageAt");
+    rpc.add_param(address);
+    rpc.add_param(slot);
+    rpc.add_param(value);
+    rpc
+}
+
+/// Allows sending transactions from the address without its private key
+pub fn anvil_impersonate_account(address: Address) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_impersonateAccount");
+    rpc.add_param(address);
+    rpc
+}
+
+pub fn anvil_stop_impersonating_account(address: Address) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_stopImpersonatingAccount");
+    rpc.add_param(address);
+    rpc
+}
+
+/// Mines `blocks` blocks at once, `interval` seconds apart
+pub fn anvil_mine(blocks: Option<U256>, interval: Option<U256>) -> Rpc<()> {
+    let mut rpc = Rpc::new("anvil_mine");
+    rpc.add_param(blocks.unwrap_or_else(|| U256::from_int_unchecked(1_u8)));
+    if let Some(interval) = interval {
+        rpc.add_param(interval);
+    }
+    rpc
+}
+
+pub fn hardhat_set_balance(address: Address, balance: U256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_setBalance");
+    rpc.add_param(address);
+    rpc.add_param(balance);
+    rpc
+}
+
+pub fn hardhat_set_code(address: Address, code: Bytes) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_setCode");
+    rpc.add_param(address);
+    rpc.add_param(code);
+    rpc
+}
+
+pub fn hardhat_set_nonce(address: Address, nonce: U256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_setNonce");
+    rpc.add_param(address);
+    rpc.add_param(nonce);
+    rpc
+}
+
+pub fn hardhat_set_storage_at(address: Address, slot: U256, value: H256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_setStorageAt");
+    rpc.add_param(address);
+    rpc.add_param(slot);
+    rpc.add_param(value);
+    rpc
+}
+
+/// Allows sending transactions from the address without its private key
+pub fn hardhat_impersonate_account(address: Address) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_impersonateAccount");
+    rpc.add_param(address);
+    rpc
+}
+
+pub fn hardhat_stop_impersonating_account(address: Address) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_stopImpersonatingAccount");
+    rpc.add_param(address);
+    rpc
+}
+
+/// Mines `blocks` blocks at once, `interval` seconds apart
+pub fn hardhat_mine(blocks: Option<U256>, interval: Option<U256>) -> Rpc<bool> {
+    let mut rpc = Rpc::new("hardhat_mine");
+    rpc.add_param(blocks.unwrap_or_else(|| U256::from_int_unchecked(1_u8)));
+    if let Some(interval) = interval {
+        rpc.add_param(interval);
+    }
+    rpc
+}
@@ -1,7 +1,8 @@
 use super::Rpc;
 use crate::types::{
-    Address, Block, BlockParameter, Bytes, Call, Filter, GasCall, HashOrLog, SignedTransaction,
-    SyncInfo, Transaction, TransactionReceipt, TransactionRequest, H256, H64, U128, U256, U64,
+    AccessListResult, AccountProof, Address, Block, BlockParameter, Bytes, Call, FeeHistory,
+    Filter, GasCall, HashOrLog, SignedTransaction, SyncInfo, Transaction, TransactionReceipt,
+    TransactionRequest, TypedData, H256, H64, U128, U256, U64,
 };
 
 pub fn eth_protocol_version() -> Rpc<String> {
@@ -28,6 +29,26 @@ pub fn eth_gas_price() -> Rpc<U256> {
     Rpc::new("eth_gasPrice")
 }
 
+pub fn eth_fee_history(
+    block_count: U64,
+    newest_block: BlockParameter,
+    reward_percentiles: Vec<f64>,
+) -> Rpc<FeeHistory> {
+    let mut rpc = Rpc::new("eth_feeHistory");
+    rpc.add_param(block_count);
+    rpc.add_param(newest_block);
+    rpc.add_param(reward_percentiles);
+    rpc
+}
+
+pub fn eth_max_priority_fee_per_gas() -> Rpc<U256> {
+    Rpc::new("eth_maxPriorityFeePerGas")
+}
+
+pub fn eth_chain_id() -> Rpc<U64> {
+    Rpc::new("eth_chainId")
+}
+
 pub fn eth_accounts() -> Rpc<Vec<Address>> {
     Rpc::new("eth_accounts")
 }
@@ -62,6 +83,15 @@ pub fn eth_get_transaction_receipt(transaction_hash: H256) -> Rpc<Option<Transac
     rpc
 }
 
+pub fn eth_get_block_receipts(
+    block_param: Option<BlockParameter>,
+) -> Rpc<Option<Vec<TransactionReceipt>>> {
+    let block_param = block_param.unwrap_or(BlockParameter::Latest);
+    let mut rpc = Rpc::new("eth_getBlockReceipts");
+    rpc.add_param(block_param);
+    rpc
+}
+
 pub fn eth_get_storage_at(
     address: Address,
     storage_pos: U256,
@@ -131,6 +161,19 @@ pub fn eth_get_code(address: Address, block_param: Option<BlockParameter>) -> Rp
     rpc
 }
 
+pub fn eth_get_proof(
+    address: Address,
+    storage_keys: Vec<H256>,
+    block_param: Option<BlockParameter>,
+) -> Rpc<AccountProof> {
+    let block_param = block_param.unwrap_or(BlockParameter::Latest);
+    let mut rpc = Rpc::new("eth_getProof");
+    rpc.add_param(address);
+    rpc.add_param(storage_keys);
+    rpc.add_param(block_param);
+    rpc
+}
+
 pub fn eth_sign(address: Address, data: Bytes) -> Rpc<Bytes> {
     let mut rpc = Rpc::new("eth_sign");
     rpc.add_param(address);
@@ -138,6 +181,13 @@ pub fn eth_sign(address: Address, data: Bytes) -> Rpc<Bytes> {
     rpc
 }
 
+pub fn eth_sign_typed_data_v4(address: Address, typed_data: TypedData) -> Rpc<Bytes> {
+    let mut rpc = Rpc::new("eth_signTypedData_v4");
+    rpc.add_param(address);
+    rpc.add_param(typed_data);
+    rpc
+}
+
 // DEVIATION FROM SPEC
 // c.f. https://github.com/ethereum/go-ethereum/issues/22223
 // also geth returns something like: {raw: hex_encoded_tx, tx: json_encoded_tx}, however according to JSON RPC
@@ -172,6 +222,17 @@ pub fn eth_estimate_gas(gas_call: GasCall, block_param: Option<BlockParameter>)
     rpc
 }
 
+pub fn eth_create_access_list(
+    gas_call: GasCall,
+    block_param: Option<BlockParameter>,
+) -> Rpc<AccessListResult> {
+    let block_param = block_param.unwrap_or(BlockParameter::Latest);
+    let mut rpc = Rpc::new("eth_createAccessList");
+    rpc.add_param(gas_call);
+    rpc.add_param(block_param);
+    rpc
+}
+
 pub fn eth_get_block_by_hash(block_hash: H256, full_transactions: bool) -> Rpc<Option<Block>> {
     let mut rpc = Rpc::new("eth_getBlockByHash");
     rpc.add_param(block_hash);
@@ -326,3 +387,224 @@ pub fn eth_submit_hashrate(hash_rate: H256, client_id: H256) -> Rpc<bool> {
     rpc.add_param(client_id);
     rpc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TypedDataDomain, TypedDataField};
+    use crate::{Connection, MockTransport};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    const ADDRESS: &str = "0x7f0d15c7faae65896648c8273b6d7e43f58fa842";
+    const SLOT: &str = "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+
+    fn connection(mock: &MockTransport) -> Connection<MockTransport> {
+        Connection::new(mock.clone())
+    }
+
+    #[test]
+    fn chain_id_and_max_priority_fee_per_gas() {
+        let mock = MockTransport::new();
+        mock.push_result_for("eth_chainId", json!([]), "0x539");
+        mock.push_result_for("eth_maxPriorityFeePerGas", json!([]), "0x3b9aca00");
+        let mut connection = connection(&mock);
+
+        assert_eq!(
+            connection.call(eth_chain_id()).unwrap(),
+            U64::from_int_unchecked(1337_u16)
+        );
+        assert_eq!(
+            connection.call(eth_max_priority_fee_per_gas()).unwrap(),
+            U256::from_int_unchecked(1_000_000_000_u32)
+        );
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn get_proof() {
+        let mock = MockTransport::new();
+        mock.push_result_for(
+            "eth_getProof",
+            json!([ADDRESS, [SLOT], "0xc5043f"]),
+            json!({
+                "address": ADDRESS,
+                "accountProof": [
+                    "0xf90211a090dcaf88c40c7bbc95a912cbdde67c175767b31173df9ee4b0d733bfdd511c43a0babe369f6b12092f49181ae04ca173fb68d1a5456f18d20fa32cba73954052bda0473ecf8a7e36a829e75039a3b055e51b8332cbf03324ab4af2066bbd6fbf0021a0bbda34753d7aa6c38e603f360244e8f59611921d9e1f128372fec0d586d4f9e0a04e44caecff45c9891f74f6a2156735886eedf6f1a733628ebc802ec79d844648a0a5f3f2f7542148c973977c8a1e154c4300fec92f755f7846f1b734d3ab1d90e7a0e823850f50bf72baae9d1733a36a444ab65d0a6faaba404f0583ce0ca4dad92da0f7a00cbe7d4b30b11faea3ae61b7f1f2b315b61d9f6bd68bfe587ad0eeceb721a07117ef9fc932f1a88e908eaead8565c19b5645dc9e5b1b6e841c5edbdfd71681a069eb2de283f32c11f859d7bcf93da23990d3e662935ed4d6b39ce3673ec84472a0203d26456312bbc4da5cd293b75b840fc5045e493d6f904d180823ec22bfed8ea09287b5c21f2254af4e64fca76acc5cd87399c7f1ede818db4326c98ce2dc2208a06fc2d754e304c48ce6a517753c62b1a9c1d5925b89707486d7fc08919e0a94eca07b1c54f15e299bd58bdfef9741538c7828b5d7d11a489f9c20d052b3471df475a051f9dd3739a927c89e357580a4c97b40234aa01ed3d5e0390dc982a7975880a0a089d613f26159af43616fd9455bb461f4869bfede26f2130835ed067a8b967bfb80"
+                ],
+                "balance": "0x0",
+                "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                "nonce": "0x0",
+                "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "storageProof": [{ "key": SLOT, "value": "0x0", "proof": [] }]
+            }),
+        );
+        let mut connection = connection(&mock);
+
+        let address = Address::try_from(ADDRESS).unwrap();
+        let slot = H256::try_from(SLOT).unwrap();
+        let block = BlockParameter::Custom(U64::from_int_unchecked(12_911_679_u32));
+        let proof = connection
+            .call(eth_get_proof(address, vec![slot], Some(block)))
+            .unwrap();
+        assert_eq!(proof.address, address);
+        assert_eq!(proof.account_proof[0].0.len(), 532);
+        assert_eq!(proof.nonce, U64::zero());
+        assert_eq!(proof.storage_proof[0].key, slot);
+        assert!(proof.storage_proof[0].proof.is_empty());
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn create_access_list() {
+        let mock = MockTransport::new();
+        mock.push_result_for(
+            "eth_createAccessList",
+            json!([
+                {
+                    "from": "0x8cd02c6cbd8375b39b06577f8d50c51d86e8d5cd",
+                    "to": ADDRESS,
+                    "data": "0x608060806080608155"
+                },
+                "pending"
+            ]),
+            json!({
+                "accessList": [{
+                    "address": ADDRESS,
+                    "storageKeys": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000081"
+                    ]
+                }],
+                "gasUsed": "0x125f8"
+            }),
+        );
+        let mut connection = connection(&mock);
+
+        let call = GasCall {
+            from: Some(Address::try_from("0x8cd02c6cbd8375b39b06577f8d50c51d86e8d5cd").unwrap()),
+            to: Some(Address::try_from(ADDRESS).unwrap()),
+            data: Some(Bytes::try_from("0x608060806080608155").unwrap()),
+            ..Default::default()
+        };
+        let result = connection
+            .call(eth_create_access_list(call, Some(BlockParameter::Pending)))
+            .unwrap();
+        assert_eq!(result.access_list[0].storage_keys.len(), 1);
+        assert_eq!(result.gas_used, U256::from_int_unchecked(0x125f8_u32));
+        assert_eq!(result.error, None);
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn get_block_receipts() {
+        let mock = MockTransport::new();
+        mock.push_result_for(
+            "eth_getBlockReceipts",
+            json!(["0x10d4f"]),
+            json!([{
+                "blockHash": "0x8243343df08b9751f5ca0c5f8c9c0460d8a9b6351066fae0acbd4d3e776de8bb",
+                "blockNumber": "0x10d4f",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x5208",
+                "effectiveGasPrice": "0x9502f907",
+                "from": "0xa7d9ddbe1f17865597fbd27ec712455208b6b76d",
+                "gasUsed": "0x5208",
+                "logs": [],
+                "logsBloom": format!("0x{}", "0".repeat(512)),
+                "status": "0x1",
+                "to": "0xf02c1c8e6114b1dbe8937a39260b5b0a374432bb",
+                "transactionHash": "0x85d995eba9763907fdf35cd2034144dd9d53ce32cbec21349d4b12823c6860c5",
+                "transactionIndex": "0x0",
+                "type": "0x2"
+            }]),
+        );
+        mock.push_result_for("eth_getBlockReceipts", json!(["latest"]), json!(null));
+        let mut connection = connection(&mock);
+
+        let block = BlockParameter::Custom(U64::from_int_unchecked(0x10d4f_u32));
+        let receipts = connection
+            .call(eth_get_block_receipts(Some(block)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(
+            receipts[0].block_number,
+            U64::from_int_unchecked(0x10d4f_u32)
+        );
+        assert_eq!(receipts[0].status, U64::from_int_unchecked(1_u8));
+        // an unknown block has no receipts
+        assert_eq!(connection.call(eth_get_block_receipts(None)).unwrap(), None);
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn sign_typed_data_v4() {
+        let field = |name: &str, field_type: &str| TypedDataField {
+            name: name.to_owned(),
+            field_type: field_type.to_owned(),
+        };
+        let mut types = HashMap::new();
+        types.insert(
+            "EIP712Domain".to_owned(),
+            vec![
+                field("name", "string"),
+                field("version", "string"),
+                field("chainId", "uint256"),
+                field("verifyingContract", "address"),
+            ],
+        );
+        types.insert(
+            "Person".to_owned(),
+            vec![field("name", "string"), field("wallet", "address")],
+        );
+        let typed_data = TypedData {
+            types,
+            primary_type: "Person".to_owned(),
+            domain: TypedDataDomain {
+                name: Some("Ether Mail".to_owned()),
+                version: Some("1".to_owned()),
+                chain_id: Some(U64::from_int_unchecked(1_u8)),
+                verifying_contract: Some(
+                    Address::try_from("0xcccccccccccccccccccccccccccccccccccccccc").unwrap(),
+                ),
+                salt: None,
+            },
+            message: json!({
+                "name": "Bob",
+                "wallet": "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+            }),
+        };
+        let signature = format!("0x{}1b", "ab".repeat(64));
+        let mock = MockTransport::new();
+        mock.push_result("eth_signTypedData_v4", &signature);
+        let mut connection = connection(&mock);
+
+        let address = Address::try_from(ADDRESS).unwrap();
+        let result = connection
+            .call(eth_sign_typed_data_v4(address, typed_data))
+            .unwrap();
+        assert_eq!(result, Bytes::try_from(signature.as_str()).unwrap());
+
+        let params = &mock.requests_of("eth_signTypedData_v4")[0].params;
+        assert_eq!(params[0], ADDRESS);
+        assert_eq!(params[1]["primaryType"], "Person");
+        assert_eq!(
+            params[1]["domain"],
+            json!({
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": "0x1",
+                "verifyingContract": "0xcccccccccccccccccccccccccccccccccccccccc"
+            })
+        );
+        assert_eq!(
+            params[1]["types"]["Person"],
+            json!([
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ])
+        );
+        assert_eq!(params[1]["message"]["name"], "Bob");
+    }
+}
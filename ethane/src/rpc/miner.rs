@@ -0,0 +1,68 @@
+use super::Rpc;
+use crate::types::{Address, U256};
+
+/// Starts mining, optionally limited to the given number of threads
+pub fn miner_start(threads: Option<u32>) -> Rpc<()> {
+    let mut rpc = Rpc::new("miner_start");
+    if let Some(threads) = threads {
+        rpc.add_param(threads);
+    }
+    rpc
+}
+
+pub fn miner_stop() -> Rpc<()> {
+    Rpc::new("miner_stop")
+}
+
+pub fn miner_set_etherbase(address: Address) -> Rpc<bool> {
+    let mut rpc = Rpc::new("miner_setEtherbase");
+    rpc.add_param(address);
+    rpc
+}
+
+pub fn miner_set_gas_price(gas_price: U256) -> Rpc<bool> {
+    let mut rpc = Rpc::new("miner_setGasPrice");
+    rpc.add_param(gas_price);
+    rpc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Connection, MockTransport};
+    use serde_json::{json, Value};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn start_and_stop() {
+        let mock = MockTransport::new();
+        mock.push_result_for("miner_stop", json!([]), Value::Null);
+        mock.push_result_for("miner_start", json!([]), Value::Null);
+        mock.push_result_for("miner_start", [2], Value::Null);
+        let mut connection = Connection::new(mock.clone());
+
+        connection.call(miner_stop()).unwrap();
+        connection.call(miner_start(None)).unwrap();
+        connection.call(miner_start(Some(2))).unwrap();
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn set_etherbase_and_gas_price() {
+        let address = "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f";
+        let mock = MockTransport::new();
+        mock.push_result_for("miner_setEtherbase", [address], true);
+        mock.push_result_for("miner_setGasPrice", ["0x3b9aca00"], true);
+        let mut connection = Connection::new(mock.clone());
+
+        assert!(connection
+            .call(miner_set_etherbase(Address::try_from(address).unwrap()))
+            .unwrap());
+        assert!(connection
+            .call(miner_set_gas_price(U256::from_int_unchecked(
+                1_000_000_000_u32
+            )))
+            .unwrap());
+        assert!(mock.is_done());
+    }
+}
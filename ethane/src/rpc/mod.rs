@@ -6,6 +6,11 @@
 //!     - [real-time events](https://geth.ethereum.org/docs/rpc/pubsub)
 //!     - [personal](https://geth.ethereum.org/docs/rpc/ns-personal)
 //!     - [txpool](https://geth.ethereum.org/docs/rpc/ns-txpool)
+//!     - [admin](https://geth.ethereum.org/docs/rpc/ns-admin)
+//!     - [miner](https://geth.ethereum.org/docs/rpc/ns-miner)
+//!     - [debug](https://geth.ethereum.org/docs/rpc/ns-debug) tracing
+//! - the Parity style [trace module](https://openethereum.github.io/JSONRPC-trace-module)
+//! - the cheatcodes of development nodes in [dev]
 //!
 //! There are some deviations between what is really supported and the official specification.
 //! This is why some functions are marked as deprecated. They will probably be removed.
@@ -19,18 +24,28 @@ use serde_json::Value;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
+pub use admin::*;
+pub use debug::*;
 pub use eth::*;
+pub use miner::*;
 pub use net::*;
 pub use personal::*;
 pub use sub::*;
 //pub(crate) use sub::eth_unsubscribe;
+pub use trace::*;
 pub use txpool::*;
 pub use web3::*;
 
+pub mod dev;
+
+mod admin;
+mod debug;
 mod eth;
+mod miner;
 mod net;
 mod personal;
 mod sub;
+mod trace;
 mod txpool;
 mod web3;
 
@@ -62,6 +77,25 @@ pub struct RpcResponse<'a, T: 'a> {
     pub result: T,
 }
 
+/// Response returned by the node if the remote procedure call failed
+#[derive(Deserialize)]
+pub struct RpcErrorResponse<'a> {
+    pub id: Option<usize>,
+    pub jsonrpc: &'a str,
+    pub error: RpcError,
+}
+
+/// Error object of a failed remote procedure call
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RpcError {
+    /// The error code, e.g. `-32000` for a generic server error or `3` for a reverted execution
+    pub code: i64,
+    /// A short description of the error
+    pub message: String,
+    /// Additional information about the error, e.g. the revert data of a failed call
+    pub data: Option<Value>,
+}
+
 impl<T: DeserializeOwned + Debug> Rpc<T> {
     const JSON_RPC: &'static str = "2.0";
 
@@ -75,9 +109,73 @@ impl<T: DeserializeOwned + Debug> Rpc<T> {
         }
     }
 
+    /// Creates a call of any method, e.g. one of a node extension which is not wrapped here.
+    ///
+    /// `params` has to serialize to a JSON array, like a `Vec` or a tuple, to pass several
+    /// parameters. Any other value is passed as the only parameter, `()` passes none. The result
+    /// is deserialized into `T`, use [Value] to get the raw JSON.
+    ///
+    /// Panics if `params` fails to serialize, e.g. a map whose keys are not strings.
+    pub fn custom<U: Serialize>(method: &str, params: U) -> Rpc<T> {
+        let mut rpc = Rpc::new(method);
+        match serde_json::to_value(params) {
+            Ok(Value::Array(params)) => rpc.params = params,
+            Ok(Value::Null) => (),
+            Ok(param) => rpc.params.push(param),
+            Err(err) => panic!("Invalid parameters of {}: {}", method, err),
+        }
+        rpc
+    }
+
+    /// Appends a parameter, panicking if it is invalid, e.g. a filter bounded by a block hash.
     pub(crate) fn add_param<U: Serialize + Debug>(&mut self, parameter: U) {
-        if let Ok(serialized_param) = serde_json::to_value(&parameter) {
-            self.params.push(serialized_param);
+        match serde_json::to_value(&parameter) {
+            Ok(serialized_param) => self.params.push(serialized_param),
+            Err(err) => panic!("Invalid parameter of {}: {}", self.method, err),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, BlockParameter};
+
+    #[test]
+    fn custom_rpc() {
+        let rpc: Rpc<Value> = Rpc::custom(
+            "optimism_outputAtBlock",
+            (Address::zero(), BlockParameter::Latest),
+        );
+        assert_eq!(
+            serde_json::to_string(&rpc).unwrap(),
+            r#"{"jsonrpc":"2.0","method":"optimism_outputAtBlock","params":["0x0000000000000000000000000000000000000000","latest"],"id":0}"#
+        );
+
+        let rpc: Rpc<bool> = Rpc::custom("miner_setExtra", "ethane");
+        assert_eq!(rpc.params, vec![Value::from("ethane")]);
+        let rpc: Rpc<bool> = Rpc::custom("miner_stop", ());
+        assert!(rpc.params.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid parameters of debug_custom")]
+    fn invalid_custom_params() {
+        let params: std::collections::HashMap<(u8, u8), u8> =
+            vec![((1, 2), 3)].into_iter().collect();
+        let _: Rpc<Value> = Rpc::custom("debug_custom", params);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid parameter of eth_getLogs")]
+    fn invalid_param() {
+        let filter = crate::types::Filter {
+            from_block: Some(BlockParameter::Hash {
+                block_hash: Default::default(),
+                require_canonical: false,
+            }),
+            ..Default::default()
+        };
+        eth_get_logs(filter);
+    }
+}
@@ -2,6 +2,7 @@
 
 use super::Rpc;
 use crate::types::{BlockHeader, FilterSubscription, Log, SyncInfoSubscription, H256, U128};
+use serde::Serialize;
 use std::marker::PhantomData;
 
 /// Rpc to start a subscription
@@ -10,6 +11,19 @@ pub struct SubscriptionRequest<T> {
     result_type: PhantomData<T>,
 }
 
+impl<T> SubscriptionRequest<T> {
+    /// Creates a subscription with any method and parameters like [Rpc::custom].
+    ///
+    /// The items are deserialized into `T` and the subscription is cancelled with
+    /// `eth_unsubscribe`.
+    pub fn custom<U: Serialize>(method: &str, params: U) -> Self {
+        SubscriptionRequest {
+            rpc: Rpc::custom(method, params),
+            result_type: PhantomData,
+        }
+    }
+}
+
 pub fn eth_subscribe_new_heads() -> SubscriptionRequest<BlockHeader> {
     let mut rpc = Rpc::new("eth_subscribe");
     rpc.add_param("newHeads");
@@ -0,0 +1,14 @@
+use super::Rpc;
+use crate::types::{BlockParameter, LocalizedTrace, TraceFilter};
+
+pub fn trace_block(block_param: BlockParameter) -> Rpc<Option<Vec<LocalizedTrace>>> {
+    let mut rpc = Rpc::new("trace_block");
+    rpc.add_param(block_param);
+    rpc
+}
+
+pub fn trace_filter(filter: TraceFilter) -> Rpc<Vec<LocalizedTrace>> {
+    let mut rpc = Rpc::new("trace_filter");
+    rpc.add_param(filter);
+    rpc
+}
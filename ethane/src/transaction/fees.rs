@@ -0,0 +1,334 @@
+use super::PendingTransaction;
+use crate::rpc;
+use crate::types::{BlockParameter, TransactionRequest, U256, U64};
+use crate::{Connection, ConnectionError, Request};
+
+use std::convert::TryInto;
+
+/// Priority fee used by [FeeHistoryEstimator] if the fee history contains no rewards, 1 gwei.
+pub const DEFAULT_PRIORITY_FEE: u128 = 1_000_000_000;
+
+/// Fee fields of a transaction
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fees {
+    /// A legacy transaction paying a fixed gas price
+    Legacy { gas_price: U256 },
+    /// An EIP-1559 transaction paying the base fee and a priority fee, up to `max_fee_per_gas`
+    Eip1559 {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+}
+
+impl Fees {
+    /// Sets the fee fields of the transaction and clears the ones of the other transaction type.
+    pub fn apply(&self, transaction: &mut TransactionRequest) {
+        match *self {
+            Fees::Legacy { gas_price } => {
+                transaction.gas_price = Some(gas_price);
+                transaction.max_fee_per_gas = None;
+                transaction.max_priority_fee_per_gas = None;
+            }
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                transaction.gas_price = None;
+                transaction.max_fee_per_gas = Some(max_fee_per_gas);
+                transaction.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            }
+        }
+    }
+}
+
+/// A strategy to determine the fees of a transaction
+pub trait FeeEstimator {
+    fn estimate<T: Request>(&self, connection: &mut Connection<T>)
+        -> Result<Fees, ConnectionError>;
+
+    /// Estimates and applies the fees if the transaction has none of the fee fields set.
+    fn fill<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        transaction: &mut TransactionRequest,
+    ) -> Result<(), ConnectionError> {
+        if transaction.gas_price.is_none()
+            && transaction.max_fee_per_gas.is_none()
+            && transaction.max_priority_fee_per_gas.is_none()
+        {
+            self.estimate(connection)?.apply(transaction);
+        }
+        Ok(())
+    }
+
+    /// Sends a transaction after [filling](Self::fill) its fees.
+    fn send_transaction<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        mut transaction: TransactionRequest,
+    ) -> Result<PendingTransaction, ConnectionError> {
+        self.fill(connection, &mut transaction)?;
+        connection.send_transaction(transaction)
+    }
+}
+
+/// Uses the legacy gas price returned by `eth_gasPrice`
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GasPriceEstimator;
+
+impl FeeEstimator for GasPriceEstimator {
+    fn estimate<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Fees, ConnectionError> {
+        Ok(Fees::Legacy {
+            gas_price: connection.call(rpc::eth_gas_price())?,
+        })
+    }
+}
+
+/// Estimates EIP-1559 fees from `eth_feeHistory`
+///
+/// The priority fee is the average of the rewards at `percentile` over the last `block_count`
+/// blocks. The max fee allows the base fee to double before the transaction is priced out.
+#[derive(Copy, Clone, Debug)]
+pub struct FeeHistoryEstimator {
+    pub block_count: u64,
+    pub percentile: f64,
+}
+
+impl Default for FeeHistoryEstimator {
+    fn default() -> Self {
+        Self {
+            block_count: 10,
+            percentile: 50.0,
+        }
+    }
+}
+
+impl FeeEstimator for FeeHistoryEstimator {
+    fn estimate<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Fees, ConnectionError> {
+        let history = connection.call(rpc::eth_fee_history(
+            U64::from_int_unchecked(self.block_count),
+            BlockParameter::Latest,
+            vec![self.percentile],
+        ))?;
+        let base_fee = history
+            .base_fee_per_gas
+            .last()
+            .map(to_u128)
+            .ok_or_else(|| {
+                ConnectionError::Serde(String::from("Fee history contains no base fee"))
+            })?;
+        let rewards = history
+            .reward
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|rewards| rewards.first().map(to_u128))
+            .collect::<Vec<u128>>();
+        let priority_fee = if rewards.is_empty() {
+            DEFAULT_PRIORITY_FEE
+        } else {
+            rewards
+                .iter()
+                .fold(0_u128, |sum, reward| sum.saturating_add(*reward))
+                / rewards.len() as u128
+        };
+
+        Ok(Fees::Eip1559 {
+            max_fee_per_gas: U256::from_int_unchecked(
+                base_fee.saturating_mul(2).saturating_add(priority_fee),
+            ),
+            max_priority_fee_per_gas: U256::from_int_unchecked(priority_fee),
+        })
+    }
+}
+
+/// Always returns the same fees
+#[derive(Copy, Clone, Debug)]
+pub struct FixedFees(pub Fees);
+
+impl FeeEstimator for FixedFees {
+    fn estimate<T: Request>(
+        &self,
+        _connection: &mut Connection<T>,
+    ) -> Result<Fees, ConnectionError> {
+        Ok(self.0)
+    }
+}
+
+/// Limits the fees returned by another estimator
+///
+/// The gas price of legacy transactions and the max fee of EIP-1559 transactions are capped at
+/// `max_fee`, the priority fee never exceeds the max fee.
+#[derive(Copy, Clone, Debug)]
+pub struct CappedFees<E: FeeEstimator> {
+    pub estimator: E,
+    pub max_fee: U256,
+}
+
+impl<E: FeeEstimator> FeeEstimator for CappedFees<E> {
+    fn estimate<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+    ) -> Result<Fees, ConnectionError> {
+        let cap = to_u128(&self.max_fee);
+        let fees = match self.estimator.estimate(connection)? {
+            Fees::Legacy { gas_price } => Fees::Legacy {
+                gas_price: U256::from_int_unchecked(to_u128(&gas_price).min(cap)),
+            },
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let max_fee = to_u128(&max_fee_per_gas).min(cap);
+                Fees::Eip1559 {
+                    max_fee_per_gas: U256::from_int_unchecked(max_fee),
+                    max_priority_fee_per_gas: U256::from_int_unchecked(
+                        to_u128(&max_priority_fee_per_gas).min(max_fee),
+                    ),
+                }
+            }
+        };
+        Ok(fees)
+    }
+}
+
+/// Converts a fee to `u128`, saturating at `u128::MAX` which is far beyond any real fee.
+pub(crate) fn to_u128(value: &U256) -> u128 {
+    let (high, low) = value.as_bytes().split_at(16);
+    if high.iter().any(|b| *b != 0) {
+        return u128::MAX;
+    }
+    // unwrap is fine, the slice is exactly 16 bytes long
+    u128::from_be_bytes(low.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Address;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    /// Queues the fee history of the default estimator
+    fn fee_history(mock: &MockTransport, history: serde_json::Value) {
+        mock.push_result_for(
+            "eth_feeHistory",
+            (
+                U64::from_int_unchecked(10_u8),
+                BlockParameter::Latest,
+                [50.0],
+            ),
+            history,
+        );
+    }
+
+    fn gwei(value: u128) -> U256 {
+        U256::from_int_unchecked(value * 1_000_000_000)
+    }
+
+    #[test]
+    fn fee_history_estimator() {
+        // base fees of 10, 12 and 14 gwei with rewards of 1 and 3 gwei
+        let mock = MockTransport::new();
+        fee_history(
+            &mock,
+            json!({
+                "oldestBlock": "0x10",
+                "baseFeePerGas": ["0x2540be400", "0x2cb417800", "0x342770c00"],
+                "gasUsedRatio": [0.9, 0.8],
+                "reward": [["0x3b9aca00"], ["0xb2d05e00"]]
+            }),
+        );
+        let mut connection = Connection::new(mock.clone());
+        let fees = FeeHistoryEstimator::default()
+            .estimate(&mut connection)
+            .unwrap();
+        assert_eq!(
+            fees,
+            Fees::Eip1559 {
+                max_fee_per_gas: gwei(30),
+                max_priority_fee_per_gas: gwei(2),
+            }
+        );
+
+        fee_history(
+            &mock,
+            json!({
+                "oldestBlock": "0x10",
+                "baseFeePerGas": ["0x2540be400"],
+                "gasUsedRatio": []
+            }),
+        );
+        let fees = FeeHistoryEstimator::default()
+            .estimate(&mut connection)
+            .unwrap();
+        assert_eq!(
+            fees,
+            Fees::Eip1559 {
+                max_fee_per_gas: gwei(21),
+                max_priority_fee_per_gas: gwei(1),
+            }
+        );
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn capped_fees() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_gasPrice", "0x12a05f200");
+        let mut connection = Connection::new(mock.clone());
+        let capped = CappedFees {
+            estimator: GasPriceEstimator,
+            max_fee: gwei(3),
+        };
+        assert_eq!(
+            capped.estimate(&mut connection).unwrap(),
+            Fees::Legacy { gas_price: gwei(3) }
+        );
+
+        let capped = CappedFees {
+            estimator: FixedFees(Fees::Eip1559 {
+                max_fee_per_gas: gwei(50),
+                max_priority_fee_per_gas: gwei(5),
+            }),
+            max_fee: gwei(4),
+        };
+        assert_eq!(
+            capped.estimate(&mut connection).unwrap(),
+            Fees::Eip1559 {
+                max_fee_per_gas: gwei(4),
+                max_priority_fee_per_gas: gwei(4),
+            }
+        );
+        // fixed fees do not request anything
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn fill_only_missing_fees() {
+        let mock = MockTransport::new();
+        let mut connection = Connection::new(mock.clone());
+        let estimator = FixedFees(Fees::Legacy { gas_price: gwei(7) });
+
+        let mut transaction = TransactionRequest {
+            from: Address::zero(),
+            ..Default::default()
+        };
+        estimator.fill(&mut connection, &mut transaction).unwrap();
+        assert_eq!(transaction.gas_price, Some(gwei(7)));
+
+        let mut transaction = TransactionRequest {
+            max_fee_per_gas: Some(gwei(1)),
+            ..Default::default()
+        };
+        estimator.fill(&mut connection, &mut transaction).unwrap();
+        assert_eq!(transaction.gas_price, None);
+        assert_eq!(transaction.max_fee_per_gas, Some(gwei(1)));
+        assert!(mock.requests().is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+//! Helpers to send transactions and follow them until they are confirmed
+
+#[cfg(feature = "blocking")]
+mod fees;
+#[cfg(feature = "blocking")]
+mod nonce;
+#[cfg(feature = "blocking")]
+mod pending;
+#[cfg(feature = "blocking")]
+mod replacement;
+
+#[cfg(feature = "blocking")]
+pub use fees::{
+    CappedFees, FeeEstimator, FeeHistoryEstimator, Fees, FixedFees, GasPriceEstimator,
+    DEFAULT_PRIORITY_FEE,
+};
+#[cfg(feature = "blocking")]
+pub use nonce::NonceManager;
+#[cfg(feature = "blocking")]
+pub use pending::{
+    PendingTransaction, PendingTransactionError, TransactionStatus, DEFAULT_GRACE_PERIOD,
+};
+#[cfg(feature = "blocking")]
+pub use replacement::{
+    bumped_fees, cancel, speed_up, Replacement, ReplacementOutcome, MIN_FEE_BUMP_PERCENT,
+};
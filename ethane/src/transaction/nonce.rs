@@ -0,0 +1,205 @@
+use super::PendingTransaction;
+use crate::rpc;
+use crate::types::{Address, BlockParameter, TransactionRequest, U256};
+use crate::{Connection, ConnectionError, Request};
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Hands out sequential nonces for sending many transactions without waiting for each other
+///
+/// The nonce of an account is seeded from `eth_getTransactionCount` at the pending block on first
+/// use and counted up locally afterwards. The manager can be shared between threads (e.g. in an
+/// `Arc`), each using its own [Connection]. Every account is locked on its own, so seeding the
+/// nonce of one account does not block the others.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    /// The next nonce of every account, `None` if it has to be seeded from the node
+    nonces: Mutex<HashMap<Address, Arc<Mutex<Option<u64>>>>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next nonce of the given account, seeding it from the node if needed.
+    pub fn next<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        address: Address,
+    ) -> Result<U256, ConnectionError> {
+        let account = self.account(address);
+        let mut next = lock(&account);
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => pending_nonce(connection, address)?,
+        };
+        *next = Some(nonce + 1);
+        Ok(U256::from_int_unchecked(nonce))
+    }
+
+    /// Forgets the local nonce of the account, so it is seeded from the node again on next use.
+    pub fn reset(&self, address: Address) {
+        *lock(&self.account(address)) = None;
+    }
+
+    /// Seeds the local nonce of the account from the node right away.
+    pub fn resync<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        address: Address,
+    ) -> Result<(), ConnectionError> {
+        let account = self.account(address);
+        let mut next = lock(&account);
+        *next = Some(pending_nonce(connection, address)?);
+        Ok(())
+    }
+
+    /// Sends a transaction with the next nonce of its sender, unless it already has one.
+    ///
+    /// If the node rejects the nonce as too low, e.g. because transactions were sent from the
+    /// same account outside of this manager, the nonce is resynced and the transaction is sent
+    /// once more. On any other error, the account is [reset](Self::reset), as the handed out
+    /// nonce would leave a gap otherwise.
+    pub fn send_transaction<T: Request>(
+        &self,
+        connection: &mut Connection<T>,
+        mut transaction: TransactionRequest,
+    ) -> Result<PendingTransaction, ConnectionError> {
+        if transaction.nonce.is_some() {
+            return connection.send_transaction(transaction);
+        }
+
+        let from = transaction.from;
+        transaction.nonce = Some(self.next(connection, from)?);
+        match connection.send_transaction(transaction.clone()) {
+            Err(ConnectionError::JsonRpc(err)) if is_nonce_too_low(&err.message) => {
+                self.resync(connection, from)?;
+                transaction.nonce = Some(self.next(connection, from)?);
+                let pending = connection.send_transaction(transaction);
+                if pending.is_err() {
+                    self.reset(from);
+                }
+                pending
+            }
+            Err(err) => {
+                self.reset(from);
+                Err(err)
+            }
+            pending => pending,
+        }
+    }
+
+    /// The nonce of the account, the map is only locked to look it up.
+    fn account(&self, address: Address) -> Arc<Mutex<Option<u64>>> {
+        lock(&self.nonces).entry(address).or_default().clone()
+    }
+}
+
+/// A thread which panicked while holding the lock leaves a valid nonce behind, or `None`.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn pending_nonce<T: Request>(
+    connection: &mut Connection<T>,
+    address: Address,
+) -> Result<u64, ConnectionError> {
+    let nonce = connection.call(rpc::eth_get_transaction_count(
+        address,
+        Some(BlockParameter::Pending),
+    ))?;
+    let (high, low) = nonce.as_bytes().split_at(24);
+    if high.iter().any(|b| *b != 0) {
+        return Err(ConnectionError::Serde(format!(
+            "Nonce {} does not fit into 64 bits",
+            nonce
+        )));
+    }
+    // unwrap is fine, the slice is exactly 8 bytes long
+    Ok(u64::from_be_bytes(low.try_into().unwrap()))
+}
+
+// geth responds with "nonce too low", others e.g. with "Nonce too low"
+fn is_nonce_too_low(message: &str) -> bool {
+    message.to_lowercase().contains("nonce too low")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::H256;
+    use crate::MockTransport;
+
+    fn nonce_of(mock: &MockTransport, address: Address, nonce: &str) {
+        mock.push_result_for(
+            "eth_getTransactionCount",
+            (address, BlockParameter::Pending),
+            nonce,
+        );
+    }
+
+    #[test]
+    fn nonce_manager_sequential_nonces() {
+        let manager = NonceManager::new();
+        let first = Address::from_int_unchecked(1_u8);
+        let second = Address::from_int_unchecked(2_u8);
+        let mock = MockTransport::new();
+        nonce_of(&mock, first, "0x7");
+        nonce_of(&mock, second, "0x2");
+        let mut connection = Connection::new(mock.clone());
+
+        assert_eq!(
+            manager.next(&mut connection, first).unwrap(),
+            U256::from_int_unchecked(7_u8)
+        );
+        assert_eq!(
+            manager.next(&mut connection, second).unwrap(),
+            U256::from_int_unchecked(2_u8)
+        );
+        assert_eq!(
+            manager.next(&mut connection, first).unwrap(),
+            U256::from_int_unchecked(8_u8)
+        );
+        assert!(mock.is_done());
+
+        manager.reset(first);
+        nonce_of(&mock, first, "0xa");
+        assert_eq!(
+            manager.next(&mut connection, first).unwrap(),
+            U256::from_int_unchecked(10_u8)
+        );
+        assert_eq!(mock.requests_of("eth_getTransactionCount").len(), 3);
+    }
+
+    #[test]
+    fn nonce_manager_resync_on_nonce_too_low() {
+        let manager = NonceManager::new();
+        let from = Address::from_int_unchecked(1_u8);
+        let mock = MockTransport::new();
+        nonce_of(&mock, from, "0x1");
+        mock.push_error("eth_sendTransaction", -32000, "nonce too low");
+        nonce_of(&mock, from, "0x3");
+        mock.push_result("eth_sendTransaction", H256::from_int_unchecked(1_u8));
+        let mut connection = Connection::new(mock.clone());
+        let transaction = TransactionRequest {
+            from,
+            ..Default::default()
+        };
+
+        let pending = manager.send_transaction(&mut connection, transaction);
+        assert_eq!(pending.unwrap().hash(), H256::from_int_unchecked(1_u8));
+        let sent = mock.requests_of("eth_sendTransaction");
+        assert_eq!(sent[0].params[0]["nonce"], "0x1");
+        assert_eq!(sent[1].params[0]["nonce"], "0x3");
+        assert_eq!(
+            manager.next(&mut connection, from).unwrap(),
+            U256::from_int_unchecked(4_u8)
+        );
+        assert!(mock.is_done());
+    }
+}
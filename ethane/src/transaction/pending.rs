@@ -0,0 +1,404 @@
+use crate::rpc::{self, Rpc};
+use crate::types::{Address, BlockParameter, Transaction, TransactionReceipt, H256, U256, U64};
+use crate::{Connection, ConnectionError, Request, Subscribe};
+
+use std::time::{Duration, Instant};
+
+/// How long a transaction which the node has never returned is considered pending by default
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Wraps the different errors that may occur while waiting for a transaction.
+#[derive(Debug)]
+pub enum PendingTransactionError {
+    /// The node no longer knows the transaction and its nonce has not been used
+    Dropped(H256),
+    /// Another transaction with the same nonce was mined instead
+    Replaced(H256),
+    /// The transaction was not confirmed within the timeout
+    Timeout(H256),
+    /// The node could not be queried
+    Connection(ConnectionError),
+}
+
+impl From<ConnectionError> for PendingTransactionError {
+    fn from(err: ConnectionError) -> Self {
+        Self::Connection(err)
+    }
+}
+
+/// Status of a pending transaction, returned by [poll](PendingTransaction::poll).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionStatus {
+    /// The transaction is not included in a block yet
+    Pending,
+    /// The transaction is included in a block but has less than the required confirmations
+    Included {
+        receipt: TransactionReceipt,
+        confirmations: u64,
+    },
+    /// The receipt seen by a previous poll was removed by a chain reorganization
+    Reorged,
+    /// The transaction has at least the required confirmations
+    Confirmed(TransactionReceipt),
+}
+
+/// A handle to a sent transaction
+///
+/// Returned by [send_transaction](crate::Connection::send_transaction) and
+/// [send_raw_transaction](crate::Connection::send_raw_transaction), but can also be created
+/// from any transaction hash. Call [wait](Self::wait) to block until the transaction has the
+/// required number of confirmations.
+#[derive(Clone, Debug)]
+pub struct PendingTransaction {
+    hash: H256,
+    confirmations: u64,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    grace_period: Duration,
+    first_poll: Option<Instant>,
+    sender: Option<(Address, U256)>,
+    receipt: Option<TransactionReceipt>,
+}
+
+impl PendingTransaction {
+    /// Creates a handle which waits for 1 confirmation, polling every second without a timeout.
+    pub fn new(hash: H256) -> Self {
+        Self {
+            hash,
+            confirmations: 1,
+            poll_interval: Duration::from_secs(1),
+            timeout: None,
+            grace_period: DEFAULT_GRACE_PERIOD,
+            first_poll: None,
+            sender: None,
+            receipt: None,
+        }
+    }
+
+    /// Sets the number of confirmations to wait for, where the including block counts as the first.
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long a transaction which the node has never returned is still considered pending,
+    /// defaults to [DEFAULT_GRACE_PERIOD].
+    ///
+    /// A node, or another node behind the same load balancer, may not know a transaction right
+    /// after it was broadcast.
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Queries the current status of the transaction once.
+    ///
+    /// If the transaction is unknown to the node and has no receipt, it was either dropped
+    /// from the pool or replaced by another transaction with the same nonce, which is returned
+    /// as an error. A transaction which the node has never returned is only reported as dropped
+    /// after the [grace period](Self::grace_period).
+    pub fn poll<T: Request>(
+        &mut self,
+        connection: &mut Connection<T>,
+    ) -> Result<TransactionStatus, PendingTransactionError> {
+        let first_poll = *self.first_poll.get_or_insert_with(Instant::now);
+        if let Some(receipt) = connection.call(rpc::eth_get_transaction_receipt(self.hash))? {
+            let head = connection.call(rpc::eth_block_number())?;
+            let confirmations = (to_u64(head) + 1).saturating_sub(to_u64(receipt.block_number));
+            self.receipt = Some(receipt.clone());
+            if confirmations >= self.confirmations {
+                return Ok(TransactionStatus::Confirmed(receipt));
+            }
+            return Ok(TransactionStatus::Included {
+                receipt,
+                confirmations,
+            });
+        }
+
+        let transaction = connection.call(eth_get_transaction_by_hash(self.hash))?;
+        let reorged = self.receipt.take().is_some();
+        match transaction {
+            Some(transaction) => {
+                if let Some(from) = transaction.from {
+                    self.sender = Some((from, transaction.nonce));
+                }
+                if reorged {
+                    Ok(TransactionStatus::Reorged)
+                } else {
+                    Ok(TransactionStatus::Pending)
+                }
+            }
+            None if self.sender.is_none() && first_poll.elapsed() < self.grace_period => {
+                Ok(TransactionStatus::Pending)
+            }
+            None => {
+                if let Some((from, nonce)) = self.sender {
+                    let mined_nonces = connection.call(rpc::eth_get_transaction_count(
+                        from,
+                        Some(BlockParameter::Latest),
+                    ))?;
+                    // both are big endian with the same length, so bytes compare as numbers
+                    if mined_nonces.into_bytes() > nonce.into_bytes() {
+                        return Err(PendingTransactionError::Replaced(self.hash));
+                    }
+                }
+                Err(PendingTransactionError::Dropped(self.hash))
+            }
+        }
+    }
+
+    /// Polls the node until the transaction is confirmed or the timeout is reached.
+    pub fn wait<T: Request>(
+        &mut self,
+        connection: &mut Connection<T>,
+    ) -> Result<TransactionReceipt, PendingTransactionError> {
+        let start = Instant::now();
+        loop {
+            if let TransactionStatus::Confirmed(receipt) = self.poll(connection)? {
+                return Ok(receipt);
+            }
+            let remaining = self.remaining(start)?;
+            std::thread::sleep(remaining.map_or(self.poll_interval, |remaining| {
+                remaining.min(self.poll_interval)
+            }));
+        }
+    }
+
+    /// Waits for the transaction like [wait](Self::wait), but checks the status on every new
+    /// block received from a `newHeads` subscription instead of polling.
+    ///
+    /// The timeout is only checked when a new block arrives.
+    pub fn wait_for_new_heads<T: Request + Subscribe>(
+        &mut self,
+        connection: &mut Connection<T>,
+    ) -> Result<TransactionReceipt, PendingTransactionError> {
+        let start = Instant::now();
+        let mut new_heads = connection.subscribe(rpc::eth_subscribe_new_heads())?;
+        loop {
+            if let TransactionStatus::Confirmed(receipt) = self.poll(connection)? {
+                return Ok(receipt);
+            }
+            self.remaining(start)?;
+            new_heads.next_item()?;
+        }
+    }
+
+    fn remaining(&self, start: Instant) -> Result<Option<Duration>, PendingTransactionError> {
+        match self.timeout {
+            Some(timeout) => match timeout.checked_sub(start.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+                _ => Err(PendingTransactionError::Timeout(self.hash)),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+/// Same as [eth_get_transaction_by_hash](rpc::eth_get_transaction_by_hash), but the node
+/// returns `null` for unknown transactions.
+fn eth_get_transaction_by_hash(transaction_hash: H256) -> Rpc<Option<Transaction>> {
+    let mut rpc = Rpc::new("eth_getTransactionByHash");
+    rpc.add_param(transaction_hash);
+    rpc
+}
+
+fn to_u64(value: U64) -> u64 {
+    u64::from_be_bytes(value.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::{json, Value};
+
+    const HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn receipt(block_number: &str) -> Value {
+        json!({
+            "transactionHash": "0x01", "transactionIndex": "0x0", "blockHash": "0x02",
+            "blockNumber": block_number, "from": "0x03", "to": "0x04",
+            "cumulativeGasUsed": "0x5208", "gasUsed": "0x5208", "contractAddress": null,
+            "logs": [], "logsBloom": format!("0x{}", "0".repeat(512)), "status": "0x1",
+        })
+    }
+
+    fn transaction() -> Value {
+        json!({
+            "hash": "0x01", "from": "0x03", "to": "0x04", "gas": "0x5208", "gasPrice": "0x1",
+            "input": "0x", "nonce": "0x5", "value": "0x0",
+        })
+    }
+
+    fn new_pending() -> PendingTransaction {
+        PendingTransaction::new(H256::from_int_unchecked(1_u8))
+    }
+
+    #[test]
+    fn pending_transaction_confirmations() {
+        let mock = MockTransport::new();
+        mock.push_result_for("eth_getTransactionReceipt", [HASH], Value::Null);
+        mock.push_result_for("eth_getTransactionByHash", [HASH], transaction());
+        mock.push_result("eth_getTransactionReceipt", receipt("0xa"));
+        mock.push_result("eth_blockNumber", "0xa");
+        mock.push_result("eth_getTransactionReceipt", receipt("0xa"));
+        mock.push_result("eth_blockNumber", "0xb");
+        let mut connection = Connection::new(mock.clone());
+        let mut pending = new_pending().confirmations(2);
+
+        assert_eq!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Pending
+        );
+        match pending.poll(&mut connection).unwrap() {
+            TransactionStatus::Included { confirmations, .. } => assert_eq!(confirmations, 1),
+            status => panic!("Expected an included transaction, got {:?}", status),
+        }
+        match pending.poll(&mut connection).unwrap() {
+            TransactionStatus::Confirmed(receipt) => {
+                assert_eq!(receipt.block_number, U64::from_int_unchecked(10_u8))
+            }
+            status => panic!("Expected a confirmed transaction, got {:?}", status),
+        }
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn pending_transaction_reorg() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_getTransactionReceipt", receipt("0xa"));
+        mock.push_result("eth_blockNumber", "0xa");
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_result("eth_getTransactionByHash", transaction());
+        let mut connection = Connection::new(mock.clone());
+        let mut pending = new_pending().confirmations(3);
+
+        assert!(matches!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Included { .. }
+        ));
+        assert_eq!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Reorged
+        );
+        assert!(mock.is_done());
+    }
+
+    #[test]
+    fn pending_transaction_connection_errors() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_getTransactionReceipt", receipt("0xa"));
+        mock.push_result("eth_blockNumber", "0xa");
+        mock.push_error("eth_getTransactionReceipt", -32005, "limit exceeded");
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_error("eth_getTransactionByHash", -32005, "limit exceeded");
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_result("eth_getTransactionByHash", transaction());
+        let mut connection = Connection::new(mock.clone());
+        let mut pending = new_pending().confirmations(3);
+
+        assert!(matches!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Included { .. }
+        ));
+        // errors are not mistaken for a transaction which is unknown to the node
+        for _ in 0..2 {
+            assert!(matches!(
+                pending.poll(&mut connection),
+                Err(PendingTransactionError::Connection(
+                    ConnectionError::JsonRpc(_)
+                ))
+            ));
+        }
+        // the receipt seen before the errors is still known
+        assert_eq!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Reorged
+        );
+        assert!(mock.is_done());
+    }
+
+    /// The transaction is seen once and is unknown afterwards
+    fn seen_then_unknown(mined_nonces: &str) -> Connection<MockTransport> {
+        let mock = MockTransport::new();
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_result("eth_getTransactionByHash", transaction());
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_result("eth_getTransactionByHash", Value::Null);
+        mock.push_result_for(
+            "eth_getTransactionCount",
+            (Address::from_int_unchecked(3_u8), BlockParameter::Latest),
+            mined_nonces,
+        );
+        Connection::new(mock)
+    }
+
+    #[test]
+    fn pending_transaction_dropped_or_replaced() {
+        let mut connection = seen_then_unknown("0x5");
+        let mut pending = new_pending();
+        pending.poll(&mut connection).unwrap();
+        assert!(matches!(
+            pending.poll(&mut connection),
+            Err(PendingTransactionError::Dropped(_))
+        ));
+
+        let mut connection = seen_then_unknown("0x6");
+        let mut pending = new_pending();
+        pending.poll(&mut connection).unwrap();
+        assert!(matches!(
+            pending.poll(&mut connection),
+            Err(PendingTransactionError::Replaced(_))
+        ));
+    }
+
+    #[test]
+    fn pending_transaction_not_seen_yet() {
+        let mock = MockTransport::new();
+        for _ in 0..2 {
+            mock.push_result("eth_getTransactionReceipt", Value::Null);
+            mock.push_result("eth_getTransactionByHash", Value::Null);
+        }
+        let mut connection = Connection::new(mock.clone());
+        let mut pending = new_pending();
+        assert_eq!(
+            pending.poll(&mut connection).unwrap(),
+            TransactionStatus::Pending
+        );
+
+        let mut pending = pending.grace_period(Duration::from_millis(0));
+        assert!(matches!(
+            pending.poll(&mut connection),
+            Err(PendingTransactionError::Dropped(_))
+        ));
+        // the sender is unknown, so replacements are not checked
+        assert!(mock.requests_of("eth_getTransactionCount").is_empty());
+    }
+
+    #[test]
+    fn pending_transaction_timeout() {
+        let mock = MockTransport::new();
+        mock.push_result("eth_getTransactionReceipt", Value::Null);
+        mock.push_result("eth_getTransactionByHash", transaction());
+        let mut connection = Connection::new(mock);
+        let mut pending = new_pending().timeout(Duration::from_millis(0));
+        assert!(matches!(
+            pending.wait(&mut connection),
+            Err(PendingTransactionError::Timeout(_))
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+use super::fees::to_u128;
+use super::{Fees, PendingTransaction, PendingTransactionError, TransactionStatus};
+use crate::types::{Transaction, TransactionReceipt, TransactionRequest, U256};
+use crate::{Connection, ConnectionError, Request};
+
+use std::time::{Duration, Instant};
+
+/// Minimum fee increase in percent which nodes accept for replacing a pending transaction
+pub const MIN_FEE_BUMP_PERCENT: u128 = 10;
+
+/// Gas used by a plain value transfer
+const TRANSFER_GAS: u64 = 21_000;
+
+/// Returns the fees of the transaction increased by `percent`, rounded up.
+///
+/// EIP-1559 fees are bumped if the transaction has them, the gas price otherwise.
+pub fn bumped_fees(transaction: &Transaction, percent: u128) -> Fees {
+    let bump = |fee: &U256| {
+        let fee = to_u128(fee);
+        U256::from_int_unchecked(
+            fee.saturating_add((fee.saturating_mul(percent).saturating_add(99)) / 100),
+        )
+    };
+    match (
+        &transaction.max_fee_per_gas,
+        &transaction.max_priority_fee_per_gas,
+    ) {
+        (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) => Fees::Eip1559 {
+            max_fee_per_gas: bump(max_fee_per_gas),
+            max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+        },
+        _ => Fees::Legacy {
+            gas_price: bump(&transaction.gas_price),
+        },
+    }
+}
+
+/// Builds a replacement which sends the same payload with the minimum fee bump.
+///
+/// Returns `None` if the sender of the transaction is unknown.
+pub fn speed_up(transaction: &Transaction) -> Option<TransactionRequest> {
+    let mut replacement = TransactionRequest {
+        from: transaction.from?,
+        to: transaction.to,
+        gas: Some(transaction.gas),
+        value: Some(transaction.value),
+        data: Some(transaction.input.clone()),
+        nonce: Some(transaction.nonce),
+        ..Default::default()
+    };
+    bumped_fees(transaction, MIN_FEE_BUMP_PERCENT).apply(&mut replacement);
+    Some(replacement)
+}
+
+/// Builds a replacement which transfers zero value to the sender with the minimum fee bump.
+///
+/// Returns `None` if the sender of the transaction is unknown.
+pub fn cancel(transaction: &Transaction) -> Option<TransactionRequest> {
+    let from = transaction.from?;
+    let mut replacement = TransactionRequest {
+        from,
+        to: Some(from),
+        gas: Some(U256::from_int_unchecked(TRANSFER_GAS)),
+        value: Some(U256::zero()),
+        nonce: Some(transaction.nonce),
+        ..Default::default()
+    };
+    bumped_fees(transaction, MIN_FEE_BUMP_PERCENT).apply(&mut replacement);
+    Some(replacement)
+}
+
+/// The transaction which was mined out of an original and its replacement
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplacementOutcome {
+    Original(TransactionReceipt),
+    Replacement(TransactionReceipt),
+}
+
+/// Tracks an original transaction and its replacement until one of them is confirmed
+pub struct Replacement {
+    pub original: PendingTransaction,
+    pub replacement: PendingTransaction,
+}
+
+impl Replacement {
+    /// Sends the replacement and starts tracking it along with the original transaction.
+    pub fn send<T: Request>(
+        connection: &mut Connection<T>,
+        original: &Transaction,
+        replacement: TransactionRequest,
+    ) -> Result<Self, ConnectionError> {
+        Ok(Self {
+            original: PendingTransaction::new(original.hash),
+            replacement: connection.send_transaction(replacement)?,
+        })
+    }
+
+    /// Polls both transactions until one of them is confirmed.
+    ///
+    /// Once one of them is mined, the other one is reported as dropped or replaced by the node,
+    /// which is not treated as an error. The number of confirmations is the one configured on
+    /// the respective [PendingTransaction].
+    pub fn wait<T: Request>(
+        &mut self,
+        connection: &mut Connection<T>,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+    ) -> Result<ReplacementOutcome, PendingTransactionError> {
+        let start = Instant::now();
+        loop {
+            let replacement = self.replacement.poll(connection);
+            if let Ok(TransactionStatus::Confirmed(receipt)) = replacement {
+                return Ok(ReplacementOutcome::Replacement(receipt));
+            }
+            let original = self.original.poll(connection);
+            if let Ok(TransactionStatus::Confirmed(receipt)) = original {
+                return Ok(ReplacementOutcome::Original(receipt));
+            }
+            match (replacement, original) {
+                (Err(PendingTransactionError::Connection(err)), _)
+                | (_, Err(PendingTransactionError::Connection(err))) => {
+                    return Err(PendingTransactionError::Connection(err))
+                }
+                // neither of them is known anymore, so a third transaction took the nonce
+                (Err(err), Err(_)) => return Err(err),
+                _ => (),
+            }
+            if let Some(timeout) = timeout {
+                if start.elapsed() >= timeout {
+                    return Err(PendingTransactionError::Timeout(self.replacement.hash()));
+                }
+            }
+            std::thread::sleep(poll_interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, Bytes};
+
+    fn transaction(eip1559: bool) -> Transaction {
+        let mut transaction: Transaction = serde_json::from_str(
+            r#"{"hash":"0x01","from":"0x03","to":"0x04","gas":"0x186a0","gasPrice":"0x3b9aca01",
+            "input":"0xabcd","nonce":"0x5","value":"0x64"}"#,
+        )
+        .unwrap();
+        if eip1559 {
+            transaction.max_fee_per_gas = Some(U256::from_int_unchecked(30_u8));
+            transaction.max_priority_fee_per_gas = Some(U256::from_int_unchecked(2_u8));
+        }
+        transaction
+    }
+
+    #[test]
+    fn speed_up_transaction() {
+        let replacement = speed_up(&transaction(false)).unwrap();
+        assert_eq!(replacement.from, Address::from_int_unchecked(3_u8));
+        assert_eq!(replacement.to, Some(Address::from_int_unchecked(4_u8)));
+        assert_eq!(replacement.data, Some(Bytes::from_slice(&[0xab, 0xcd])));
+        assert_eq!(replacement.nonce, Some(U256::from_int_unchecked(5_u8)));
+        // 1000000001 * 1.1 rounded up
+        assert_eq!(
+            replacement.gas_price,
+            Some(U256::from_int_unchecked(1_100_000_002_u64))
+        );
+
+        let replacement = speed_up(&transaction(true)).unwrap();
+        assert_eq!(replacement.gas_price, None);
+        assert_eq!(
+            replacement.max_fee_per_gas,
+            Some(U256::from_int_unchecked(33_u8))
+        );
+        assert_eq!(
+            replacement.max_priority_fee_per_gas,
+            Some(U256::from_int_unchecked(3_u8))
+        );
+    }
+
+    #[test]
+    fn cancel_transaction() {
+        let replacement = cancel(&transaction(true)).unwrap();
+        assert_eq!(replacement.to, Some(Address::from_int_unchecked(3_u8)));
+        assert_eq!(replacement.value, Some(U256::zero()));
+        assert_eq!(replacement.data, None);
+        assert_eq!(replacement.gas, Some(U256::from_int_unchecked(21_000_u64)));
+        assert_eq!(replacement.nonce, Some(U256::from_int_unchecked(5_u8)));
+
+        let mut unknown_sender = transaction(false);
+        unknown_sender.from = None;
+        assert!(cancel(&unknown_sender).is_none());
+    }
+}
@@ -3,16 +3,32 @@
 //! This module provides custom types, but also re-exports some types from [ethereum_types].
 
 pub use ethane_types::*;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::de;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
 /// Information about block number, defaults to `BlockParameter::Latest`
-#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BlockParameter {
     Latest,
     Earliest,
     Pending,
+    /// The latest block which is safe from reorgs under honest majority
+    Safe,
+    /// The latest block which is finalized by the consensus layer
+    Finalized,
     Custom(U64),
+    /// A block given by its hash as specified in [EIP-1898](https://eips.ethereum.org/EIPS/eip-1898)
+    ///
+    /// Only accepted by methods which read the state, e.g. `eth_call` or `eth_getBalance`, a
+    /// [Filter] or [TraceFilter] bounded by a block hash fails to serialize. If
+    /// `require_canonical` is set, the node fails if the block is not part of the canonical chain.
+    Hash {
+        block_hash: H256,
+        require_canonical: bool,
+    },
 }
 
 impl Serialize for BlockParameter {
@@ -21,7 +37,64 @@ impl Serialize for BlockParameter {
             BlockParameter::Latest => serializer.serialize_str("latest"),
             BlockParameter::Earliest => serializer.serialize_str("earliest"),
             BlockParameter::Pending => serializer.serialize_str("pending"),
+            BlockParameter::Safe => serializer.serialize_str("safe"),
+            BlockParameter::Finalized => serializer.serialize_str("finalized"),
             BlockParameter::Custom(num) => serializer.serialize_str(&num.to_string()), // TODO non-prefixed string?
+            BlockParameter::Hash {
+                block_hash,
+                require_canonical,
+            } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("blockHash", &block_hash)?;
+                map.serialize_entry("requireCanonical", &require_canonical)?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// The different shapes of a serialized [BlockParameter]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BlockParameterRepr {
+    Tag(String),
+    Hash {
+        #[serde(rename = "blockHash")]
+        block_hash: H256,
+        #[serde(rename = "requireCanonical", default)]
+        require_canonical: bool,
+    },
+    Number {
+        #[serde(rename = "blockNumber")]
+        block_number: U64,
+    },
+}
+
+impl<'de> Deserialize<'de> for BlockParameter {
+    fn deserialize<T: Deserializer<'de>>(deserializer: T) -> Result<Self, T::Error> {
+        match BlockParameterRepr::deserialize(deserializer)? {
+            BlockParameterRepr::Tag(tag) => match tag.as_str() {
+                "latest" => Ok(BlockParameter::Latest),
+                "earliest" => Ok(BlockParameter::Earliest),
+                "pending" => Ok(BlockParameter::Pending),
+                "safe" => Ok(BlockParameter::Safe),
+                "finalized" => Ok(BlockParameter::Finalized),
+                number if number.starts_with("0x") => U64::try_from(number)
+                    .map(BlockParameter::Custom)
+                    .map_err(|_| de::Error::custom(format!("Invalid block number {}", number))),
+                _ => Err(de::Error::custom(format!(
+                    "Invalid block parameter {}",
+                    tag
+                ))),
+            },
+            BlockParameterRepr::Hash {
+                block_hash,
+                require_canonical,
+            } => Ok(BlockParameter::Hash {
+                block_hash,
+                require_canonical,
+            }),
+            BlockParameterRepr::Number { block_number } => Ok(BlockParameter::Custom(block_number)),
         }
     }
 }
@@ -32,6 +105,19 @@ impl Default for BlockParameter {
     }
 }
 
+/// Serializes the bound of a block range, which must not be a [BlockParameter::Hash]
+fn serialize_block_bound<T: Serializer>(
+    block: &Option<BlockParameter>,
+    serializer: T,
+) -> Result<T::Ok, T::Error> {
+    match block {
+        Some(BlockParameter::Hash { .. }) => Err(serde::ser::Error::custom(
+            "A block range cannot be bounded by a block hash",
+        )),
+        block => block.serialize(serializer),
+    }
+}
+
 /// Used for creating transactions
 #[derive(Clone, Debug, PartialEq, Serialize, Default)]
 pub struct TransactionRequest {
@@ -43,6 +129,12 @@ pub struct TransactionRequest {
     #[serde(rename = "gasPrice")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
+    #[serde(rename = "maxFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(rename = "maxPriorityFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -62,6 +154,10 @@ pub struct Transaction {
     pub gas: U256,
     #[serde(rename = "gasPrice")]
     pub gas_price: U256,
+    #[serde(rename = "maxFeePerGas")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(rename = "maxPriorityFeePerGas")]
+    pub max_priority_fee_per_gas: Option<U256>,
     pub hash: H256,
     pub input: Bytes,
     pub nonce: U256,
@@ -221,6 +317,12 @@ pub struct Call {
     #[serde(rename = "gasPrice")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
+    #[serde(rename = "maxFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(rename = "maxPriorityFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -239,6 +341,12 @@ pub struct GasCall {
     #[serde(rename = "gasPrice")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
+    #[serde(rename = "maxFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(rename = "maxPriorityFeePerGas")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<U256>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -250,9 +358,11 @@ pub struct GasCall {
 pub struct Filter {
     #[serde(rename = "fromBlock")]
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_block_bound")]
     pub from_block: Option<BlockParameter>,
     #[serde(rename = "toBlock")]
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_block_bound")]
     pub to_block: Option<BlockParameter>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<ValueOrVec<Address>>,
@@ -337,6 +447,331 @@ pub struct SyncStatus {
     pub known_states: Option<u64>,
 }
 
+/// Base fees and priority fees of a range of blocks, returned by `eth_feeHistory`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct FeeHistory {
+    #[serde(rename = "oldestBlock")]
+    pub oldest_block: U64,
+    /// Contains one more element than the number of blocks, the base fee of the next block
+    #[serde(rename = "baseFeePerGas")]
+    pub base_fee_per_gas: Vec<U256>,
+    #[serde(rename = "gasUsedRatio")]
+    pub gas_used_ratio: Vec<f64>,
+    /// The priority fees at the requested percentiles for each block
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Merkle proof of an account and some of its storage slots, returned by `eth_getProof`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AccountProof {
+    pub address: Address,
+    #[serde(rename = "accountProof")]
+    pub account_proof: Vec<Bytes>,
+    pub balance: U256,
+    #[serde(rename = "codeHash")]
+    pub code_hash: H256,
+    pub nonce: U64,
+    #[serde(rename = "storageHash")]
+    pub storage_hash: H256,
+    #[serde(rename = "storageProof")]
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// Merkle proof of a single storage slot
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StorageProof {
+    pub key: H256,
+    pub value: U256,
+    pub proof: Vec<Bytes>,
+}
+
+/// An address and the storage keys a transaction accesses on it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccessListItem {
+    pub address: Address,
+    #[serde(rename = "storageKeys")]
+    pub storage_keys: Vec<H256>,
+}
+
+/// Result of `eth_createAccessList`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AccessListResult {
+    #[serde(rename = "accessList")]
+    pub access_list: Vec<AccessListItem>,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// Set if the call would fail
+    pub error: Option<String>,
+}
+
+/// Structured data to be signed according to [EIP-712](https://eips.ethereum.org/EIPS/eip-712)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TypedData {
+    /// The struct types used by the message, including `EIP712Domain`
+    pub types: HashMap<String, Vec<TypedDataField>>,
+    #[serde(rename = "primaryType")]
+    pub primary_type: String,
+    pub domain: TypedDataDomain,
+    pub message: serde_json::Value,
+}
+
+/// A member of a struct type in [TypedData]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// The domain of [TypedData], unset fields are left out
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TypedDataDomain {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(rename = "chainId", skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U64>,
+    #[serde(rename = "verifyingContract", skip_serializing_if = "Option::is_none")]
+    pub verifying_contract: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt: Option<H256>,
+}
+
+/// Result of the [StructLogger](crate::rpc::StructLogger) tracer
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StructLoggerTrace {
+    pub gas: u64,
+    pub failed: bool,
+    /// Hex encoded return data without `0x` prefix
+    #[serde(rename = "returnValue")]
+    pub return_value: String,
+    #[serde(rename = "structLogs")]
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A single executed opcode
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    #[serde(rename = "gasCost")]
+    pub gas_cost: u64,
+    pub depth: u64,
+    pub error: Option<String>,
+    pub stack: Option<Vec<U256>>,
+    /// Memory in 32 byte words, hex encoded without `0x` prefix
+    pub memory: Option<Vec<String>>,
+    /// Storage slots accessed so far, hex encoded without `0x` prefix
+    pub storage: Option<HashMap<String, String>>,
+    pub refund: Option<u64>,
+}
+
+/// A call frame returned by the [CallTracer](crate::rpc::CallTracer)
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CallFrame {
+    /// The kind of call, e.g. `CALL`, `DELEGATECALL` or `CREATE`
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: Option<U256>,
+    pub gas: U256,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    pub input: Bytes,
+    pub output: Option<Bytes>,
+    pub error: Option<String>,
+    #[serde(rename = "revertReason")]
+    pub revert_reason: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+    #[serde(default)]
+    pub logs: Vec<CallLog>,
+}
+
+/// A log emitted within a [CallFrame]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CallLog {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+/// An account as seen by the [PrestateTracer](crate::rpc::PrestateTracer)
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PrestateAccount {
+    pub balance: Option<U256>,
+    pub nonce: Option<u64>,
+    pub code: Option<Bytes>,
+    pub storage: Option<HashMap<H256, H256>>,
+}
+
+/// The trace of a single transaction returned by `debug_traceBlockByNumber`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct BlockTrace<T> {
+    #[serde(rename = "txHash")]
+    pub tx_hash: Option<H256>,
+    pub result: Option<T>,
+    pub error: Option<String>,
+}
+
+/// A Parity style trace returned by `trace_block` and `trace_filter`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct LocalizedTrace {
+    pub action: TraceAction,
+    pub result: Option<TraceResult>,
+    pub error: Option<String>,
+    #[serde(rename = "blockHash")]
+    pub block_hash: H256,
+    #[serde(rename = "blockNumber")]
+    pub block_number: u64,
+    #[serde(rename = "transactionHash")]
+    pub transaction_hash: Option<H256>,
+    #[serde(rename = "transactionPosition")]
+    pub transaction_position: Option<u64>,
+    pub subtraces: u64,
+    /// Position of the trace in the call tree of the transaction
+    #[serde(rename = "traceAddress")]
+    pub trace_address: Vec<u64>,
+    #[serde(rename = "type")]
+    pub trace_type: String,
+}
+
+/// The action of a [LocalizedTrace]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TraceAction {
+    Call(CallAction),
+    Create(CreateAction),
+    Suicide(SuicideAction),
+    Reward(RewardAction),
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CallAction {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub input: Bytes,
+    #[serde(rename = "callType")]
+    pub call_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct CreateAction {
+    pub from: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub init: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SuicideAction {
+    pub address: Address,
+    #[serde(rename = "refundAddress")]
+    pub refund_address: Address,
+    pub balance: U256,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct RewardAction {
+    pub author: Address,
+    pub value: U256,
+    #[serde(rename = "rewardType")]
+    pub reward_type: String,
+}
+
+/// The result of a [LocalizedTrace]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+    Create {
+        #[serde(rename = "gasUsed")]
+        gas_used: U256,
+        code: Bytes,
+        address: Address,
+    },
+    Call {
+        #[serde(rename = "gasUsed")]
+        gas_used: U256,
+        output: Bytes,
+    },
+}
+
+/// Used for filtering traces with `trace_filter`
+#[derive(Clone, Debug, PartialEq, Serialize, Default)]
+pub struct TraceFilter {
+    #[serde(rename = "fromBlock")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_block_bound")]
+    pub from_block: Option<BlockParameter>,
+    #[serde(rename = "toBlock")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_block_bound")]
+    pub to_block: Option<BlockParameter>,
+    #[serde(rename = "fromAddress")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<Vec<Address>>,
+    #[serde(rename = "toAddress")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_address: Option<Vec<Address>>,
+    /// Number of traces to skip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<u64>,
+    /// Maximum number of traces to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+}
+
+/// Information about the running node, returned by `admin_nodeInfo`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct NodeInfo {
+    pub id: String,
+    pub name: String,
+    pub enode: String,
+    pub enr: Option<String>,
+    pub ip: String,
+    pub ports: NodePorts,
+    #[serde(rename = "listenAddr")]
+    pub listen_addr: String,
+    /// Protocol specific information, e.g. the chain config for `eth`
+    pub protocols: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct NodePorts {
+    pub discovery: u16,
+    pub listener: u16,
+}
+
+/// A connected peer, returned by `admin_peers`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PeerInfo {
+    pub id: String,
+    pub name: String,
+    pub enode: String,
+    pub enr: Option<String>,
+    /// Supported capabilities, e.g. `eth/66`
+    pub caps: Vec<String>,
+    pub network: PeerNetworkInfo,
+    pub protocols: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct PeerNetworkInfo {
+    #[serde(rename = "localAddress")]
+    pub local_address: String,
+    #[serde(rename = "remoteAddress")]
+    pub remote_address: String,
+    pub inbound: bool,
+    pub trusted: bool,
+    #[serde(rename = "static")]
+    pub is_static: bool,
+}
+
 /// A wrapper for a signed transaction
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct SignedTransaction {
@@ -364,6 +799,405 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_types_block_parameter_round_trip() {
+        let block_hash = H256::from_int_unchecked(1_u8);
+        let block_params = [
+            BlockParameter::Latest,
+            BlockParameter::Earliest,
+            BlockParameter::Pending,
+            BlockParameter::Safe,
+            BlockParameter::Finalized,
+            BlockParameter::Custom(U64::from_int_unchecked(11827902_u64)),
+            BlockParameter::Hash {
+                block_hash,
+                require_canonical: true,
+            },
+            BlockParameter::Hash {
+                block_hash,
+                require_canonical: false,
+            },
+        ];
+        for block_param in block_params.iter() {
+            let serialized = serde_json::to_string(block_param).unwrap();
+            assert_eq!(
+                serde_json::from_str::<BlockParameter>(&serialized).unwrap(),
+                *block_param
+            );
+        }
+
+        assert_eq!(
+            serde_json::to_string(&block_params[6]).unwrap(),
+            format!(
+                r#"{{"blockHash":"{}","requireCanonical":true}}"#,
+                block_hash
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<BlockParameter>(&format!(r#"{{"blockHash":"{}"}}"#, block_hash))
+                .unwrap(),
+            BlockParameter::Hash {
+                block_hash,
+                require_canonical: false,
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<BlockParameter>(r#"{"blockNumber":"0xa"}"#).unwrap(),
+            BlockParameter::Custom(U64::from_int_unchecked(10_u8))
+        );
+        assert!(serde_json::from_str::<BlockParameter>(r#""newest""#).is_err());
+    }
+
+    #[test]
+    fn test_types_block_range_rejects_hash() {
+        let block_hash = BlockParameter::Hash {
+            block_hash: H256::from_int_unchecked(1_u8),
+            require_canonical: false,
+        };
+        let filter = Filter {
+            from_block: Some(BlockParameter::Safe),
+            to_block: Some(BlockParameter::Finalized),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&filter).unwrap(),
+            r#"{"fromBlock":"safe","toBlock":"finalized"}"#
+        );
+
+        let filter = Filter {
+            to_block: Some(block_hash),
+            ..filter
+        };
+        assert!(serde_json::to_value(&filter).is_err());
+        let trace_filter = TraceFilter {
+            from_block: Some(block_hash),
+            ..Default::default()
+        };
+        assert!(serde_json::to_value(&trace_filter).is_err());
+    }
+
+    #[test]
+    fn test_types_account_proof() {
+        let proof: AccountProof = serde_json::from_str(
+            r#"{
+                "address": "0x7f0d15c7faae65896648c8273b6d7e43f58fa842",
+                "accountProof": ["0xf90211a0", "0xf90211a1"],
+                "balance": "0x0",
+                "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                "nonce": "0x0",
+                "storageHash": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "storageProof": [{
+                    "key": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "value": "0x0",
+                    "proof": []
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(proof.account_proof.len(), 2);
+        assert_eq!(proof.storage_proof[0].value, U256::zero());
+        assert!(proof.storage_proof[0].proof.is_empty());
+    }
+
+    #[test]
+    fn test_types_typed_data() {
+        let mut types = HashMap::new();
+        types.insert(
+            String::from("Mail"),
+            vec![TypedDataField {
+                name: String::from("contents"),
+                field_type: String::from("string"),
+            }],
+        );
+        let typed_data = TypedData {
+            types,
+            primary_type: String::from("Mail"),
+            domain: TypedDataDomain {
+                chain_id: Some(U64::from_int_unchecked(1_u8)),
+                ..Default::default()
+            },
+            message: serde_json::json!({ "contents": "checkmate" }),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&typed_data).unwrap(),
+            r#"{"types":{"Mail":[{"name":"contents","type":"string"}]},"primaryType":"Mail","domain":{"chainId":"0x1"},"message":{"contents":"checkmate"}}"#
+        );
+    }
+
+    #[test]
+    fn test_types_call_frame() {
+        let frame: CallFrame = serde_json::from_str(
+            r#"{
+                "type": "CALL",
+                "from": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                "to": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                "value": "0x0",
+                "gas": "0x7a120",
+                "gasUsed": "0x5c8c",
+                "input": "0xa9059cbb",
+                "output": "0x",
+                "calls": [{
+                    "type": "STATICCALL",
+                    "from": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                    "to": "0xca247d7425a29c6645fa991f9151f994a830882d",
+                    "gas": "0x1000",
+                    "gasUsed": "0x64",
+                    "input": "0x",
+                    "error": "execution reverted",
+                    "revertReason": "nope"
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(frame.call_type, "CALL");
+        assert_eq!(frame.gas_used, U256::from_int_unchecked(0x5c8c_u32));
+        assert_eq!(frame.calls.len(), 1);
+        assert_eq!(frame.calls[0].value, None);
+        assert_eq!(frame.calls[0].revert_reason, Some(String::from("nope")));
+        assert!(frame.calls[0].calls.is_empty());
+    }
+
+    #[test]
+    fn test_types_localized_trace() {
+        let traces: Vec<LocalizedTrace> = serde_json::from_str(
+            r#"[{
+                "action": {
+                    "callType": "call",
+                    "from": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                    "to": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                    "gas": "0x0",
+                    "input": "0x",
+                    "value": "0xde0b6b3a7640000"
+                },
+                "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+                "blockNumber": 3068185,
+                "result": { "gasUsed": "0x0", "output": "0x" },
+                "subtraces": 0,
+                "traceAddress": [],
+                "transactionHash": "0x07da28d752aba3b9dd7060005e554719c6205c8a3aea358599fc9b245c52f1f6",
+                "transactionPosition": 0,
+                "type": "call"
+            }, {
+                "action": {
+                    "author": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                    "rewardType": "block",
+                    "value": "0x1bc16d674ec80000"
+                },
+                "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+                "blockNumber": 3068185,
+                "result": null,
+                "subtraces": 0,
+                "traceAddress": [],
+                "transactionHash": null,
+                "transactionPosition": null,
+                "type": "reward"
+            }]"#,
+        )
+        .unwrap();
+        assert!(matches!(traces[0].action, TraceAction::Call(_)));
+        assert!(matches!(traces[0].result, Some(TraceResult::Call { .. })));
+        assert!(matches!(traces[1].action, TraceAction::Reward(_)));
+        assert_eq!(traces[1].transaction_hash, None);
+    }
+
+    fn localized_trace(action: &str, result: &str, trace_type: &str) -> LocalizedTrace {
+        serde_json::from_str(&format!(
+            r#"{{
+                "action": {},
+                "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+                "blockNumber": 3068185,
+                "result": {},
+                "subtraces": 0,
+                "traceAddress": [1, 0],
+                "transactionHash": "0x07da28d752aba3b9dd7060005e554719c6205c8a3aea358599fc9b245c52f1f6",
+                "transactionPosition": 2,
+                "type": "{}"
+            }}"#,
+            action, result, trace_type
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_types_trace_variants() {
+        let call = localized_trace(
+            r#"{
+                "callType": "delegatecall",
+                "from": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                "to": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                "gas": "0x1a1f8",
+                "input": "0xa9059cbb",
+                "value": "0x0"
+            }"#,
+            r#"{ "gasUsed": "0x3fe", "output": "0x0000000000000000000000000000000000000000000000000000000000000001" }"#,
+            "call",
+        );
+        match (&call.action, &call.result) {
+            (TraceAction::Call(action), Some(TraceResult::Call { gas_used, output })) => {
+                assert_eq!(action.call_type, "delegatecall");
+                assert_eq!(action.gas, U256::from_int_unchecked(0x1a1f8_u32));
+                assert_eq!(*gas_used, U256::from_int_unchecked(0x3fe_u16));
+                assert_eq!(output.0.len(), 32);
+            }
+            trace => panic!("Expected a call trace, got {:?}", trace),
+        }
+        assert_eq!(call.trace_address, vec![1, 0]);
+
+        let create = localized_trace(
+            r#"{
+                "from": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                "gas": "0x5ee8e",
+                "init": "0x6080604052",
+                "value": "0x0"
+            }"#,
+            r#"{
+                "address": "0xca247d7425a29c6645fa991f9151f994a830882d",
+                "code": "0x6080",
+                "gasUsed": "0x4683f"
+            }"#,
+            "create",
+        );
+        match (&create.action, &create.result) {
+            (TraceAction::Create(action), Some(TraceResult::Create { address, code, .. })) => {
+                assert_eq!(action.init, Bytes(vec![0x60, 0x80, 0x60, 0x40, 0x52]));
+                assert_eq!(
+                    *address,
+                    Address::try_from("0xca247d7425a29c6645fa991f9151f994a830882d").unwrap()
+                );
+                assert_eq!(*code, Bytes(vec![0x60, 0x80]));
+            }
+            trace => panic!("Expected a create trace, got {:?}", trace),
+        }
+
+        let suicide = localized_trace(
+            r#"{
+                "address": "0xca247d7425a29c6645fa991f9151f994a830882d",
+                "balance": "0xde0b6b3a7640000",
+                "refundAddress": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f"
+            }"#,
+            "null",
+            "suicide",
+        );
+        match &suicide.action {
+            TraceAction::Suicide(action) => {
+                assert_eq!(
+                    action.refund_address,
+                    Address::try_from("0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f").unwrap()
+                );
+                assert_eq!(
+                    action.balance,
+                    U256::from_int_unchecked(1_000_000_000_000_000_000_u64)
+                );
+            }
+            action => panic!("Expected a suicide action, got {:?}", action),
+        }
+        assert_eq!(suicide.result, None);
+    }
+
+    #[test]
+    fn test_types_nested_call_frames() {
+        let frame: CallFrame = serde_json::from_str(
+            r#"{
+                "type": "CALL",
+                "from": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                "to": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                "value": "0x0",
+                "gas": "0x7a120",
+                "gasUsed": "0x2d2b4",
+                "input": "0x1249c58b",
+                "output": "0x",
+                "calls": [{
+                    "type": "CREATE2",
+                    "from": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                    "to": "0xca247d7425a29c6645fa991f9151f994a830882d",
+                    "value": "0x0",
+                    "gas": "0x6d1e0",
+                    "gasUsed": "0x1a2b0",
+                    "input": "0x6080604052",
+                    "output": "0x6080",
+                    "calls": [{
+                        "type": "DELEGATECALL",
+                        "from": "0xca247d7425a29c6645fa991f9151f994a830882d",
+                        "to": "0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a",
+                        "gas": "0x5000",
+                        "gasUsed": "0x12c",
+                        "input": "0x",
+                        "output": "0x"
+                    }]
+                }, {
+                    "type": "SELFDESTRUCT",
+                    "from": "0x99429f64cf4d5837620dcc293c1a537d58729b68",
+                    "to": "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f",
+                    "value": "0x1",
+                    "gas": "0x0",
+                    "gasUsed": "0x0",
+                    "input": "0x"
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(frame.calls.len(), 2);
+        let create = &frame.calls[0];
+        assert_eq!(create.call_type, "CREATE2");
+        assert_eq!(create.output, Some(Bytes(vec![0x60, 0x80])));
+        assert_eq!(create.calls.len(), 1);
+        assert_eq!(create.calls[0].call_type, "DELEGATECALL");
+        assert_eq!(create.calls[0].value, None);
+        assert!(create.calls[0].calls.is_empty());
+        assert_eq!(frame.calls[1].call_type, "SELFDESTRUCT");
+        assert_eq!(frame.calls[1].output, None);
+    }
+
+    #[test]
+    fn test_types_prestate() {
+        let prestate: HashMap<Address, PrestateAccount> = serde_json::from_str(
+            r#"{
+                "0x99429f64cf4d5837620dcc293c1a537d58729b68": {
+                    "balance": "0x0",
+                    "nonce": 1,
+                    "code": "0x6080",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000000": "0x000000000000000000000000000000000000000000000000000000000000000b"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let account =
+            &prestate[&Address::try_from("0x99429f64cf4d5837620dcc293c1a537d58729b68").unwrap()];
+        assert_eq!(account.nonce, Some(1));
+        assert_eq!(
+            account.storage.as_ref().unwrap()[&H256::zero()],
+            H256::from_int_unchecked(11_u8)
+        );
+    }
+
+    #[test]
+    fn test_types_peer_info() {
+        let peers: Vec<PeerInfo> = serde_json::from_str(
+            r#"[{
+                "enode": "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@52.16.188.185:30303",
+                "id": "4f4a9e2e5fbb23b8e7d6b1fa4b4a3bc9c1f1b33d1cb6b6c3a4b8e4bda4d8e9f0",
+                "name": "Geth/v1.10.26-stable/linux-amd64/go1.18.5",
+                "caps": ["eth/66", "eth/67", "snap/1"],
+                "network": {
+                    "localAddress": "192.168.0.2:39556",
+                    "remoteAddress": "52.16.188.185:30303",
+                    "inbound": false,
+                    "trusted": false,
+                    "static": true
+                },
+                "protocols": { "eth": { "version": 67 }, "snap": "handshake" }
+            }]"#,
+        )
+        .unwrap();
+        assert_eq!(peers[0].caps.len(), 3);
+        assert_eq!(peers[0].enr, None);
+        assert!(peers[0].network.is_static);
+        assert!(!peers[0].network.inbound);
+    }
+
     #[test]
     fn test_types_private_key() {
         let raw_hex_key = "0xe4745d1287b67412ce806746e83d49efe5cec53f5a27aa666fb9e8092a8dbd43";
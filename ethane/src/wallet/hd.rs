@@ -0,0 +1,288 @@
+//! Hierarchical deterministic keys from [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki)
+//! mnemonics, derived as specified in [BIP-32](https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki)
+
+use super::{private_key_to_address, random_bytes, WalletError};
+use crate::types::Address;
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::SecretKey;
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+/// The BIP-44 path of the Ethereum accounts, without the account index
+pub const ETHEREUM_PATH: &str = "m/44'/60'/0'/0";
+
+/// Indices from this one on derive hardened keys
+const HARDENED: u32 = 1 << 31;
+
+/// A key derived from a mnemonic, the key is zeroed when it is dropped
+#[derive(Clone, PartialEq)]
+pub struct DerivedAccount {
+    pub path: String,
+    pub private_key: [u8; 32],
+    pub address: Address,
+}
+
+/// The private key is not printed
+impl std::fmt::Debug for DerivedAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerivedAccount")
+            .field("path", &self.path)
+            .field("address", &format_args!("{}", self.address))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for DerivedAccount {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+/// The seed of a mnemonic, from which keys are derived, it is zeroed when the wallet is dropped
+///
+/// ```
+/// use ethane::wallet::HdWallet;
+///
+/// let wallet = HdWallet::from_mnemonic(
+///     "test test test test test test test test test test test junk",
+///     "",
+/// )
+/// .unwrap();
+/// let accounts = (0..10)
+///     .map(|index| wallet.account(index))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(
+///     accounts[0].address.to_string(),
+///     "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct HdWallet {
+    seed: [u8; 64],
+}
+
+impl HdWallet {
+    /// Checks the words and the checksum of an English mnemonic and derives its seed with the
+    /// passphrase, which is empty if not used.
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<Self, WalletError> {
+        let mnemonic = bip39::Mnemonic::parse_in(bip39::Language::English, mnemonic)
+            .map_err(|e| WalletError::InvalidMnemonic(e.to_string()))?;
+        Ok(Self {
+            seed: mnemonic.to_seed(passphrase),
+        })
+    }
+
+    pub fn from_seed(seed: [u8; 64]) -> Self {
+        Self { seed }
+    }
+
+    /// Generates a random English mnemonic with 12, 15, 18, 21 or 24 words.
+    pub fn generate_mnemonic(word_count: usize) -> Result<String, WalletError> {
+        if !matches!(word_count, 12 | 15 | 18 | 21 | 24) {
+            return Err(WalletError::InvalidMnemonic(format!(
+                "Unsupported word count {}",
+                word_count
+            )));
+        }
+        let entropy = random_bytes::<32>()?;
+        let mnemonic = bip39::Mnemonic::from_entropy(&entropy[..word_count * 4 / 3])
+            .map_err(|e| WalletError::InvalidMnemonic(e.to_string()))?;
+        Ok(mnemonic.to_string())
+    }
+
+    /// Derives the Ethereum account with the given index, i.e. the path `m/44'/60'/0'/0/index`.
+    pub fn account(&self, index: u32) -> Result<DerivedAccount, WalletError> {
+        self.derive(&format!("{}/{}", ETHEREUM_PATH, index))
+    }
+
+    /// Derives the account of a path like `m/44'/60'/0'/0/0`, hardened indices are marked by
+    /// `'` or `h`.
+    pub fn derive(&self, path: &str) -> Result<DerivedAccount, WalletError> {
+        let mut key = ExtendedKey::master(&self.seed)?;
+        for index in parse_path(path)? {
+            key = key.child(index)?;
+        }
+        let private_key: [u8; 32] = key.secret.to_bytes().into();
+        Ok(DerivedAccount {
+            path: path.to_owned(),
+            address: private_key_to_address(&private_key)?,
+            private_key,
+        })
+    }
+}
+
+/// The seed is not printed
+impl std::fmt::Debug for HdWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HdWallet").finish_non_exhaustive()
+    }
+}
+
+impl Drop for HdWallet {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+struct ExtendedKey {
+    secret: SecretKey,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    fn master(seed: &[u8]) -> Result<Self, WalletError> {
+        Self::from_hmac(b"Bitcoin seed", seed, None)
+    }
+
+    fn child(&self, index: u32) -> Result<Self, WalletError> {
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED {
+            data.push(0);
+            data.extend_from_slice(&self.secret.to_bytes());
+        } else {
+            let public = self.secret.public_key().to_encoded_point(true);
+            data.extend_from_slice(public.as_bytes());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+        Self::from_hmac(&self.chain_code, &data, Some(&self.secret))
+    }
+
+    /// Splits the HMAC into the key, which is added to the parent key, and the chain code.
+    fn from_hmac(key: &[u8], data: &[u8], parent: Option<&SecretKey>) -> Result<Self, WalletError> {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key)
+            .map_err(|e| WalletError::InvalidKey(e.to_string()))?;
+        mac.update(data);
+        let hash = mac.finalize().into_bytes();
+
+        // fails for the negligible chance of a key which is zero or not less than the order
+        let invalid = |_| WalletError::InvalidKey("The derived key is invalid".to_owned());
+        let mut secret = SecretKey::from_slice(&hash[..32]).map_err(invalid)?;
+        if let Some(parent) = parent {
+            let sum = *secret.to_nonzero_scalar() + *parent.to_nonzero_scalar();
+            secret = SecretKey::from_bytes(&sum.to_bytes()).map_err(invalid)?;
+        }
+        let mut chain_code = [0_u8; 32];
+        chain_code.copy_from_slice(&hash[32..]);
+        Ok(Self { secret, chain_code })
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<u32>, WalletError> {
+    let invalid = || WalletError::InvalidPath(path.to_owned());
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid());
+    }
+    segments
+        .map(|segment| {
+            let (index, hardened) = match segment.strip_suffix(&['\'', 'h', 'H'][..]) {
+                Some(index) => (index, true),
+                None => (segment, false),
+            };
+            let index: u32 = index.parse().map_err(|_| invalid())?;
+            match (index < HARDENED, hardened) {
+                (true, true) => Ok(index + HARDENED),
+                (true, false) => Ok(index),
+                (false, _) => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn bip32_test_vector() {
+        let seed = hex!("000102030405060708090a0b0c0d0e0f");
+        let derive = |path: &str| {
+            let mut key = ExtendedKey::master(&seed).unwrap();
+            for index in parse_path(path).unwrap() {
+                key = key.child(index).unwrap();
+            }
+            <[u8; 32]>::from(key.secret.to_bytes())
+        };
+        assert_eq!(
+            derive("m"),
+            hex!("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35")
+        );
+        assert_eq!(
+            derive("m/0'"),
+            hex!("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+        );
+        assert_eq!(
+            derive("m/0h/1"),
+            hex!("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+        );
+        assert_eq!(
+            derive("m/0H/1/2'"),
+            hex!("cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca")
+        );
+    }
+
+    #[test]
+    fn ethereum_accounts() {
+        let wallet = HdWallet::from_mnemonic(
+            "test test test test test test test test test test test junk",
+            "",
+        )
+        .unwrap();
+        let account = wallet.account(0).unwrap();
+        assert_eq!(account.path, "m/44'/60'/0'/0/0");
+        assert_eq!(
+            account.private_key,
+            hex!("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+        );
+        assert_eq!(
+            account.address,
+            Address::try_from("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap()
+        );
+        assert_eq!(
+            format!("{:?}", account),
+            "DerivedAccount { path: \"m/44'/60'/0'/0/0\", \
+             address: 0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266, .. }"
+        );
+        assert_eq!(
+            wallet.account(1).unwrap().address,
+            Address::try_from("0x70997970c51812dc3a010c7d01b50e0d17dc79c8").unwrap()
+        );
+    }
+
+    #[test]
+    fn mnemonics() {
+        let wallet = HdWallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "TREZOR",
+        )
+        .unwrap();
+        assert_eq!(
+            wallet.seed,
+            hex!("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04")
+        );
+        // the checksum word does not match
+        assert!(HdWallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon",
+            ""
+        )
+        .is_err());
+
+        let mnemonic = HdWallet::generate_mnemonic(24).unwrap();
+        assert_eq!(mnemonic.split(' ').count(), 24);
+        assert!(HdWallet::from_mnemonic(&mnemonic, "").is_ok());
+        assert!(HdWallet::generate_mnemonic(13).is_err());
+    }
+
+    #[test]
+    fn invalid_paths() {
+        for path in &["", "44'/60'", "m/", "m/x", "m/2147483648", "m//1"] {
+            assert!(parse_path(path).is_err(), "{}", path);
+        }
+        assert_eq!(parse_path("m").unwrap(), Vec::<u32>::new());
+    }
+}
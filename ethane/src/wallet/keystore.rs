@@ -0,0 +1,468 @@
+//! The [Web3 Secret Storage](https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/)
+//! format, version 3
+
+use super::{keccak, private_key_to_address, random_bytes, WalletError};
+use crate::types::Address;
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+const CIPHER: &str = "aes-128-ctr";
+const PRF: &str = "hmac-sha256";
+/// The length of the derived key, its first half is the encryption key, the second the MAC key
+const DERIVED_KEY_LEN: usize = 32;
+/// The highest scrypt cost `log_n` of a keystore, which is four times the standard cost
+const MAX_SCRYPT_LOG_N: u32 = 20;
+/// The most memory scrypt may use to derive the key, `128 * r * n` bytes
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+/// The highest product of the scrypt block size `r` and parallelization `p`
+const MAX_SCRYPT_R_P: u64 = 256;
+/// The most PBKDF2 iterations of a keystore
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+/// The key derivation function which derives the encryption key from the password
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// Scrypt with the cost `n = 2^log_n`, the block size `r` and the parallelization `p`
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    /// PBKDF2 with HMAC-SHA256
+    Pbkdf2 { iterations: u32 },
+}
+
+impl Kdf {
+    /// The parameters of geth, which need 256 MiB memory to decrypt
+    pub const STANDARD_SCRYPT: Kdf = Kdf::Scrypt {
+        log_n: 18,
+        r: 8,
+        p: 1,
+    };
+    /// The light parameters of geth, which are considerably faster but less secure
+    pub const LIGHT_SCRYPT: Kdf = Kdf::Scrypt {
+        log_n: 12,
+        r: 8,
+        p: 6,
+    };
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Self::STANDARD_SCRYPT
+    }
+}
+
+/// A private key encrypted with a password
+///
+/// Keystores are (de)serialized in the JSON format of geth's keystore files, the `crypto` key
+/// may also be spelled `Crypto`.
+///
+/// ```
+/// use ethane::wallet::{Kdf, Keystore};
+///
+/// let private_key = [0x2a; 32];
+/// let keystore = Keystore::encrypt(&private_key, "password", Kdf::LIGHT_SCRYPT).unwrap();
+/// let json = serde_json::to_string(&keystore).unwrap();
+///
+/// let keystore: Keystore = serde_json::from_str(&json).unwrap();
+/// assert_eq!(keystore.decrypt("password").unwrap(), private_key);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    version: u8,
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<HexBytes>,
+    #[serde(alias = "Crypto")]
+    crypto: Crypto,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Crypto {
+    cipher: String,
+    cipherparams: CipherParams,
+    ciphertext: HexBytes,
+    #[serde(flatten)]
+    kdf: KdfParams,
+    mac: HexBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CipherParams {
+    iv: HexBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        r: u32,
+        p: u32,
+        salt: HexBytes,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: usize,
+        prf: String,
+        salt: HexBytes,
+    },
+}
+
+impl Keystore {
+    /// Encrypts a private key with a password, using a random salt and initialization vector.
+    ///
+    /// Fails with [InvalidKeystore](WalletError::InvalidKeystore) if the cost of the key
+    /// derivation exceeds the limits of [decrypt](Self::decrypt).
+    pub fn encrypt(private_key: &[u8; 32], password: &str, kdf: Kdf) -> Result<Self, WalletError> {
+        let address = private_key_to_address(private_key)?;
+        let salt = random_bytes::<32>()?.to_vec();
+        let iv = random_bytes::<16>()?.to_vec();
+        let kdf = match kdf {
+            Kdf::Scrypt { log_n, r, p } => KdfParams::Scrypt {
+                dklen: DERIVED_KEY_LEN,
+                n: 1_u64.checked_shl(log_n.into()).ok_or_else(|| {
+                    WalletError::InvalidKeystore(format!("The scrypt cost 2^{} is too high", log_n))
+                })?,
+                r,
+                p,
+                salt: HexBytes(salt),
+            },
+            Kdf::Pbkdf2 { iterations } => KdfParams::Pbkdf2 {
+                c: iterations,
+                dklen: DERIVED_KEY_LEN,
+                prf: PRF.to_owned(),
+                salt: HexBytes(salt),
+            },
+        };
+
+        let derived_key = kdf.derive_key(password)?;
+        let mut ciphertext = private_key.to_vec();
+        Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+            .map_err(|e| WalletError::InvalidKeystore(e.to_string()))?
+            .apply_keystream(&mut ciphertext);
+        let mac = mac(&derived_key, &ciphertext);
+
+        Ok(Self {
+            version: 3,
+            id: uuid_v4()?,
+            address: Some(HexBytes(address.as_bytes().to_vec())),
+            crypto: Crypto {
+                cipher: CIPHER.to_owned(),
+                cipherparams: CipherParams { iv: HexBytes(iv) },
+                ciphertext: HexBytes(ciphertext),
+                kdf,
+                mac: HexBytes(mac.to_vec()),
+            },
+        })
+    }
+
+    /// Decrypts the private key, fails with [WrongPassword](WalletError::WrongPassword) if the
+    /// MAC does not match.
+    ///
+    /// Keystores whose key derivation is too expensive are rejected as
+    /// [InvalidKeystore](WalletError::InvalidKeystore): scrypt with a cost above `2^20`, more
+    /// than 1 GiB of memory or `r * p` above 256, and PBKDF2 with more than 10 million
+    /// iterations.
+    pub fn decrypt(&self, password: &str) -> Result<[u8; 32], WalletError> {
+        if self.version != 3 {
+            return Err(WalletError::InvalidKeystore(format!(
+                "Unsupported version {}",
+                self.version
+            )));
+        }
+        if self.crypto.cipher != CIPHER {
+            return Err(WalletError::InvalidKeystore(format!(
+                "Unsupported cipher {}",
+                self.crypto.cipher
+            )));
+        }
+        let derived_key = self.crypto.kdf.derive_key(password)?;
+        // compared in constant time, so the time does not reveal how many bytes match
+        let mac = mac(&derived_key, &self.crypto.ciphertext.0);
+        if !bool::from(mac[..].ct_eq(&self.crypto.mac.0)) {
+            return Err(WalletError::WrongPassword);
+        }
+
+        let mut private_key = Zeroizing::new(self.crypto.ciphertext.0.clone());
+        Aes128Ctr::new_from_slices(&derived_key[..16], &self.crypto.cipherparams.iv.0)
+            .map_err(|e| WalletError::InvalidKeystore(e.to_string()))?
+            .apply_keystream(&mut private_key);
+        <[u8; 32]>::try_from(private_key.as_slice())
+            .map_err(|_| WalletError::InvalidKeystore("The private key is not 32 bytes".to_owned()))
+    }
+
+    /// The address of the key, if it is stored in the keystore
+    pub fn address(&self) -> Option<Address> {
+        let address = self.address.as_ref()?;
+        <[u8; 20]>::try_from(address.0.as_slice())
+            .ok()
+            .map(Address::from)
+    }
+
+    /// The random UUID of the keystore
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Reads a keystore file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, WalletError> {
+        let json = fs::read_to_string(path).map_err(|e| WalletError::Io(e.to_string()))?;
+        serde_json::from_str(&json).map_err(|e| WalletError::InvalidKeystore(e.to_string()))
+    }
+
+    /// Writes the keystore to a file, which is overwritten if it exists.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let json =
+            serde_json::to_string(self).map_err(|e| WalletError::InvalidKeystore(e.to_string()))?;
+        fs::write(path, json).map_err(|e| WalletError::Io(e.to_string()))
+    }
+}
+
+impl KdfParams {
+    /// Derives the key, which is zeroed when it is dropped.
+    fn derive_key(&self, password: &str) -> Result<Zeroizing<Vec<u8>>, WalletError> {
+        match self {
+            KdfParams::Scrypt {
+                dklen,
+                n,
+                r,
+                p,
+                salt,
+            } => {
+                check_key_len(*dklen)?;
+                if !n.is_power_of_two() {
+                    return Err(WalletError::InvalidKeystore(format!(
+                        "The scrypt cost {} is not a power of two",
+                        n
+                    )));
+                }
+                if n.trailing_zeros() > MAX_SCRYPT_LOG_N {
+                    return Err(WalletError::InvalidKeystore(format!(
+                        "The scrypt cost {} is higher than 2^{}",
+                        n, MAX_SCRYPT_LOG_N
+                    )));
+                }
+                let (r_wide, p_wide) = (u64::from(*r), u64::from(*p));
+                if 128 * r_wide * n > MAX_SCRYPT_MEMORY || r_wide * p_wide > MAX_SCRYPT_R_P {
+                    return Err(WalletError::InvalidKeystore(format!(
+                        "The scrypt parameters n = {}, r = {} and p = {} are too expensive",
+                        n, r, p
+                    )));
+                }
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p, *dklen)
+                    .map_err(|e| WalletError::InvalidKeystore(e.to_string()))?;
+                let mut key = Zeroizing::new(vec![0_u8; *dklen]);
+                scrypt::scrypt(password.as_bytes(), &salt.0, &params, &mut key)
+                    .map_err(|e| WalletError::InvalidKeystore(e.to_string()))?;
+                Ok(key)
+            }
+            KdfParams::Pbkdf2 {
+                c,
+                dklen,
+                prf,
+                salt,
+            } => {
+                check_key_len(*dklen)?;
+                if prf != PRF {
+                    return Err(WalletError::InvalidKeystore(format!(
+                        "Unsupported pseudorandom function {}",
+                        prf
+                    )));
+                }
+                if *c > MAX_PBKDF2_ITERATIONS {
+                    return Err(WalletError::InvalidKeystore(format!(
+                        "The PBKDF2 iteration count {} is higher than {}",
+                        c, MAX_PBKDF2_ITERATIONS
+                    )));
+                }
+                let mut key = Zeroizing::new(vec![0_u8; *dklen]);
+                pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), &salt.0, *c, &mut key);
+                Ok(key)
+            }
+        }
+    }
+}
+
+fn check_key_len(dklen: usize) -> Result<(), WalletError> {
+    if dklen < DERIVED_KEY_LEN {
+        return Err(WalletError::InvalidKeystore(format!(
+            "The derived key length {} is shorter than {}",
+            dklen, DERIVED_KEY_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    keccak(&[&derived_key[16..32], ciphertext].concat())
+}
+
+/// A random UUID as specified in RFC 4122, version 4
+fn uuid_v4() -> Result<String, WalletError> {
+    let mut bytes = random_bytes::<16>()?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Bytes which are serialized as hex string without `0x` prefix
+#[derive(Clone, Debug, PartialEq)]
+struct HexBytes(Vec<u8>);
+
+impl Serialize for HexBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let string = string.strip_prefix("0x").unwrap_or(&string);
+        hex::decode(string).map(HexBytes).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    /// The PBKDF2 test vector of the Web3 Secret Storage definition. Its scrypt vector uses
+    /// `r = 1` with `n = 2^18`, which is rejected by RFC 7914.
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+    const PRIVATE_KEY: [u8; 32] =
+        hex!("7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d");
+
+    #[test]
+    fn decrypt_test_vector() {
+        let keystore: Keystore = serde_json::from_str(PBKDF2_KEYSTORE).unwrap();
+        assert_eq!(keystore.decrypt("testpassword").unwrap(), PRIVATE_KEY);
+        assert!(matches!(
+            keystore.decrypt("wrongpassword"),
+            Err(WalletError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn scrypt_derived_key() {
+        let kdf = KdfParams::Scrypt {
+            dklen: 32,
+            n: 4096,
+            r: 8,
+            p: 6,
+            salt: HexBytes(
+                hex!("ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19").to_vec(),
+            ),
+        };
+        assert_eq!(
+            *kdf.derive_key("testpassword").unwrap(),
+            hex!("8a2a3435d87dc157e4249057689714165d60daa463cfe51b2d50d42ba384adf4")
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        for kdf in &[Kdf::LIGHT_SCRYPT, Kdf::Pbkdf2 { iterations: 1024 }] {
+            let keystore = Keystore::encrypt(&PRIVATE_KEY, "password", *kdf).unwrap();
+            assert_eq!(
+                keystore.address(),
+                Some(private_key_to_address(&PRIVATE_KEY).unwrap())
+            );
+            assert_eq!(keystore.id().len(), 36);
+
+            let json = serde_json::to_string(&keystore).unwrap();
+            let keystore: Keystore = serde_json::from_str(&json).unwrap();
+            assert_eq!(keystore.decrypt("password").unwrap(), PRIVATE_KEY);
+        }
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let kdf = Kdf::Scrypt {
+            log_n: 64,
+            r: 8,
+            p: 1,
+        };
+        assert!(matches!(
+            Keystore::encrypt(&PRIVATE_KEY, "password", kdf),
+            Err(WalletError::InvalidKeystore(_))
+        ));
+
+        let mut keystore = Keystore::encrypt(&PRIVATE_KEY, "password", Kdf::LIGHT_SCRYPT).unwrap();
+        keystore.crypto.mac.0.pop();
+        assert!(matches!(
+            keystore.decrypt("password"),
+            Err(WalletError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn expensive_parameters() {
+        let salt = HexBytes(vec![0; 32]);
+        let scrypt = |n, r, p| KdfParams::Scrypt {
+            dklen: 32,
+            n,
+            r,
+            p,
+            salt: salt.clone(),
+        };
+        let pbkdf2 = |c| KdfParams::Pbkdf2 {
+            c,
+            dklen: 32,
+            prf: PRF.to_owned(),
+            salt: salt.clone(),
+        };
+        for kdf in &[
+            scrypt(1 << 21, 1, 1),
+            scrypt(1 << 20, 16, 1),
+            scrypt(1 << 10, 8, 64),
+            scrypt(1 << 10, u32::MAX, u32::MAX),
+            pbkdf2(u32::MAX),
+        ] {
+            assert!(matches!(
+                kdf.derive_key("password"),
+                Err(WalletError::InvalidKeystore(_))
+            ));
+        }
+
+        let mut keystore: Keystore = serde_json::from_str(PBKDF2_KEYSTORE).unwrap();
+        keystore.crypto.kdf = pbkdf2(MAX_PBKDF2_ITERATIONS + 1);
+        assert!(matches!(
+            keystore.decrypt("testpassword"),
+            Err(WalletError::InvalidKeystore(_))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! Local key management, so private keys never have to be sent to a node
+//!
+//! Keys are plain 32 byte secp256k1 private keys. They can be loaded from and stored in the
+//! JSON [Keystore] format used by geth and most wallets, instead of importing them into a node
+//! with [personal_import_raw_key](crate::rpc::personal_import_raw_key). An [HdWallet] derives
+//! any number of accounts from a single mnemonic, like the accounts of development nodes.
+
+mod hd;
+mod keystore;
+
+pub use hd::{DerivedAccount, HdWallet, ETHEREUM_PATH};
+pub use keystore::{Kdf, Keystore};
+
+use crate::types::Address;
+
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Wraps the different errors that may occur when handling keys.
+#[derive(Debug)]
+pub enum WalletError {
+    /// The bytes are not a valid secp256k1 private key
+    InvalidKey(String),
+    /// The keystore is malformed or uses an unsupported cipher or key derivation function
+    InvalidKeystore(String),
+    /// The password does not match the keystore
+    WrongPassword,
+    /// The mnemonic contains unknown words or its checksum does not match
+    InvalidMnemonic(String),
+    /// The derivation path is malformed
+    InvalidPath(String),
+    Io(String),
+}
+
+/// Returns the address which belongs to a private key.
+pub fn private_key_to_address(private_key: &[u8; 32]) -> Result<Address, WalletError> {
+    let secret = k256::SecretKey::from_slice(private_key)
+        .map_err(|e| WalletError::InvalidKey(e.to_string()))?;
+    let public = secret.public_key().to_encoded_point(false);
+    // the address is the hash of the uncompressed public key without its 0x04 prefix
+    let hash = keccak(&public.as_bytes()[1..]);
+    let mut address = [0_u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(Address::from(address))
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0_u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], WalletError> {
+    let mut bytes = [0_u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| WalletError::Io(e.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn address_of_private_key() {
+        let private_key =
+            hex_literal::hex!("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318");
+        assert_eq!(
+            private_key_to_address(&private_key).unwrap(),
+            Address::try_from("0x2c7536e3605d9c16a7a3d7b1898e529396a65c23").unwrap()
+        );
+        assert!(private_key_to_address(&[0_u8; 32]).is_err());
+    }
+}
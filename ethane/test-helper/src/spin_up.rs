@@ -1,5 +1,9 @@
+use ethane::logs::{CheckpointStore, Indexer, IndexerError, IndexerEvent};
 use ethane::rpc::{Rpc, SubscriptionRequest};
-use ethane::{Connection, ConnectionError, Http, Request, Subscribe, Subscription, WebSocket};
+use ethane::types::{Filter, Log, H256};
+use ethane::{
+    Connection, ConnectionError, FilterWatcher, Http, Request, Subscribe, Subscription, WebSocket,
+};
 use regex::{Regex, RegexBuilder};
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
@@ -63,6 +67,46 @@ impl ConnectionWrapper {
             _ => panic!("Subscription not supported for this transport"),
         }
     }
+
+    pub fn watch_logs(
+        &mut self,
+        filter: Filter,
+    ) -> Result<Box<dyn DynSubscription<Log> + '_>, ConnectionError> {
+        match self {
+            Self::Websocket(bundle) => bundle.connection.watch_logs(filter).map(boxed),
+            Self::Http(bundle) => bundle.connection.watch_logs(filter).map(boxed),
+            #[cfg(target_family = "unix")]
+            Self::Uds(bundle) => bundle.connection.watch_logs(filter).map(boxed),
+        }
+    }
+
+    pub fn backfill<S: CheckpointStore>(
+        &mut self,
+        indexer: &mut Indexer<S>,
+        handler: &mut impl FnMut(IndexerEvent) -> Result<(), IndexerError>,
+    ) -> Result<u64, IndexerError> {
+        match self {
+            Self::Websocket(bundle) => indexer.backfill(&mut bundle.connection, handler),
+            Self::Http(bundle) => indexer.backfill(&mut bundle.connection, handler),
+            #[cfg(target_family = "unix")]
+            Self::Uds(bundle) => indexer.backfill(&mut bundle.connection, handler),
+        }
+    }
+
+    pub fn watch_blocks(&mut self) -> Result<Box<dyn DynSubscription<H256> + '_>, ConnectionError> {
+        match self {
+            Self::Websocket(bundle) => bundle.connection.watch_blocks().map(boxed),
+            Self::Http(bundle) => bundle.connection.watch_blocks().map(boxed),
+            #[cfg(target_family = "unix")]
+            Self::Uds(bundle) => bundle.connection.watch_blocks().map(boxed),
+        }
+    }
+}
+
+fn boxed<'a, U: DeserializeOwned + Debug>(
+    subscription: impl DynSubscription<U> + 'a,
+) -> Box<dyn DynSubscription<U> + 'a> {
+    Box::new(subscription)
 }
 
 pub trait DynSubscription<U: DeserializeOwned + Debug> {
@@ -77,6 +121,12 @@ impl<T: Subscribe + Request, U: DeserializeOwned + Debug> DynSubscription<U>
     }
 }
 
+impl<'a, T: Request, U: DeserializeOwned + Debug> DynSubscription<U> for FilterWatcher<'a, T, U> {
+    fn next_item(&mut self) -> Result<U, ConnectionError> {
+        self.next_item()
+    }
+}
+
 #[allow(dead_code)]
 pub struct ConnectionNodeBundle<T: Request> {
     connection: Connection<T>,
@@ -155,7 +205,7 @@ impl NodeProcess {
         let cmd = vec![
             "--http".to_string(),
             "--http.api".to_string(),
-            "personal,eth,net,web3,txpool".to_string(),
+            "personal,eth,net,web3,txpool,debug,admin,miner".to_string(),
             "--http.port".to_string(),
             port.to_string(),
             "--allow-insecure-unlock".to_string(),
@@ -171,7 +221,7 @@ impl NodeProcess {
         let cmd = vec![
             "--ws".to_string(),
             "--ws.api".to_string(),
-            "personal,eth,net,web3,txpool".to_string(),
+            "personal,eth,net,web3,txpool,debug,admin,miner".to_string(),
             "--ws.port".to_string(),
             port.to_string(),
             "--allow-insecure-unlock".to_string(),
@@ -0,0 +1,43 @@
+use ethane::rpc;
+
+use test_helper::*;
+
+/// A local node which does not exist, so adding it as peer does not dial the network
+const ENODE: &str = "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:30399";
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the admin API
+fn test_admin_node_info() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let node_info = rpc_call_with_return(&mut client, rpc::admin_node_info());
+    assert!(node_info.enode.starts_with("enode://"));
+    assert!(node_info.name.starts_with("Geth"));
+    assert!(node_info.protocols.contains_key("eth"));
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the admin API
+fn test_admin_peers() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    // the dev node runs without discovery
+    rpc_call_test_expected(&mut client, rpc::admin_peers(), vec![]);
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the admin API
+fn test_admin_add_and_remove_peer() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_expected(&mut client, rpc::admin_add_peer(String::from(ENODE)), true);
+    rpc_call_test_expected(
+        &mut client,
+        rpc::admin_remove_peer(String::from(ENODE)),
+        true,
+    );
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the admin API
+fn test_admin_datadir() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_some(&mut client, rpc::admin_datadir());
+}
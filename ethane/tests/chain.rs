@@ -0,0 +1,54 @@
+use ethane::chain::{BlockEvent, BlockFollower};
+use ethane::devchain::DevChain;
+use ethane::rpc::{self, dev};
+use ethane::types::*;
+use ethane::Connection;
+
+/// Mines one block for every transfer to the recipient.
+fn transfer(connection: &mut Connection<DevChain>, from: Address, to: Address, count: usize) {
+    for _ in 0..count {
+        let transaction = TransactionRequest {
+            from,
+            to: Some(to),
+            value: Some(U256::from_int_unchecked(1_000_u16)),
+            ..Default::default()
+        };
+        connection
+            .call(rpc::eth_send_transaction(transaction))
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_block_follower_reorg() {
+    let chain = DevChain::new();
+    let accounts = chain.accounts();
+    let mut client = Connection::new(chain.clone());
+    let mut connection = Connection::new(chain);
+    let mut follower = BlockFollower::new();
+    let events = follower.poll(&mut connection).unwrap();
+    let fork_block = follower.head().unwrap().clone();
+    assert_eq!(events, vec![BlockEvent::Applied(vec![fork_block.clone()])]);
+
+    let snapshot_id = client.call(dev::evm_snapshot()).unwrap();
+    transfer(&mut client, accounts[0], accounts[1], 2);
+    match &follower.poll(&mut connection).unwrap()[..] {
+        [BlockEvent::Applied(applied)] => {
+            assert_eq!(applied.len(), 2);
+            assert_eq!(applied[0].parent_hash, fork_block.hash.unwrap());
+        }
+        events => panic!("Expected applied blocks, got {:?}", events),
+    }
+
+    // replace the two blocks with three other ones
+    assert!(client.call(dev::evm_revert(snapshot_id)).unwrap());
+    transfer(&mut client, accounts[0], accounts[2], 3);
+    match &follower.poll(&mut connection).unwrap()[..] {
+        [BlockEvent::Reverted(reverted), BlockEvent::Applied(applied)] => {
+            assert_eq!(reverted.len(), 2);
+            assert_eq!(applied.len(), 3);
+            assert_eq!(applied[0].parent_hash, fork_block.hash.unwrap());
+        }
+        events => panic!("Expected a reorg, got {:?}", events),
+    }
+}
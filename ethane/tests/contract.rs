@@ -29,9 +29,12 @@ fn test_eth_call_contract() {
         conn,
         "../ethane/test-helper/src/fixtures/TestERC20.abi",
         contract_address,
-    );
+    )
+    .unwrap();
 
-    let result = caller.call("balanceOf", vec![Parameter::from(address)], None);
+    let result = caller
+        .call("balanceOf", vec![Parameter::from(address)], None)
+        .unwrap();
     match result {
         CallResult::Transaction(_) => panic!("Should be eth_call"),
         CallResult::Call(r) => match r[0] {
@@ -62,24 +65,29 @@ fn test_eth_call_contract_transfer() {
         conn,
         "../ethane/test-helper/src/fixtures/TestERC20.abi",
         contract_address,
-    );
+    )
+    .unwrap();
 
-    let result = caller.call(
-        "transfer",
-        vec![
-            Parameter::from(to_address),
-            Parameter::from(U256::from_int_unchecked(1000_u16)),
-        ],
-        Some(CallOpts {
-            force_call_type: None,
-            from: Some(address),
-        }),
-    );
+    let result = caller
+        .call(
+            "transfer",
+            vec![
+                Parameter::from(to_address),
+                Parameter::from(U256::from_int_unchecked(1000_u16)),
+            ],
+            Some(CallOpts {
+                from: Some(address),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
     match result {
         CallResult::Call(_) => panic!("Should be a transaction"),
         CallResult::Transaction(tx_hash) => {
             wait_for_transaction(&mut client, tx_hash);
-            let result = caller.call("balanceOf", vec![Parameter::from(to_address)], None);
+            let result = caller
+                .call("balanceOf", vec![Parameter::from(to_address)], None)
+                .unwrap();
             match result {
                 CallResult::Transaction(_) => panic!("Should be eth_call"),
                 CallResult::Call(r) => match r[0] {
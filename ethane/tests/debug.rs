@@ -0,0 +1,94 @@
+use ethane::rpc::{self, CallTracer, PrestateTracer, StructLogger};
+use ethane::types::*;
+use std::convert::TryFrom;
+
+use test_helper::*;
+
+const ADDRESS1: &str = "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f";
+
+#[test]
+fn test_debug_trace_transaction_struct_logger() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+
+    let trace = rpc_call_with_return(
+        &mut client,
+        rpc::debug_trace_transaction(tx_hash, StructLogger::default()),
+    );
+    assert_eq!(trace.gas, 21000);
+    assert!(!trace.failed);
+    // a plain transfer executes no code
+    assert!(trace.struct_logs.is_empty());
+}
+
+#[test]
+fn test_debug_trace_transaction_call_tracer() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+
+    let frame = rpc_call_with_return(
+        &mut client,
+        rpc::debug_trace_transaction(tx_hash, CallTracer::default()),
+    );
+    assert_eq!(frame.call_type, "CALL");
+    assert_eq!(frame.from, sender);
+    assert_eq!(frame.to, Some(Address::try_from(ADDRESS1).unwrap()));
+    assert!(frame.calls.is_empty());
+}
+
+#[test]
+fn test_debug_trace_transaction_prestate_tracer() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+
+    let prestate = rpc_call_with_return(
+        &mut client,
+        rpc::debug_trace_transaction(tx_hash, PrestateTracer),
+    );
+    assert!(prestate.contains_key(&sender));
+    assert!(prestate.contains_key(&Address::try_from(ADDRESS1).unwrap()));
+}
+
+#[test]
+fn test_debug_trace_call() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let call = Call {
+        from: Some(Address::try_from(ADDRESS1).unwrap()),
+        to: create_account(&mut client).1,
+        value: Some(U256::zero()),
+        ..Default::default()
+    };
+    let frame = rpc_call_with_return(
+        &mut client,
+        rpc::debug_trace_call(call, None, CallTracer::default()),
+    );
+    assert_eq!(frame.error, None);
+}
+
+#[test]
+fn test_debug_trace_block_by_number() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+    let tx_receipt =
+        rpc_call_with_return(&mut client, rpc::eth_get_transaction_receipt(tx_hash)).unwrap();
+
+    let traces = rpc_call_with_return(
+        &mut client,
+        rpc::debug_trace_block_by_number(
+            BlockParameter::Custom(tx_receipt.block_number),
+            CallTracer::default(),
+        ),
+    );
+    assert!(traces
+        .iter()
+        .filter_map(|trace| trace.result.as_ref())
+        .any(|frame| frame.from == sender));
+}
@@ -0,0 +1,219 @@
+use ethane::devchain::DevChain;
+use ethane::rpc::{self, dev};
+use ethane::types::*;
+use ethane::Connection;
+use std::convert::TryFrom;
+
+const ADDRESS1: &str = "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f";
+
+fn transfer(from: Address, to: Address) -> TransactionRequest {
+    TransactionRequest {
+        from,
+        to: Some(to),
+        value: Some(U256::from_int_unchecked(1_000_u16)),
+        gas_price: Some(U256::zero()),
+        ..Default::default()
+    }
+}
+
+fn timestamp(connection: &mut Connection<DevChain>) -> U256 {
+    connection
+        .call(rpc::eth_get_block_by_number(None, false))
+        .unwrap()
+        .unwrap()
+        .timestamp
+}
+
+#[test]
+fn test_evm_snapshot_and_revert() {
+    let chain = DevChain::new();
+    let account = chain.accounts()[0];
+    let mut connection = Connection::new(chain);
+    let address = Address::try_from(ADDRESS1).unwrap();
+
+    let snapshot_id = connection.call(dev::evm_snapshot()).unwrap();
+    connection
+        .call(rpc::eth_send_transaction(transfer(account, address)))
+        .unwrap();
+    assert!(connection.call(dev::evm_revert(snapshot_id)).unwrap());
+    assert_eq!(
+        connection
+            .call(rpc::eth_get_balance(address, None))
+            .unwrap(),
+        U256::zero()
+    );
+    // a snapshot can only be reverted to once
+    assert!(!connection.call(dev::evm_revert(snapshot_id)).unwrap());
+}
+
+#[test]
+fn test_evm_mine_and_increase_time() {
+    let mut connection = Connection::new(DevChain::new());
+    let block_number = connection.call(rpc::eth_block_number()).unwrap();
+    let before = timestamp(&mut connection);
+
+    assert_eq!(connection.call(dev::evm_increase_time(3600)).unwrap(), 3600);
+    connection.call(dev::evm_mine(None)).unwrap();
+    let new_block_number = connection.call(rpc::eth_block_number()).unwrap();
+    assert!(new_block_number.as_bytes() > block_number.as_bytes());
+    assert!(timestamp(&mut connection).as_bytes() > before.as_bytes());
+
+    connection.call(dev::evm_mine(Some(2_000_000_000))).unwrap();
+    assert_eq!(
+        timestamp(&mut connection),
+        U256::from_int_unchecked(2_000_000_000_u32)
+    );
+    connection
+        .call(dev::evm_set_next_block_timestamp(2_000_000_100))
+        .unwrap();
+    connection.call(dev::evm_mine(None)).unwrap();
+    assert_eq!(
+        timestamp(&mut connection),
+        U256::from_int_unchecked(2_000_000_100_u32)
+    );
+}
+
+#[test]
+fn test_anvil_set_account() {
+    let mut connection = Connection::new(DevChain::new());
+    let address = Address::try_from(ADDRESS1).unwrap();
+    let balance = U256::from_int_unchecked(1_000_000_u64);
+    connection
+        .call(dev::anvil_set_balance(address, balance))
+        .unwrap();
+    assert_eq!(
+        connection
+            .call(rpc::eth_get_balance(address, None))
+            .unwrap(),
+        balance
+    );
+
+    let code = Bytes::from_slice(&[0x60, 0x00]);
+    connection
+        .call(dev::anvil_set_code(address, code.clone()))
+        .unwrap();
+    assert_eq!(
+        connection.call(rpc::eth_get_code(address, None)).unwrap(),
+        code
+    );
+
+    let nonce = U256::from_int_unchecked(5_u8);
+    connection
+        .call(dev::anvil_set_nonce(address, nonce))
+        .unwrap();
+    assert_eq!(
+        connection
+            .call(rpc::eth_get_transaction_count(address, None))
+            .unwrap(),
+        nonce
+    );
+
+    let value = H256::from_int_unchecked(11_u8);
+    assert!(connection
+        .call(dev::anvil_set_storage_at(address, U256::zero(), value))
+        .unwrap());
+    assert_eq!(
+        connection
+            .call(rpc::eth_get_storage_at(address, U256::zero(), None))
+            .unwrap(),
+        Bytes::from_slice(value.as_bytes())
+    );
+}
+
+#[test]
+fn test_anvil_impersonate_account() {
+    let mut connection = Connection::new(DevChain::new());
+    let address = Address::try_from(ADDRESS1).unwrap();
+    let transaction = transfer(address, Address::zero());
+    connection
+        .call(dev::anvil_set_balance(
+            address,
+            U256::from_int_unchecked(1_000_u16),
+        ))
+        .unwrap();
+
+    assert!(connection
+        .call(rpc::eth_send_transaction(transaction.clone()))
+        .is_err());
+    connection
+        .call(dev::anvil_impersonate_account(address))
+        .unwrap();
+    connection
+        .call(rpc::eth_send_transaction(transaction.clone()))
+        .unwrap();
+    connection
+        .call(dev::anvil_stop_impersonating_account(address))
+        .unwrap();
+    assert!(connection
+        .call(rpc::eth_send_transaction(transaction))
+        .is_err());
+}
+
+#[test]
+fn test_anvil_mine() {
+    let mut connection = Connection::new(DevChain::new());
+    let before = timestamp(&mut connection);
+    connection
+        .call(dev::anvil_mine(
+            Some(U256::from_int_unchecked(3_u8)),
+            Some(U256::from_int_unchecked(100_u8)),
+        ))
+        .unwrap();
+    assert_eq!(
+        connection.call(rpc::eth_block_number()).unwrap(),
+        U64::from_int_unchecked(3_u8)
+    );
+    let after = timestamp(&mut connection);
+    assert!(after.as_bytes() > before.as_bytes());
+}
+
+#[test]
+fn test_hardhat_cheatcodes() {
+    let mut connection = Connection::new(DevChain::new());
+    let address = Address::try_from(ADDRESS1).unwrap();
+    let balance = U256::from_int_unchecked(1_000_u16);
+    let code = Bytes::from_slice(&[0x60, 0x00]);
+    let nonce = U256::from_int_unchecked(2_u8);
+    let value = H256::from_int_unchecked(1_u8);
+
+    assert!(connection
+        .call(dev::hardhat_set_balance(address, balance))
+        .unwrap());
+    assert!(connection
+        .call(dev::hardhat_set_code(address, code.clone()))
+        .unwrap());
+    assert!(connection
+        .call(dev::hardhat_set_nonce(address, nonce))
+        .unwrap());
+    assert!(connection
+        .call(dev::hardhat_set_storage_at(address, U256::zero(), value))
+        .unwrap());
+    assert!(connection
+        .call(dev::hardhat_impersonate_account(address))
+        .unwrap());
+    connection
+        .call(rpc::eth_send_transaction(transfer(
+            address,
+            Address::zero(),
+        )))
+        .unwrap();
+    assert!(connection
+        .call(dev::hardhat_stop_impersonating_account(address))
+        .unwrap());
+    assert!(connection.call(dev::hardhat_mine(None, None)).unwrap());
+
+    assert_eq!(
+        connection.call(rpc::eth_block_number()).unwrap(),
+        U64::from_int_unchecked(2_u8)
+    );
+    assert_eq!(
+        connection.call(rpc::eth_get_code(address, None)).unwrap(),
+        code
+    );
+    assert_eq!(
+        connection
+            .call(rpc::eth_get_storage_at(address, U256::zero(), None))
+            .unwrap(),
+        Bytes::from_slice(value.as_bytes())
+    );
+}
@@ -57,6 +57,60 @@ fn test_eth_gas_price() {
     );
 }
 
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_fee_history() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let fee_history = rpc_call_with_return(
+        &mut client,
+        rpc::eth_fee_history(
+            U64::from_int_unchecked(1_u8),
+            BlockParameter::Latest,
+            vec![50.0],
+        ),
+    );
+    // the base fee of the next block is included as well
+    assert_eq!(fee_history.base_fee_per_gas.len(), 2);
+    assert_eq!(fee_history.gas_used_ratio.len(), 1);
+    assert_eq!(fee_history.reward.unwrap().len(), 1);
+}
+
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_max_priority_fee_per_gas() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_some(&mut client, rpc::eth_max_priority_fee_per_gas());
+}
+
+#[test]
+fn test_eth_chain_id() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    // geth --dev
+    rpc_call_test_expected(
+        &mut client,
+        rpc::eth_chain_id(),
+        U64::from_int_unchecked(1337_u16),
+    );
+}
+
+#[test]
+fn test_custom_rpc() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_expected(
+        &mut client,
+        rpc::Rpc::custom("eth_chainId", ()),
+        serde_json::Value::from("0x539"),
+    );
+    rpc_call_test_expected(
+        &mut client,
+        rpc::Rpc::custom(
+            "eth_getBalance",
+            (Address::zero(), BlockParameter::Earliest),
+        ),
+        U256::zero(),
+    );
+}
+
 #[test]
 fn test_eth_accounts() {
     let mut client = ConnectionWrapper::new_from_env(None);
@@ -91,6 +145,53 @@ fn test_eth_get_balance() {
     }
 }
 
+#[test]
+fn test_eth_get_balance_by_block_hash() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+    let tx_receipt =
+        rpc_call_with_return(&mut client, rpc::eth_get_transaction_receipt(tx_hash)).unwrap();
+    let block_param = BlockParameter::Hash {
+        block_hash: tx_receipt.block_hash,
+        require_canonical: true,
+    };
+
+    let balance = rpc_call_with_return(
+        &mut client,
+        rpc::eth_get_balance(
+            sender,
+            Some(BlockParameter::Custom(tx_receipt.block_number)),
+        ),
+    );
+    rpc_call_test_expected(
+        &mut client,
+        rpc::eth_get_balance(sender, Some(block_param)),
+        balance,
+    );
+    rpc_call_test_expected(
+        &mut client,
+        rpc::eth_get_transaction_count(sender, Some(block_param)),
+        U256::from_int_unchecked(1_u8),
+    );
+}
+
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_get_balance_safe_and_finalized() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let address = Address::try_from(ADDRESS1).unwrap();
+    rpc_call_test_some(
+        &mut client,
+        rpc::eth_get_balance(address, Some(BlockParameter::Safe)),
+    );
+    rpc_call_test_some(
+        &mut client,
+        rpc::eth_get_balance(address, Some(BlockParameter::Finalized)),
+    );
+}
+
 #[test]
 fn test_eth_send_transaction_to_address() {
     let mut client = ConnectionWrapper::new_from_env(None);
@@ -193,6 +294,24 @@ fn test_eth_get_transaction_receipt() {
     assert_eq!(tx_receipt.to.unwrap(), Address::try_from(ADDRESS3).unwrap());
 }
 
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_get_block_receipts() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let tx_hash = simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+    let tx_receipt =
+        rpc_call_with_return(&mut client, rpc::eth_get_transaction_receipt(tx_hash)).unwrap();
+
+    let receipts = rpc_call_with_return(
+        &mut client,
+        rpc::eth_get_block_receipts(Some(BlockParameter::Custom(tx_receipt.block_number))),
+    )
+    .unwrap();
+    assert!(receipts.contains(&tx_receipt));
+}
+
 #[test]
 #[ignore]
 fn test_eth_get_storage_at() {
@@ -399,6 +518,21 @@ fn test_eth_get_code_contract() {
     }
 }
 
+#[test]
+fn test_eth_get_proof() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let address = Address::try_from(ADDRESS1).unwrap();
+    let proof = rpc_call_with_return(
+        &mut client,
+        rpc::eth_get_proof(address, vec![H256::zero()], None),
+    );
+    assert_eq!(proof.address, address);
+    assert!(!proof.account_proof.is_empty());
+    assert_eq!(proof.storage_proof.len(), 1);
+    assert_eq!(proof.storage_proof[0].key, H256::zero());
+    assert_eq!(proof.storage_proof[0].value, U256::zero());
+}
+
 #[test]
 fn test_eth_sign() {
     let mut client = ConnectionWrapper::new_from_env(None);
@@ -428,6 +562,53 @@ fn test_eth_sign() {
     );
 }
 
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_sign_typed_data_v4() {
+    // geth only signs typed data through clef, so this needs a node which supports it
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let address = match import_account(&mut client, H256::try_from(FIX_SECRET).unwrap()) {
+        Ok(a) => a,
+        Err(_) => Address::try_from("0xdc677f7c5060b0b441d30f361d0c8529ac04e099").unwrap(),
+    };
+    let field = |name: &str, field_type: &str| TypedDataField {
+        name: String::from(name),
+        field_type: String::from(field_type),
+    };
+    let mut types = std::collections::HashMap::new();
+    types.insert(
+        String::from("EIP712Domain"),
+        vec![field("name", "string"), field("chainId", "uint256")],
+    );
+    types.insert(
+        String::from("Mail"),
+        vec![field("to", "address"), field("contents", "string")],
+    );
+    let typed_data = TypedData {
+        types,
+        primary_type: String::from("Mail"),
+        domain: TypedDataDomain {
+            name: Some(String::from("Ethane")),
+            chain_id: Some(U64::from_int_unchecked(1337_u16)),
+            ..Default::default()
+        },
+        message: serde_json::json!({ "to": ADDRESS2, "contents": "checkmate" }),
+    };
+    client
+        .call(rpc::personal_unlock_account(
+            address,
+            String::from(ACCOUNTS_PASSWORD),
+            None,
+        ))
+        .unwrap();
+
+    let signature = rpc_call_with_return(
+        &mut client,
+        rpc::eth_sign_typed_data_v4(address, typed_data),
+    );
+    assert_eq!(signature.0.len(), 65);
+}
+
 // DEVIATION FROM SPEC
 // c.f. https://github.com/ethereum/go-ethereum/issues/22223
 // also geth returns something like: {raw: hex_encoded_tx, tx: json_encoded_tx}, however according to JSON RPC
@@ -507,6 +688,23 @@ fn test_eth_estimate_gas() {
     );
 }
 
+#[test]
+#[ignore] // @TODO not supported
+fn test_eth_create_access_list() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let gas_call = GasCall {
+        from: Some(create_account(&mut client).1),
+        to: Some(create_account(&mut client).1),
+        value: Some(U256::zero()),
+        ..Default::default()
+    };
+    let access_list =
+        rpc_call_with_return(&mut client, rpc::eth_create_access_list(gas_call, None));
+    assert!(access_list.access_list.is_empty());
+    assert_eq!(access_list.gas_used, U256::from_int_unchecked(21000_u32));
+    assert_eq!(access_list.error, None);
+}
+
 #[test]
 fn test_eth_get_block_by_hash() {
     let mut client = ConnectionWrapper::new_from_env(None);
@@ -0,0 +1,104 @@
+use ethane::logs::FilterBuilder;
+use ethane::rpc;
+use ethane::types::*;
+use std::path::Path;
+
+use test_helper::*;
+
+#[test]
+fn test_watch_blocks() {
+    // both clients talk to the same node
+    let mut client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let mut watching_client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let mut watcher = watching_client.watch_blocks().unwrap();
+
+    let tx = TransactionRequest {
+        from: create_account(&mut client).1,
+        to: Some(create_account(&mut client).1),
+        value: Some(U256::zero()),
+        ..Default::default()
+    };
+    let tx_hash = client.call(rpc::eth_send_transaction(tx)).unwrap();
+    wait_for_transaction(&mut client, tx_hash);
+    let tx_receipt =
+        rpc_call_with_return(&mut client, rpc::eth_get_transaction_receipt(tx_hash)).unwrap();
+
+    loop {
+        if watcher.next_item().unwrap() == tx_receipt.block_hash {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_watch_logs() {
+    let mut client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let mut watching_client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let address = create_account(&mut client).1;
+    let (contract_address, _) = deploy_contract(
+        &mut client,
+        address,
+        Path::new(TEST_CONTRACT_PATH),
+        TEST_CONTRACT_NAME,
+    );
+    let topic = H256::from(&keccak(b"Solution(uint256)"));
+    let filter = Filter {
+        address: Some(ValueOrVec::Value(contract_address)),
+        topics: Some(vec![Some(ValueOrVec::Value(topic))]),
+        ..Default::default()
+    };
+    let mut watcher = watching_client.watch_logs(filter).unwrap();
+
+    let out = keccak(b"set_pos0()");
+    let tx = TransactionRequest {
+        from: create_account(&mut client).1,
+        to: Some(contract_address),
+        data: Some(Bytes::from_slice(&out[..4])),
+        ..Default::default()
+    };
+    let tx_hash = client.call(rpc::eth_send_transaction(tx)).unwrap();
+    wait_for_transaction(&mut client, tx_hash);
+
+    let log = watcher.next_item().unwrap();
+    assert_eq!(log.address, contract_address);
+    assert_eq!(log.topics[0], topic);
+    assert_eq!(log.transaction_hash, Some(tx_hash));
+}
+
+#[test]
+fn test_filter_builder() {
+    let mut client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let address = create_account(&mut client).1;
+    let (contract_address, _) = deploy_contract(
+        &mut client,
+        address,
+        Path::new(TEST_CONTRACT_PATH),
+        TEST_CONTRACT_NAME,
+    );
+    let out = keccak(b"set_pos0()");
+    let tx = TransactionRequest {
+        from: create_account(&mut client).1,
+        to: Some(contract_address),
+        data: Some(Bytes::from_slice(&out[..4])),
+        ..Default::default()
+    };
+    let tx_hash = client.call(rpc::eth_send_transaction(tx)).unwrap();
+    wait_for_transaction(&mut client, tx_hash);
+    let tx_receipt =
+        rpc_call_with_return(&mut client, rpc::eth_get_transaction_receipt(tx_hash)).unwrap();
+
+    let filter = FilterBuilder::new()
+        .address(contract_address)
+        .event("Solution(uint256 solution)")
+        .from_block(BlockParameter::Custom(tx_receipt.block_number))
+        .to_block(BlockParameter::Latest);
+    let logs = rpc_call_with_return(&mut client, rpc::eth_get_logs(filter.into()));
+    assert_eq!(logs.len(), 1);
+    match &logs[0] {
+        HashOrLog::Log(log) => {
+            assert_eq!(log.topics[0], H256::from(&keccak(b"Solution(uint256)")));
+            assert_eq!(log.transaction_hash, Some(tx_hash));
+        }
+        HashOrLog::H256(_) => panic!("Expected a log"),
+    }
+}
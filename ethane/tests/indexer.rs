@@ -0,0 +1,50 @@
+use ethane::logs::{FilterBuilder, Indexer, IndexerEvent, MemoryCheckpoint};
+use ethane::rpc;
+use ethane::types::*;
+use ethane_abi::Abi;
+use std::path::Path;
+
+use test_helper::*;
+
+#[test]
+fn test_indexer_backfill() {
+    let mut client = ConnectionWrapper::new_from_env(Some("ganache"));
+    let address = create_account(&mut client).1;
+    let (contract_address, abi_json) = deploy_contract(
+        &mut client,
+        address,
+        Path::new(TEST_CONTRACT_PATH),
+        TEST_CONTRACT_NAME,
+    );
+    let start_block = rpc_call_with_return(&mut client, rpc::eth_block_number());
+    let out = keccak(b"set_pos0()");
+    let tx = TransactionRequest {
+        from: address,
+        to: Some(contract_address),
+        data: Some(Bytes::from_slice(&out[..4])),
+        ..Default::default()
+    };
+    let tx_hash = client.call(rpc::eth_send_transaction(tx)).unwrap();
+    wait_for_transaction(&mut client, tx_hash);
+
+    let mut abi = Abi::new();
+    abi.parse_json(abi_json).unwrap();
+    let filter = FilterBuilder::new().address(contract_address);
+    let mut indexer = Indexer::new(abi, filter, MemoryCheckpoint::default())
+        .start_block(u64::from_be_bytes(start_block.into_bytes()));
+    let mut events = Vec::new();
+    let head = client
+        .backfill(&mut indexer, &mut |event| {
+            events.push(event);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(indexer.last_block(), Some(head));
+    match &events[..] {
+        [IndexerEvent::Applied(log)] => {
+            assert_eq!(log.event.name, "Solution");
+            assert_eq!(log.log.transaction_hash, Some(tx_hash));
+        }
+        events => panic!("Expected one applied log, got {:?}", events),
+    }
+}
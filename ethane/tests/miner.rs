@@ -0,0 +1,52 @@
+use ethane::rpc;
+use ethane::types::{Address, U256};
+use std::convert::TryFrom;
+
+use test_helper::*;
+
+const ADDRESS1: &str = "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f";
+
+/// Starts mining again if a test fails after stopping it, the other tests wait for blocks
+struct RestartMining;
+
+impl Drop for RestartMining {
+    fn drop(&mut self) {
+        let mut client = ConnectionWrapper::new_from_env(None);
+        let _ = client.call(rpc::miner_start(None));
+    }
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the miner API
+fn test_miner_stop_and_start() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let _restart = RestartMining;
+    rpc_call_test_expected(&mut client, rpc::miner_stop(), ());
+    rpc_call_test_expected(&mut client, rpc::eth_mining(), false);
+    rpc_call_test_expected(&mut client, rpc::miner_start(None), ());
+    rpc_call_test_expected(&mut client, rpc::eth_mining(), true);
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the miner API
+fn test_miner_set_etherbase() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let coinbase = rpc_call_with_return(&mut client, rpc::eth_coinbase());
+    rpc_call_test_expected(
+        &mut client,
+        rpc::miner_set_etherbase(Address::try_from(ADDRESS1).unwrap()),
+        true,
+    );
+    rpc_call_test_expected(&mut client, rpc::miner_set_etherbase(coinbase), true);
+}
+
+#[test]
+#[ignore] // @TODO the CI node does not expose the miner API
+fn test_miner_set_gas_price() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_expected(
+        &mut client,
+        rpc::miner_set_gas_price(U256::from_int_unchecked(1_u8)),
+        true,
+    );
+}
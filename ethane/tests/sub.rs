@@ -1,4 +1,4 @@
-use ethane::rpc::eth_send_transaction;
+use ethane::rpc::{eth_send_transaction, SubscriptionRequest};
 use ethane::rpc::{
     eth_subscribe_logs, eth_subscribe_new_heads, eth_subscribe_new_pending_transactions,
     eth_subscribe_syncing,
@@ -33,6 +33,22 @@ fn test_eth_subscribe_new_heads() {
     }
 }
 
+#[test]
+#[ignore] // @TODO not supported
+fn test_custom_subscription() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let mut subscription = client
+        .subscribe(SubscriptionRequest::<serde_json::Value>::custom(
+            "eth_subscribe",
+            "newHeads",
+        ))
+        .unwrap();
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    let block = subscription.next_item().unwrap();
+    assert!(block["number"].is_string());
+}
+
 #[test]
 #[ignore] // @TODO not supported
 fn test_eth_subscribe_new_pending_transactions() {
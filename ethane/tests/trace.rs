@@ -0,0 +1,37 @@
+use ethane::rpc;
+use ethane::types::*;
+use std::convert::TryFrom;
+
+use test_helper::*;
+
+const ADDRESS1: &str = "0x007ccffb7916f37f7aeef05e8096ecfbe55afc2f";
+
+#[test]
+#[ignore] // @TODO not supported by geth
+fn test_trace_block() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    rpc_call_test_some(&mut client, rpc::trace_block(BlockParameter::Latest));
+}
+
+#[test]
+#[ignore] // @TODO not supported by geth
+fn test_trace_filter() {
+    let mut client = ConnectionWrapper::new_from_env(None);
+    let sender = create_account(&mut client).1;
+    prefund_account(&mut client, sender);
+    simulate_transaction(&mut client, sender, ADDRESS1, U256::zero());
+
+    let filter = TraceFilter {
+        from_block: Some(BlockParameter::Earliest),
+        from_address: Some(vec![sender]),
+        ..Default::default()
+    };
+    let traces = rpc_call_with_return(&mut client, rpc::trace_filter(filter));
+    assert_eq!(traces.len(), 1);
+    match &traces[0].action {
+        TraceAction::Call(action) => {
+            assert_eq!(action.to, Address::try_from(ADDRESS1).unwrap())
+        }
+        action => panic!("Expected a call, got {:?}", action),
+    }
+}